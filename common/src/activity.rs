@@ -0,0 +1,193 @@
+use std::collections::HashSet;
+
+use nssa::{AUTHENTICATED_TRANSFER_ID, AccountId, program::Program};
+use serde::{Deserialize, Serialize};
+
+use crate::transaction::NSSATransaction;
+
+/// Number of latest blocks taken into account when computing activity
+pub const ACTIVITY_WINDOW_BLOCKS: u64 = 100;
+
+/// Number of transactions in the window, which is not penalized
+pub const ACTIVITY_FREE_TX_COUNT: u32 = 20;
+
+/// Account activity over the last `ACTIVITY_WINDOW_BLOCKS` blocks, used for spam detection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityScore {
+    pub tx_count_last_100_blocks: u32,
+    pub unique_recipients: usize,
+    /// Average amount of native token transfers sent by account
+    pub avg_amount: u128,
+    /// Higher is more legitimate
+    pub score: u32,
+}
+
+impl ActivityScore {
+    /// Computes activity of `account_id` from `(block_id, transaction)` pairs of transactions,
+    /// in which account appears
+    pub fn compute(
+        account_id: &AccountId,
+        transactions: &[(u64, NSSATransaction)],
+        chain_height: u64,
+    ) -> Self {
+        let recent_transactions: Vec<_> = transactions
+            .iter()
+            .filter(|(block_id, _)| block_id + ACTIVITY_WINDOW_BLOCKS > chain_height)
+            .map(|(_, tx)| tx)
+            .collect();
+
+        let sent_transfers: Vec<_> = recent_transactions
+            .iter()
+            .filter_map(|tx| {
+                let NSSATransaction::Public(tx) = tx else {
+                    return None;
+                };
+                let message = tx.message();
+                if message.program_id() != AUTHENTICATED_TRANSFER_ID {
+                    return None;
+                }
+                let [from, to] = message.account_ids() else {
+                    return None;
+                };
+                if from != account_id {
+                    return None;
+                }
+                let amount = Program::deserialize_instruction(message.instruction_data()).ok()?;
+                Some((*to, amount))
+            })
+            .collect();
+
+        Self::from_sent_transfers(recent_transactions.len() as u32, &sent_transfers)
+    }
+
+    /// `score = 100 + recipient_diversity + value_bonus - frequency_penalty`, saturating at zero,
+    /// where
+    ///
+    /// `recipient_diversity = 100 * unique_recipients / sent_transfers`,
+    ///
+    /// `value_bonus = min(avg_amount, 100)`,
+    ///
+    /// `frequency_penalty = 5 * (tx_count - ACTIVITY_FREE_TX_COUNT)` for counts above the free one
+    pub fn from_sent_transfers(tx_count: u32, sent_transfers: &[(AccountId, u128)]) -> Self {
+        let unique_recipients = sent_transfers
+            .iter()
+            .map(|(to, _)| to)
+            .collect::<HashSet<_>>()
+            .len();
+
+        let avg_amount = if sent_transfers.is_empty() {
+            0
+        } else {
+            sent_transfers
+                .iter()
+                .map(|(_, amount)| *amount)
+                .fold(0u128, u128::saturating_add)
+                / sent_transfers.len() as u128
+        };
+
+        let recipient_diversity = if sent_transfers.is_empty() {
+            0
+        } else {
+            (100 * unique_recipients / sent_transfers.len()) as u32
+        };
+        let value_bonus = avg_amount.min(100) as u32;
+        let frequency_penalty = tx_count
+            .saturating_sub(ACTIVITY_FREE_TX_COUNT)
+            .saturating_mul(5);
+
+        let score = (100 + recipient_diversity + value_bonus).saturating_sub(frequency_penalty);
+
+        Self {
+            tx_count_last_100_blocks: tx_count,
+            unique_recipients,
+            avg_amount,
+            score,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_without_activity() {
+        let activity = ActivityScore::from_sent_transfers(0, &[]);
+
+        assert_eq!(
+            activity,
+            ActivityScore {
+                tx_count_last_100_blocks: 0,
+                unique_recipients: 0,
+                avg_amount: 0,
+                score: 100,
+            }
+        );
+    }
+
+    #[test]
+    fn test_score_formula() {
+        let transfers = [
+            (AccountId::new([1; 32]), 10),
+            (AccountId::new([2; 32]), 20),
+            (AccountId::new([1; 32]), 30),
+            (AccountId::new([3; 32]), 40),
+        ];
+
+        let activity = ActivityScore::from_sent_transfers(4, &transfers);
+
+        assert_eq!(activity.unique_recipients, 3);
+        assert_eq!(activity.avg_amount, 25);
+        // 100 + 100 * 3 / 4 + 25
+        assert_eq!(activity.score, 200);
+    }
+
+    #[test]
+    fn test_spammer_has_lower_score() {
+        let legitimate_transfers: Vec<_> =
+            (0..5).map(|i| (AccountId::new([i; 32]), 1000)).collect();
+        let spam_transfers: Vec<_> = (0..50).map(|_| (AccountId::new([1; 32]), 1)).collect();
+
+        let legitimate = ActivityScore::from_sent_transfers(5, &legitimate_transfers);
+        let spammer = ActivityScore::from_sent_transfers(50, &spam_transfers);
+
+        assert_eq!(legitimate.score, 300);
+        // 100 + 100 * 1 / 50 + 1 - 5 * 30
+        assert_eq!(spammer.score, 0);
+        assert!(legitimate.score > spammer.score);
+    }
+
+    #[test]
+    fn test_compute_from_transactions() {
+        let signing_key = nssa::PrivateKey::try_new([1; 32]).unwrap();
+        let account_id = AccountId::from(&nssa::PublicKey::new_from_private_key(&signing_key));
+
+        let transactions: Vec<_> = [(1, [2; 32], 10), (150, [3; 32], 20), (160, [3; 32], 40)]
+            .into_iter()
+            .enumerate()
+            .map(|(nonce, (block_id, to, amount))| {
+                let tx = crate::test_utils::create_transaction_native_token_transfer(
+                    *account_id.value(),
+                    nonce as u128,
+                    to,
+                    amount,
+                    signing_key.clone(),
+                );
+                (block_id, NSSATransaction::try_from(&tx).unwrap())
+            })
+            .collect();
+
+        let activity = ActivityScore::compute(&account_id, &transactions, 200);
+
+        // Transaction in block 1 is out of window
+        assert_eq!(
+            activity,
+            ActivityScore::from_sent_transfers(
+                2,
+                &[(AccountId::new([3; 32]), 20), (AccountId::new([3; 32]), 40)]
+            )
+        );
+        assert_eq!(activity.avg_amount, 30);
+        assert_eq!(activity.unique_recipients, 1);
+    }
+}
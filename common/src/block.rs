@@ -23,7 +23,7 @@ pub type BlockHash = [u8; 32];
 pub type BlockId = u64;
 pub type TimeStamp = u64;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub struct BlockHeader {
     pub block_id: BlockId,
     pub prev_block_hash: BlockHash,
@@ -32,17 +32,33 @@ pub struct BlockHeader {
     pub signature: nssa::Signature,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub struct BlockBody {
     pub transactions: Vec<EncodedTransaction>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub struct Block {
     pub header: BlockHeader,
     pub body: BlockBody,
 }
 
+impl Block {
+    /// Checks that header hash is the hash of block data and the data is signed by `public_key`
+    pub fn is_valid_for(&self, public_key: &nssa::PublicKey) -> bool {
+        let data_bytes = borsh::to_vec(&HashableBlockDataRef {
+            block_id: self.header.block_id,
+            prev_block_hash: self.header.prev_block_hash,
+            timestamp: self.header.timestamp,
+            transactions: &self.body.transactions,
+        })
+        .unwrap();
+
+        OwnHasher::hash(&data_bytes) == self.header.hash
+            && self.header.signature.is_valid_for(&data_bytes, public_key)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub struct HashableBlockData {
     pub block_id: BlockId,
@@ -51,6 +67,15 @@ pub struct HashableBlockData {
     pub transactions: Vec<EncodedTransaction>,
 }
 
+/// Borrowed [`HashableBlockData`], encodes to the same bytes
+#[derive(BorshSerialize)]
+struct HashableBlockDataRef<'a> {
+    block_id: BlockId,
+    prev_block_hash: BlockHash,
+    timestamp: TimeStamp,
+    transactions: &'a [EncodedTransaction],
+}
+
 impl HashableBlockData {
     pub fn into_block(self, signing_key: &nssa::PrivateKey) -> Block {
         let data_bytes = borsh::to_vec(&self).unwrap();
@@ -84,7 +109,24 @@ impl From<Block> for HashableBlockData {
 
 #[cfg(test)]
 mod tests {
-    use crate::{block::HashableBlockData, test_utils};
+    use crate::{
+        block::{Block, HashableBlockData},
+        test_utils,
+        transaction::{EncodedTransaction, TxKind},
+    };
+
+    fn signed_block(signing_key: &nssa::PrivateKey) -> Block {
+        HashableBlockData {
+            block_id: 2,
+            prev_block_hash: [1; 32],
+            timestamp: 3,
+            transactions: vec![EncodedTransaction {
+                tx_kind: TxKind::Public,
+                encoded_transaction_data: vec![1, 2, 3],
+            }],
+        }
+        .into_block(signing_key)
+    }
 
     #[test]
     fn test_encoding_roundtrip() {
@@ -95,4 +137,51 @@ mod tests {
         let block_from_bytes = borsh::from_slice::<HashableBlockData>(&bytes).unwrap();
         assert_eq!(hashable, block_from_bytes);
     }
+
+    #[test]
+    fn test_block_is_valid_for_signer() {
+        let signing_key = nssa::PrivateKey::try_new([1; 32]).unwrap();
+        let public_key = nssa::PublicKey::new_from_private_key(&signing_key);
+        let block = signed_block(&signing_key);
+
+        assert!(block.is_valid_for(&public_key));
+
+        let other_key = nssa::PrivateKey::try_new([2; 32]).unwrap();
+        assert!(!block.is_valid_for(&nssa::PublicKey::new_from_private_key(&other_key)));
+    }
+
+    #[test]
+    fn test_tampered_block_is_invalid() {
+        let signing_key = nssa::PrivateKey::try_new([1; 32]).unwrap();
+        let public_key = nssa::PublicKey::new_from_private_key(&signing_key);
+
+        let mut block = signed_block(&signing_key);
+        block.header.timestamp += 1;
+        assert!(!block.is_valid_for(&public_key));
+
+        let mut block = signed_block(&signing_key);
+        block.body.transactions.clear();
+        assert!(!block.is_valid_for(&public_key));
+
+        // Hash matching tampered data is still not signed
+        let mut block = signed_block(&signing_key);
+        let other_block = signed_block(&nssa::PrivateKey::try_new([2; 32]).unwrap());
+        block.header.signature = other_block.header.signature;
+        assert!(!block.is_valid_for(&public_key));
+    }
+
+    #[test]
+    fn test_signed_block_encoding_roundtrip() {
+        let signing_key = nssa::PrivateKey::try_new([1; 32]).unwrap();
+        let block = signed_block(&signing_key);
+
+        let bytes = borsh::to_vec(&block).unwrap();
+        let block_from_bytes = borsh::from_slice::<Block>(&bytes).unwrap();
+
+        assert_eq!(block_from_bytes.header.hash, block.header.hash);
+        assert_eq!(block_from_bytes.header.signature, block.header.signature);
+        assert!(
+            block_from_bytes.is_valid_for(&nssa::PublicKey::new_from_private_key(&signing_key))
+        );
+    }
 }
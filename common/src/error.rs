@@ -49,4 +49,6 @@ pub enum ExecutionFailureKind {
     SequencerClientError(#[from] SequencerClientError),
     #[error("Can not pay for operation")]
     InsufficientFundsError,
+    #[error("Invalid transaction message: {0}")]
+    InvalidMessage(#[from] nssa::public_transaction::MessageValidationError),
 }
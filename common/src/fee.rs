@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// Fee schedule of public transactions.
+///
+/// Fee is `base_fee` plus `fee_per_kb` for every started KiB of encoded transaction data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeParams {
+    pub base_fee: u128,
+    pub fee_per_kb: u128,
+}
+
+impl FeeParams {
+    pub fn fee_for_size(&self, tx_size_bytes: usize) -> u128 {
+        let started_kbs = tx_size_bytes.div_ceil(1024) as u128;
+        self.base_fee
+            .saturating_add(started_kbs.saturating_mul(self.fee_per_kb))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_for_size() {
+        let fee_params = FeeParams {
+            base_fee: 10,
+            fee_per_kb: 3,
+        };
+
+        assert_eq!(fee_params.fee_for_size(0), 10);
+        assert_eq!(fee_params.fee_for_size(1), 13);
+        assert_eq!(fee_params.fee_for_size(1024), 13);
+        assert_eq!(fee_params.fee_for_size(1025), 16);
+        assert!(fee_params.fee_for_size(10 * 1024) > fee_params.fee_for_size(2 * 1024));
+    }
+
+    #[test]
+    fn test_default_fee_params_are_free() {
+        assert_eq!(FeeParams::default().fee_for_size(100 * 1024), 0);
+    }
+}
@@ -1,5 +1,7 @@
+pub mod activity;
 pub mod block;
 pub mod error;
+pub mod fee;
 pub mod rpc_primitives;
 pub mod sequencer_client;
 pub mod transaction;
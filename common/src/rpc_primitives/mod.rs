@@ -8,13 +8,28 @@ pub mod requests;
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct RpcLimitsConfig {
     /// Maximum byte size of the json payload.
+    #[serde(default = "default_json_payload_max_size")]
     pub json_payload_max_size: usize,
+    /// Maximum number of read requests per second from a single IP, `0` (default) disables the
+    /// limit.
+    #[serde(default)]
+    pub max_requests_per_second_per_ip: u32,
+    /// Maximum number of transaction submissions per second from a single IP, `0` (default)
+    /// disables the limit.
+    #[serde(default)]
+    pub max_write_requests_per_second_per_ip: u32,
+}
+
+fn default_json_payload_max_size() -> usize {
+    10 * 1024 * 1024
 }
 
 impl Default for RpcLimitsConfig {
     fn default() -> Self {
         Self {
-            json_payload_max_size: 10 * 1024 * 1024,
+            json_payload_max_size: default_json_payload_max_size(),
+            max_requests_per_second_per_ip: 0,
+            max_write_requests_per_second_per_ip: 0,
         }
     }
 }
@@ -8,7 +8,7 @@ use super::{
     errors::RpcParseError,
     parser::{RpcRequest, parse_params},
 };
-use crate::parse_request;
+use crate::{HashType, activity::ActivityScore, fee::FeeParams, parse_request};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct HelloRequest {}
@@ -29,6 +29,9 @@ pub struct GetBlockDataRequest {
     pub block_id: u64,
 }
 
+/// Maximal number of blocks, which can be requested by single `GetBlockRangeDataRequest`
+pub const MAX_BLOCK_RANGE_SIZE: u64 = 256;
+
 /// Get a range of blocks from `start_block_id` to `end_block_id` (inclusive)
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetBlockRangeDataRequest {
@@ -39,6 +42,9 @@ pub struct GetBlockRangeDataRequest {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetGenesisIdRequest {}
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetGenesisHashRequest {}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetLastBlockRequest {}
 
@@ -70,23 +76,59 @@ pub struct GetProofForCommitmentRequest {
     pub commitment: nssa_core::Commitment,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetProofForNullifierRequest {
+    pub nullifier: nssa_core::Nullifier,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetProgramIdsRequest {}
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetAccountActivityRequest {
+    pub account_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetFeeParamsRequest {}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EstimateFeeRequest {
+    #[serde(with = "base64_deser")]
+    pub transaction: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PauseBlockProductionRequest {
+    pub admin_token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResumeBlockProductionRequest {
+    pub admin_token: String,
+}
+
 parse_request!(HelloRequest);
 parse_request!(RegisterAccountRequest);
 parse_request!(SendTxRequest);
 parse_request!(GetBlockDataRequest);
 parse_request!(GetBlockRangeDataRequest);
 parse_request!(GetGenesisIdRequest);
+parse_request!(GetGenesisHashRequest);
 parse_request!(GetLastBlockRequest);
 parse_request!(GetInitialTestnetAccountsRequest);
 parse_request!(GetAccountBalanceRequest);
 parse_request!(GetTransactionByHashRequest);
 parse_request!(GetAccountsNoncesRequest);
 parse_request!(GetProofForCommitmentRequest);
+parse_request!(GetProofForNullifierRequest);
 parse_request!(GetAccountRequest);
 parse_request!(GetProgramIdsRequest);
+parse_request!(GetAccountActivityRequest);
+parse_request!(GetFeeParamsRequest);
+parse_request!(EstimateFeeRequest);
+parse_request!(PauseBlockProductionRequest);
+parse_request!(ResumeBlockProductionRequest);
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct HelloResponse {
@@ -175,6 +217,11 @@ pub struct GetGenesisIdResponse {
     pub genesis_id: u64,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetGenesisHashResponse {
+    pub genesis_hash: HashType,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetLastBlockResponse {
     pub last_block: u64,
@@ -205,11 +252,38 @@ pub struct GetProofForCommitmentResponse {
     pub membership_proof: Option<nssa_core::MembershipProof>,
 }
 
+/// Membership proof of spent nullifier together with nullifier set root
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetProofForNullifierResponse {
+    pub membership_proof: Option<(nssa_core::MembershipProof, [u8; 32])>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetProgramIdsResponse {
     pub program_ids: HashMap<String, ProgramId>,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetAccountActivityResponse {
+    pub activity: ActivityScore,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetFeeParamsResponse {
+    pub fee_params: FeeParams,
+}
+
+/// Minimal fee for the transaction to be accepted by sequencer
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EstimateFeeResponse {
+    pub fee: u128,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BlockProductionStatusResponse {
+    pub paused: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GetInitialTestnetAccountsResponse {
     /// Hex encoded account id
@@ -8,18 +8,22 @@ use serde_json::Value;
 
 use super::rpc_primitives::requests::{
     GetAccountBalanceRequest, GetAccountBalanceResponse, GetBlockDataRequest, GetBlockDataResponse,
-    GetGenesisIdRequest, GetGenesisIdResponse, GetInitialTestnetAccountsRequest,
+    GetGenesisHashRequest, GetGenesisHashResponse, GetGenesisIdRequest, GetGenesisIdResponse,
+    GetInitialTestnetAccountsRequest,
 };
 use crate::{
     error::{SequencerClientError, SequencerRpcError},
     rpc_primitives::{
         self,
         requests::{
-            GetAccountRequest, GetAccountResponse, GetAccountsNoncesRequest,
-            GetAccountsNoncesResponse, GetBlockRangeDataRequest, GetBlockRangeDataResponse,
+            EstimateFeeRequest, EstimateFeeResponse, GetAccountActivityRequest,
+            GetAccountActivityResponse, GetAccountRequest, GetAccountResponse,
+            GetAccountsNoncesRequest, GetAccountsNoncesResponse, GetBlockRangeDataRequest,
+            GetBlockRangeDataResponse, GetFeeParamsRequest, GetFeeParamsResponse,
             GetInitialTestnetAccountsResponse, GetLastBlockRequest, GetLastBlockResponse,
             GetProgramIdsRequest, GetProgramIdsResponse, GetProofForCommitmentRequest,
-            GetProofForCommitmentResponse, GetTransactionByHashRequest,
+            GetProofForCommitmentResponse, GetProofForNullifierRequest,
+            GetProofForNullifierResponse, GetTransactionByHashRequest,
             GetTransactionByHashResponse, SendTxRequest, SendTxResponse,
         },
     },
@@ -178,6 +182,56 @@ impl SequencerClient {
         Ok(resp_deser)
     }
 
+    /// Get activity of account for spam detection
+    pub async fn get_account_activity(
+        &self,
+        account_id: String,
+    ) -> Result<GetAccountActivityResponse, SequencerClientError> {
+        let activity_req = GetAccountActivityRequest { account_id };
+
+        let req = serde_json::to_value(activity_req)?;
+
+        let resp = self
+            .call_method_with_payload("get_account_activity", req)
+            .await?;
+
+        let resp_deser = serde_json::from_value(resp)?;
+
+        Ok(resp_deser)
+    }
+
+    /// Get fee schedule of public transactions
+    pub async fn get_fee_params(&self) -> Result<GetFeeParamsResponse, SequencerClientError> {
+        let fee_params_req = GetFeeParamsRequest {};
+
+        let req = serde_json::to_value(fee_params_req)?;
+
+        let resp = self.call_method_with_payload("get_fee_params", req).await?;
+
+        let resp_deser = serde_json::from_value(resp)?;
+
+        Ok(resp_deser)
+    }
+
+    /// Get minimal fee, which sequencer requires for `transaction`
+    pub async fn estimate_fee(
+        &self,
+        transaction: &EncodedTransaction,
+    ) -> Result<EstimateFeeResponse, SequencerClientError> {
+        let estimate_req = EstimateFeeRequest {
+            transaction: borsh::to_vec(transaction)
+                .expect("derived BorshSerialize should never fail"),
+        };
+
+        let req = serde_json::to_value(estimate_req)?;
+
+        let resp = self.call_method_with_payload("estimate_fee", req).await?;
+
+        let resp_deser = serde_json::from_value(resp)?;
+
+        Ok(resp_deser)
+    }
+
     /// Get transaction details for `hash`.
     pub async fn get_transaction_by_hash(
         &self,
@@ -204,7 +258,8 @@ impl SequencerClient {
         let transaction = EncodedTransaction::from(NSSATransaction::Public(transaction));
 
         let tx_req = SendTxRequest {
-            transaction: borsh::to_vec(&transaction).unwrap(),
+            transaction: borsh::to_vec(&transaction)
+                .expect("derived BorshSerialize should never fail"),
         };
 
         let req = serde_json::to_value(tx_req)?;
@@ -224,7 +279,8 @@ impl SequencerClient {
         let transaction = EncodedTransaction::from(NSSATransaction::PrivacyPreserving(transaction));
 
         let tx_req = SendTxRequest {
-            transaction: borsh::to_vec(&transaction).unwrap(),
+            transaction: borsh::to_vec(&transaction)
+                .expect("derived BorshSerialize should never fail"),
         };
 
         let req = serde_json::to_value(tx_req)?;
@@ -240,14 +296,26 @@ impl SequencerClient {
     pub async fn get_genesis_id(&self) -> Result<GetGenesisIdResponse, SequencerClientError> {
         let genesis_req = GetGenesisIdRequest {};
 
-        let req = serde_json::to_value(genesis_req).unwrap();
+        let req = serde_json::to_value(genesis_req)?;
+
+        let resp = self.call_method_with_payload("get_genesis", req).await?;
+
+        let resp_deser = serde_json::from_value(resp)?;
+
+        Ok(resp_deser)
+    }
+
+    /// Get genesis hash from sequencer, identifying the chain it runs
+    pub async fn get_genesis_hash(&self) -> Result<GetGenesisHashResponse, SequencerClientError> {
+        let genesis_req = GetGenesisHashRequest {};
+
+        let req = serde_json::to_value(genesis_req)?;
 
         let resp = self
-            .call_method_with_payload("get_genesis", req)
-            .await
-            .unwrap();
+            .call_method_with_payload("get_genesis_hash", req)
+            .await?;
 
-        let resp_deser = serde_json::from_value(resp).unwrap();
+        let resp_deser = serde_json::from_value(resp)?;
 
         Ok(resp_deser)
     }
@@ -258,14 +326,13 @@ impl SequencerClient {
     ) -> Result<Vec<GetInitialTestnetAccountsResponse>, SequencerClientError> {
         let acc_req = GetInitialTestnetAccountsRequest {};
 
-        let req = serde_json::to_value(acc_req).unwrap();
+        let req = serde_json::to_value(acc_req)?;
 
         let resp = self
             .call_method_with_payload("get_initial_testnet_accounts", req)
-            .await
-            .unwrap();
+            .await?;
 
-        let resp_deser = serde_json::from_value(resp).unwrap();
+        let resp_deser = serde_json::from_value(resp)?;
 
         Ok(resp_deser)
     }
@@ -277,16 +344,33 @@ impl SequencerClient {
     ) -> Result<Option<nssa_core::MembershipProof>, SequencerClientError> {
         let acc_req = GetProofForCommitmentRequest { commitment };
 
-        let req = serde_json::to_value(acc_req).unwrap();
+        let req = serde_json::to_value(acc_req)?;
 
         let resp = self
             .call_method_with_payload("get_proof_for_commitment", req)
-            .await
-            .unwrap();
+            .await?;
 
-        let resp_deser = serde_json::from_value::<GetProofForCommitmentResponse>(resp)
-            .unwrap()
-            .membership_proof;
+        let resp_deser =
+            serde_json::from_value::<GetProofForCommitmentResponse>(resp)?.membership_proof;
+
+        Ok(resp_deser)
+    }
+
+    /// Get proof for spent nullifier together with current nullifier set root
+    pub async fn get_proof_for_nullifier(
+        &self,
+        nullifier: nssa_core::Nullifier,
+    ) -> Result<Option<(nssa_core::MembershipProof, [u8; 32])>, SequencerClientError> {
+        let nullifier_req = GetProofForNullifierRequest { nullifier };
+
+        let req = serde_json::to_value(nullifier_req)?;
+
+        let resp = self
+            .call_method_with_payload("get_proof_for_nullifier", req)
+            .await?;
+
+        let resp_deser =
+            serde_json::from_value::<GetProofForNullifierResponse>(resp)?.membership_proof;
 
         Ok(resp_deser)
     }
@@ -298,7 +382,8 @@ impl SequencerClient {
         let transaction = EncodedTransaction::from(NSSATransaction::ProgramDeployment(transaction));
 
         let tx_req = SendTxRequest {
-            transaction: borsh::to_vec(&transaction).unwrap(),
+            transaction: borsh::to_vec(&transaction)
+                .expect("derived BorshSerialize should never fail"),
         };
 
         let req = serde_json::to_value(tx_req)?;
@@ -316,17 +401,36 @@ impl SequencerClient {
     ) -> Result<HashMap<String, ProgramId>, SequencerClientError> {
         let acc_req = GetProgramIdsRequest {};
 
-        let req = serde_json::to_value(acc_req).unwrap();
+        let req = serde_json::to_value(acc_req)?;
 
         let resp = self
             .call_method_with_payload("get_program_ids", req)
-            .await
-            .unwrap();
+            .await?;
 
-        let resp_deser = serde_json::from_value::<GetProgramIdsResponse>(resp)
-            .unwrap()
-            .program_ids;
+        let resp_deser = serde_json::from_value::<GetProgramIdsResponse>(resp)?.program_ids;
 
         Ok(resp_deser)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unreachable_sequencer_returns_error() {
+        let client = SequencerClient::new("http://127.0.0.1:1".to_string()).unwrap();
+
+        let genesis_result = client.get_genesis_id().await;
+        let program_ids_result = client.get_program_ids().await;
+
+        assert!(matches!(
+            genesis_result,
+            Err(SequencerClientError::HTTPError(_))
+        ));
+        assert!(matches!(
+            program_ids_result,
+            Err(SequencerClientError::HTTPError(_))
+        ));
+    }
+}
@@ -43,12 +43,13 @@ pub fn produce_dummy_empty_transaction() -> EncodedTransaction {
         account_ids,
         nonces,
         instruction_data,
+        0,
     )
     .unwrap();
     let private_key = nssa::PrivateKey::try_new([1; 32]).unwrap();
     let witness_set = nssa::public_transaction::WitnessSet::for_message(&message, &[&private_key]);
 
-    let nssa_tx = nssa::PublicTransaction::new(message, witness_set);
+    let nssa_tx = nssa::PublicTransaction::new(message, witness_set).unwrap();
 
     EncodedTransaction::from(NSSATransaction::Public(nssa_tx))
 }
@@ -59,6 +60,24 @@ pub fn create_transaction_native_token_transfer(
     to: [u8; 32],
     balance_to_move: u128,
     signing_key: nssa::PrivateKey,
+) -> EncodedTransaction {
+    create_transaction_native_token_transfer_with_fee(
+        from,
+        nonce,
+        to,
+        balance_to_move,
+        0,
+        signing_key,
+    )
+}
+
+pub fn create_transaction_native_token_transfer_with_fee(
+    from: [u8; 32],
+    nonce: u128,
+    to: [u8; 32],
+    balance_to_move: u128,
+    fee: u128,
+    signing_key: nssa::PrivateKey,
 ) -> EncodedTransaction {
     let account_ids = vec![nssa::AccountId::new(from), nssa::AccountId::new(to)];
     let nonces = vec![nonce];
@@ -68,11 +87,12 @@ pub fn create_transaction_native_token_transfer(
         account_ids,
         nonces,
         balance_to_move,
+        fee,
     )
     .unwrap();
     let witness_set = nssa::public_transaction::WitnessSet::for_message(&message, &[&signing_key]);
 
-    let nssa_tx = nssa::PublicTransaction::new(message, witness_set);
+    let nssa_tx = nssa::PublicTransaction::new(message, witness_set).unwrap();
 
     EncodedTransaction::from(NSSATransaction::Public(nssa_tx))
 }
@@ -12,6 +12,17 @@ pub enum NSSATransaction {
     ProgramDeployment(nssa::ProgramDeploymentTransaction),
 }
 
+impl NSSATransaction {
+    /// Returns the ids of all public accounts touched by the transaction
+    pub fn public_account_ids(&self) -> Vec<nssa::AccountId> {
+        match self {
+            NSSATransaction::Public(tx) => tx.message().account_ids().to_vec(),
+            NSSATransaction::PrivacyPreserving(tx) => tx.message().public_account_ids().to_vec(),
+            NSSATransaction::ProgramDeployment(_) => vec![],
+        }
+    }
+}
+
 impl From<nssa::PublicTransaction> for NSSATransaction {
     fn from(value: nssa::PublicTransaction) -> Self {
         Self::Public(value)
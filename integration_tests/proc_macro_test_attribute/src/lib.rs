@@ -1,49 +1,48 @@
 extern crate proc_macro;
 
-use proc_macro::*;
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{ItemFn, parse_macro_input, spanned::Spanned as _};
 
+/// Registers async test function in `function_map`, wrapping it into `pre_test` and `post_test`
 #[proc_macro_attribute]
 pub fn nssa_integration_test(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    let input = item.to_string();
-
-    let fn_keyword = "fn ";
-    let fn_keyword_alternative = "fn\n";
-
-    let mut start_opt = None;
-    let mut fn_name = String::new();
-
-    if let Some(start) = input.find(fn_keyword) {
-        start_opt = Some(start);
-    } else if let Some(start) = input.find(fn_keyword_alternative) {
-        start_opt = Some(start);
+    let item_fn = parse_macro_input!(item as ItemFn);
+
+    if item_fn.sig.asyncness.is_none() {
+        return syn::Error::new(
+            item_fn.sig.fn_token.span(),
+            "nssa_integration_test function must be async",
+        )
+        .to_compile_error()
+        .into();
     }
 
-    if let Some(start) = start_opt {
-        let rest = &input[start + fn_keyword.len()..];
-        if let Some(end) = rest.find(|c: char| c == '(' || c.is_whitespace()) {
-            let name = &rest[..end];
-            fn_name = name.to_string();
-        }
-    } else {
-        println!("ERROR: keyword fn not found");
+    if !item_fn.sig.inputs.is_empty() {
+        return syn::Error::new(
+            item_fn.sig.inputs.span(),
+            "nssa_integration_test function must not take arguments",
+        )
+        .to_compile_error()
+        .into();
     }
 
-    let extension = format!(
-        r#"
-    {input}
+    let fn_ident = &item_fn.sig.ident;
+    let fn_name = fn_ident.to_string();
 
-    function_map.insert("{fn_name}".to_string(), |home_dir: PathBuf| Box::pin(async {{
-        let res = pre_test(home_dir).await.unwrap();
+    quote! {
+        #item_fn
 
-        info!("Waiting for first block creation");
-        tokio::time::sleep(Duration::from_secs(TIME_TO_WAIT_FOR_BLOCK_SECONDS)).await;
+        function_map.insert(#fn_name.to_string(), |home_dir: PathBuf| Box::pin(async {
+            let res = pre_test(home_dir).await.unwrap();
 
-        {fn_name}().await;
+            info!("Waiting for first block creation");
+            tokio::time::sleep(Duration::from_secs(TIME_TO_WAIT_FOR_BLOCK_SECONDS)).await;
 
-        post_test(res).await;
-    }}));
-    "#
-    );
+            #fn_ident().await;
 
-    extension.parse().unwrap()
+            post_test(res).await;
+        }));
+    }
+    .into()
 }
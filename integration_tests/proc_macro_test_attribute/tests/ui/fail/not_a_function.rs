@@ -0,0 +1,6 @@
+use proc_macro_test_attribute::nssa_integration_test;
+
+#[nssa_integration_test]
+struct NotATest;
+
+fn main() {}
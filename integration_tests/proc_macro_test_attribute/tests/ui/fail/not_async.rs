@@ -0,0 +1,6 @@
+use proc_macro_test_attribute::nssa_integration_test;
+
+fn main() {
+    #[nssa_integration_test]
+    pub fn test_not_async() {}
+}
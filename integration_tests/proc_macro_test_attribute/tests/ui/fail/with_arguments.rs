@@ -0,0 +1,6 @@
+use proc_macro_test_attribute::nssa_integration_test;
+
+fn main() {
+    #[nssa_integration_test]
+    pub async fn test_with_arguments(home_dir: std::path::PathBuf) {}
+}
@@ -0,0 +1,20 @@
+use proc_macro_test_attribute::nssa_integration_test;
+
+include!("../test_env.rs");
+
+fn main() {
+    let mut function_map: HashMap<String, TestFunction> = HashMap::new();
+
+    /// Checks that fn keyword in doc comments does not confuse the macro
+    #[nssa_integration_test]
+    #[allow(clippy::unused_async)]
+    pub async fn test_with_attributes() {}
+
+    #[nssa_integration_test]
+    #[doc = "fn not_a_test_name()"]
+    async fn test_fn_in_attribute() {}
+
+    assert!(function_map.contains_key("test_with_attributes"));
+    assert!(function_map.contains_key("test_fn_in_attribute"));
+    assert_eq!(function_map.len(), 2);
+}
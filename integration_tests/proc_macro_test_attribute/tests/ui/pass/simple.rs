@@ -0,0 +1,12 @@
+use proc_macro_test_attribute::nssa_integration_test;
+
+include!("../test_env.rs");
+
+fn main() {
+    let mut function_map: HashMap<String, TestFunction> = HashMap::new();
+
+    #[nssa_integration_test]
+    pub async fn test_success() {}
+
+    assert!(function_map.contains_key("test_success"));
+}
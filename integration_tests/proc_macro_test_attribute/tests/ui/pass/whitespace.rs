@@ -0,0 +1,18 @@
+use proc_macro_test_attribute::nssa_integration_test;
+
+include!("../test_env.rs");
+
+fn main() {
+    let mut function_map: HashMap<String, TestFunction> = HashMap::new();
+
+    #[nssa_integration_test]
+    pub   async
+    fn
+        test_newlines   (  ) {}
+
+    #[nssa_integration_test]
+    async	fn	test_tabs() {}
+
+    assert!(function_map.contains_key("test_newlines"));
+    assert!(function_map.contains_key("test_tabs"));
+}
@@ -0,0 +1,16 @@
+// Minimal environment, which is expected by `nssa_integration_test` expansion
+use std::{collections::HashMap, future::Future, path::PathBuf, pin::Pin, time::Duration};
+
+const TIME_TO_WAIT_FOR_BLOCK_SECONDS: u64 = 0;
+
+macro_rules! info {
+    ($($arg:tt)*) => {};
+}
+
+async fn pre_test(_home_dir: PathBuf) -> Result<(), ()> {
+    Ok(())
+}
+
+async fn post_test(_res: ()) {}
+
+type TestFunction = fn(PathBuf) -> Pin<Box<dyn Future<Output = ()>>>;
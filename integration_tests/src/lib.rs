@@ -15,6 +15,7 @@ use sequencer_core::config::SequencerConfig;
 use sequencer_runner::startup_sequencer;
 use tempfile::TempDir;
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 
 use crate::test_suite_map::{prepare_function_map, tps_test};
 
@@ -56,7 +57,7 @@ fn make_private_account_input_from_str(account_id: &str) -> String {
 pub async fn pre_test(
     home_dir: PathBuf,
 ) -> Result<(ServerHandle, JoinHandle<Result<()>>, TempDir)> {
-    wallet::cli::execute_setup("test_pass".to_owned()).await?;
+    wallet::cli::execute_setup("test_password".to_owned()).await?;
 
     let home_dir_sequencer = home_dir.join("sequencer");
 
@@ -67,7 +68,7 @@ pub async fn pre_test(
     let temp_dir_sequencer = replace_home_dir_with_temp_dir_in_configs(&mut sequencer_config);
 
     let (seq_http_server_handle, sequencer_loop_handle) =
-        startup_sequencer(sequencer_config).await?;
+        startup_sequencer(sequencer_config, None, CancellationToken::new()).await?;
 
     Ok((
         seq_http_server_handle,
@@ -15,6 +15,7 @@ use nssa_core::{NullifierPublicKey, encryption::shared_key_derivation::Secp256k1
 use sequencer_runner::startup_sequencer;
 use tempfile::TempDir;
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use wallet::{
     WalletCore,
     cli::{
@@ -98,7 +99,7 @@ pub fn prepare_function_map() -> HashMap<String, TestFunction> {
 
         let PersistentStorage {
             accounts: persistent_accounts,
-            last_synced_block: _,
+            ..
         } = fetch_persistent_storage().await.unwrap();
 
         let mut new_persistent_account_id = String::new();
@@ -1464,10 +1465,11 @@ pub fn prepare_function_map() -> HashMap<String, TestFunction> {
             vec![account_id],
             vec![],
             (),
+            0,
         )
         .unwrap();
         let witness_set = nssa::public_transaction::WitnessSet::for_message(&message, &[]);
-        let transaction = nssa::PublicTransaction::new(message, witness_set);
+        let transaction = nssa::PublicTransaction::new(message, witness_set).unwrap();
         let _response = seq_client.send_tx_public(transaction).await.unwrap();
 
         info!("Waiting for next block creation");
@@ -1685,7 +1687,7 @@ async fn pre_tps_test(
     let temp_dir_sequencer = replace_home_dir_with_temp_dir_in_configs(&mut sequencer_config);
 
     let (seq_http_server_handle, sequencer_loop_handle) =
-        startup_sequencer(sequencer_config).await?;
+        startup_sequencer(sequencer_config, None, CancellationToken::new()).await?;
 
     Ok((
         seq_http_server_handle,
@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use common::{fee::FeeParams, rpc_primitives::RpcLimitsConfig};
 use key_protocol::key_management::ephemeral_key_holder::EphemeralKeyHolder;
 use nssa::{
     Account, AccountId, PrivacyPreservingTransaction, PrivateKey, PublicKey, PublicTransaction,
@@ -11,7 +12,9 @@ use nssa_core::{
     MembershipProof, NullifierPublicKey, account::AccountWithMetadata,
     encryption::IncomingViewingPublicKey,
 };
-use sequencer_core::config::{AccountInitialData, CommitmentsInitialData, SequencerConfig};
+use sequencer_core::config::{
+    AccountInitialData, CommitmentsInitialData, DEFAULT_MAX_TX_SIZE_BYTES, SequencerConfig,
+};
 
 pub(crate) struct TpsTestManager {
     public_keypairs: Vec<(PrivateKey, AccountId)>,
@@ -57,11 +60,12 @@ impl TpsTestManager {
                     [pair[0].1, pair[1].1].to_vec(),
                     [0u128].to_vec(),
                     amount,
+                    0,
                 )
                 .unwrap();
                 let witness_set =
                     nssa::public_transaction::WitnessSet::for_message(&message, &[&pair[0].0]);
-                PublicTransaction::new(message, witness_set)
+                PublicTransaction::new(message, witness_set).unwrap()
             })
             .collect();
 
@@ -109,6 +113,12 @@ impl TpsTestManager {
             initial_accounts: initial_public_accounts,
             initial_commitments: vec![initial_commitment],
             signing_key: [37; 32],
+            admin_token: None,
+            persist_mempool: false,
+            max_tx_size_bytes: DEFAULT_MAX_TX_SIZE_BYTES,
+            fee_params: FeeParams::default(),
+            rpc_limits: RpcLimitsConfig::default(),
+            state_snapshot_path: None,
         }
     }
 }
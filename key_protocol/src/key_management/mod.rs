@@ -1,3 +1,5 @@
+use anyhow::Result;
+use bip39::Mnemonic;
 use nssa_core::{
     NullifierPublicKey, SharedSecretKey,
     encryption::{EphemeralPublicKey, IncomingViewingPublicKey},
@@ -7,6 +9,13 @@ use serde::{Deserialize, Serialize};
 
 pub type PublicAccountSigningKey = [u8; 32];
 
+/// Proof of control over incoming viewing key. Schnorr signature of a challenge under the
+/// incoming viewing secret key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViewingKeyProof {
+    pub signature: nssa::Signature,
+}
+
 pub mod ephemeral_key_holder;
 pub mod key_tree;
 pub mod secret_holders;
@@ -22,28 +31,28 @@ pub struct KeyChain {
 
 impl KeyChain {
     pub fn new_os_random() -> Self {
-        // Currently dropping SeedHolder at the end of initialization.
-        // Now entirely sure if we need it in the future.
-        let seed_holder = SeedHolder::new_os_random();
-        let secret_spending_key = seed_holder.produce_top_secret_key_holder();
+        Self::new_os_random_with_mnemonic().0
+    }
 
-        let private_key_holder = secret_spending_key.produce_private_key_holder();
+    /// Same as `new_os_random`, also returns 24 word mnemonic phrase to back the keys up with
+    ///
+    /// Seed can not be recovered from the keys, so phrase is only available at creation.
+    pub fn new_os_random_with_mnemonic() -> (Self, String) {
+        let (seed_holder, mnemonic) = SeedHolder::new_os_random_with_mnemonic();
 
-        let nullifer_public_key = private_key_holder.generate_nullifier_public_key();
-        let incoming_viewing_public_key = private_key_holder.generate_incoming_viewing_public_key();
+        (Self::from_seed_holder(&seed_holder), mnemonic.to_string())
+    }
 
-        Self {
-            secret_spending_key,
-            private_key_holder,
-            nullifer_public_key,
-            incoming_viewing_public_key,
-        }
+    /// Restores keys from mnemonic phrase returned by `new_os_random_with_mnemonic`
+    pub fn from_mnemonic(phrase: &str) -> Result<Self> {
+        let mnemonic = Mnemonic::parse(phrase)?;
+
+        Ok(Self::from_seed_holder(&SeedHolder::from_mnemonic(
+            &mnemonic,
+        )))
     }
 
-    pub fn new_mnemonic(passphrase: String) -> Self {
-        // Currently dropping SeedHolder at the end of initialization.
-        // Not entirely sure if we need it in the future.
-        let seed_holder = SeedHolder::new_mnemonic(passphrase);
+    fn from_seed_holder(seed_holder: &SeedHolder) -> Self {
         let secret_spending_key = seed_holder.produce_top_secret_key_holder();
 
         let private_key_holder = secret_spending_key.produce_private_key_holder();
@@ -59,6 +68,12 @@ impl KeyChain {
         }
     }
 
+    pub fn new_mnemonic(passphrase: String) -> Self {
+        // Currently dropping SeedHolder at the end of initialization.
+        // Not entirely sure if we need it in the future.
+        Self::from_seed_holder(&SeedHolder::new_mnemonic(passphrase))
+    }
+
     pub fn calculate_shared_secret_receiver(
         &self,
         ephemeral_public_key_sender: EphemeralPublicKey,
@@ -70,6 +85,39 @@ impl KeyChain {
             &ephemeral_public_key_sender,
         )
     }
+
+    /// Signs `challenge` with incoming viewing secret key. Does not reveal any spending keys.
+    pub fn produce_viewing_key_proof(&self, challenge: &[u8; 32]) -> ViewingKeyProof {
+        // Safe unwrap, viewing secret key is a valid scalar
+        let signing_key =
+            nssa::PrivateKey::try_new(self.private_key_holder.incoming_viewing_secret_key).unwrap();
+
+        ViewingKeyProof {
+            signature: nssa::Signature::new(&signing_key, challenge),
+        }
+    }
+}
+
+/// Checks, that `proof` is a signature of `challenge` under the secret key of `viewing_public_key`
+pub fn verify_viewing_key_proof(
+    viewing_public_key: &IncomingViewingPublicKey,
+    challenge: &[u8; 32],
+    proof: &ViewingKeyProof,
+) -> bool {
+    // Compressed point is a parity byte followed by the x coordinate
+    let Some(x_only) = viewing_public_key
+        .0
+        .get(1..)
+        .and_then(|x_only| <[u8; 32]>::try_from(x_only).ok())
+    else {
+        return false;
+    };
+
+    let Ok(public_key) = nssa::PublicKey::try_new(x_only) else {
+        return false;
+    };
+
+    proof.signature.is_valid_for(challenge, &public_key)
 }
 
 #[cfg(test)]
@@ -107,6 +155,48 @@ mod tests {
             account_id_key_holder.calculate_shared_secret_receiver(ephemeral_public_key_sender);
     }
 
+    #[test]
+    fn test_viewing_key_proof() {
+        let key_chain = KeyChain::new_os_random();
+        let challenge = [42; 32];
+
+        let proof = key_chain.produce_viewing_key_proof(&challenge);
+
+        assert!(verify_viewing_key_proof(
+            &key_chain.incoming_viewing_public_key,
+            &challenge,
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_viewing_key_proof_wrong_challenge() {
+        let key_chain = KeyChain::new_os_random();
+
+        let proof = key_chain.produce_viewing_key_proof(&[42; 32]);
+
+        assert!(!verify_viewing_key_proof(
+            &key_chain.incoming_viewing_public_key,
+            &[43; 32],
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_viewing_key_proof_wrong_key() {
+        let key_chain = KeyChain::new_os_random();
+        let other_key_chain = KeyChain::new_os_random();
+        let challenge = [42; 32];
+
+        let proof = key_chain.produce_viewing_key_proof(&challenge);
+
+        assert!(!verify_viewing_key_proof(
+            &other_key_chain.incoming_viewing_public_key,
+            &challenge,
+            &proof
+        ));
+    }
+
     #[test]
     fn key_generation_test() {
         let seed_holder = SeedHolder::new_os_random();
@@ -152,4 +242,27 @@ mod tests {
             hex::encode(viewing_public_key.to_bytes())
         );
     }
+
+    #[test]
+    fn test_mnemonic_roundtrip() {
+        let (key_chain, phrase) = KeyChain::new_os_random_with_mnemonic();
+        assert_eq!(phrase.split_whitespace().count(), 24);
+
+        let restored = KeyChain::from_mnemonic(&phrase).unwrap();
+
+        assert_eq!(restored.nullifer_public_key, key_chain.nullifer_public_key);
+        assert_eq!(
+            restored.incoming_viewing_public_key,
+            key_chain.incoming_viewing_public_key
+        );
+        assert_eq!(
+            restored.secret_spending_key.0,
+            key_chain.secret_spending_key.0
+        );
+    }
+
+    #[test]
+    fn test_from_invalid_mnemonic() {
+        assert!(KeyChain::from_mnemonic("not a valid mnemonic phrase").is_err());
+    }
 }
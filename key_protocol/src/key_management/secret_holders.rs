@@ -36,11 +36,22 @@ pub struct PrivateKeyHolder {
 
 impl SeedHolder {
     pub fn new_os_random() -> Self {
+        Self::new_os_random_with_mnemonic().0
+    }
+
+    /// Same as `new_os_random`, also returns 24 word mnemonic, from which seed can be restored
+    pub fn new_os_random_with_mnemonic() -> (Self, Mnemonic) {
         let mut enthopy_bytes: [u8; 32] = [0; 32];
         OsRng.fill_bytes(&mut enthopy_bytes);
 
         let mnemonic = Mnemonic::from_entropy(&enthopy_bytes)
             .expect("Enthropy must be a multiple of 32 bytes");
+
+        (Self::from_mnemonic(&mnemonic), mnemonic)
+    }
+
+    /// Restores seed produced by `new_os_random_with_mnemonic`
+    pub fn from_mnemonic(mnemonic: &Mnemonic) -> Self {
         let seed_wide = mnemonic.to_seed("mnemonic");
 
         Self {
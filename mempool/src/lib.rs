@@ -1,43 +1,162 @@
-use tokio::sync::mpsc::{Receiver, Sender};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::sync::{
+    Semaphore, TryAcquireError,
+    mpsc::error::{SendError, TrySendError},
+};
+
+struct Entry<T> {
+    item: T,
+    inserted_at: Instant,
+}
+
+/// Queue shared between mempool and its handle
+struct Queue<T> {
+    entries: Mutex<VecDeque<Entry<T>>>,
+    /// One permit per free place in queue
+    free_slots: Semaphore,
+}
+
+impl<T> Queue<T> {
+    fn push_back(&self, item: T) {
+        self.entries.lock().unwrap().push_back(Entry {
+            item,
+            inserted_at: Instant::now(),
+        });
+    }
+}
+
+/// Metrics of items waiting in mempool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemPoolStats {
+    pub count: usize,
+    pub total_size_bytes: usize,
+    /// Time since the oldest waiting item was pushed, zero if mempool is empty
+    pub oldest_item_age: Duration,
+}
 
 pub struct MemPool<T> {
-    receiver: Receiver<T>,
+    queue: Arc<Queue<T>>,
 }
 
 impl<T> MemPool<T> {
     pub fn new(max_size: usize) -> (Self, MemPoolHandle<T>) {
-        let (sender, receiver) = tokio::sync::mpsc::channel(max_size);
+        assert!(max_size > 0, "Mempool requires max size > 0");
 
-        let mem_pool = Self { receiver };
-        let sender = MemPoolHandle::new(sender);
+        let queue = Arc::new(Queue {
+            entries: Mutex::new(VecDeque::with_capacity(max_size)),
+            free_slots: Semaphore::new(max_size),
+        });
+
+        let mem_pool = Self {
+            queue: queue.clone(),
+        };
+        let sender = MemPoolHandle::new(queue);
         (mem_pool, sender)
     }
 
     pub fn pop(&mut self) -> Option<T> {
-        use tokio::sync::mpsc::error::TryRecvError;
+        let entry = self.queue.entries.lock().unwrap().pop_front();
 
-        match self.receiver.try_recv() {
-            Ok(item) => Some(item),
-            Err(TryRecvError::Empty) => None,
-            Err(TryRecvError::Disconnected) => {
+        match entry {
+            Some(entry) => {
+                self.queue.free_slots.add_permits(1);
+                Some(entry.item)
+            }
+            // Only mempool itself holds the queue, handle is dropped
+            None if Arc::strong_count(&self.queue) == 1 => {
                 panic!("Mempool senders disconnected, cannot receive items, this is a bug")
             }
+            None => None,
+        }
+    }
+
+    /// Removes waiting items chosen by `select` and returns them in the chosen order.
+    ///
+    /// `select` gets waiting items from the oldest one and returns distinct indices into them,
+    /// items which are not selected keep their place in the queue.
+    pub fn pop_selected(&mut self, select: impl FnOnce(&[&T]) -> Vec<usize>) -> Vec<T> {
+        let mut entries = self.queue.entries.lock().unwrap();
+
+        let items: Vec<&T> = entries.iter().map(|entry| &entry.item).collect();
+        let indices = select(&items);
+        if indices.is_empty() {
+            return vec![];
+        }
+
+        let mut slots: Vec<Option<Entry<T>>> = entries.drain(..).map(Some).collect();
+        let selected: Vec<T> = indices
+            .into_iter()
+            .map(|index| {
+                slots[index]
+                    .take()
+                    .expect("Item selected twice, this is a bug")
+                    .item
+            })
+            .collect();
+        entries.extend(slots.into_iter().flatten());
+
+        self.queue.free_slots.add_permits(selected.len());
+        selected
+    }
+
+    /// Computes metrics of waiting items, `item_size` gives size of single item in bytes
+    pub fn stats(&self, item_size: impl Fn(&T) -> usize) -> MemPoolStats {
+        let entries = self.queue.entries.lock().unwrap();
+
+        MemPoolStats {
+            count: entries.len(),
+            total_size_bytes: entries.iter().map(|entry| item_size(&entry.item)).sum(),
+            oldest_item_age: entries
+                .front()
+                .map_or(Duration::ZERO, |entry| entry.inserted_at.elapsed()),
         }
     }
 }
 
+impl<T> Drop for MemPool<T> {
+    fn drop(&mut self) {
+        // Fails pending and future pushes
+        self.queue.free_slots.close();
+    }
+}
+
 pub struct MemPoolHandle<T> {
-    sender: Sender<T>,
+    queue: Arc<Queue<T>>,
 }
 
 impl<T> MemPoolHandle<T> {
-    fn new(sender: Sender<T>) -> Self {
-        Self { sender }
+    fn new(queue: Arc<Queue<T>>) -> Self {
+        Self { queue }
     }
 
     /// Send an item to the mempool blocking if max size is reached
-    pub async fn push(&self, item: T) -> Result<(), tokio::sync::mpsc::error::SendError<T>> {
-        self.sender.send(item).await
+    pub async fn push(&self, item: T) -> Result<(), SendError<T>> {
+        match self.queue.free_slots.acquire().await {
+            Ok(permit) => {
+                permit.forget();
+                self.queue.push_back(item);
+                Ok(())
+            }
+            Err(_) => Err(SendError(item)),
+        }
+    }
+
+    /// Send an item to the mempool without blocking, fails if max size is reached
+    pub fn try_push(&self, item: T) -> Result<(), TrySendError<T>> {
+        match self.queue.free_slots.try_acquire() {
+            Ok(permit) => {
+                permit.forget();
+                self.queue.push_back(item);
+                Ok(())
+            }
+            Err(TryAcquireError::NoPermits) => Err(TrySendError::Full(item)),
+            Err(TryAcquireError::Closed) => Err(TrySendError::Closed(item)),
+        }
     }
 }
 
@@ -78,6 +197,17 @@ mod tests {
         assert_eq!(pool.pop(), None);
     }
 
+    #[test]
+    async fn test_try_push_fails_when_full() {
+        let (mut pool, handle) = MemPool::new(1);
+
+        handle.try_push(1).unwrap();
+        assert!(handle.try_push(2).is_err());
+
+        assert_eq!(pool.pop(), Some(1));
+        assert_eq!(pool.pop(), None);
+    }
+
     #[test]
     async fn test_pop_empty() {
         let (mut pool, _handle): (MemPool<u64>, _) = MemPool::new(10);
@@ -96,4 +226,89 @@ mod tests {
         assert_eq!(pool.pop(), Some(1));
         assert_eq!(pool.pop(), Some(2));
     }
+
+    #[test]
+    async fn test_push_blocks_until_pop() {
+        let (mut pool, handle) = MemPool::new(1);
+
+        handle.push(1).await.unwrap();
+        let mut push_fut = std::pin::pin!(handle.push(2));
+        let blocked = tokio::time::timeout(Duration::from_millis(10), push_fut.as_mut()).await;
+        assert!(blocked.is_err());
+
+        assert_eq!(pool.pop(), Some(1));
+        push_fut.await.unwrap();
+        assert_eq!(pool.pop(), Some(2));
+    }
+
+    #[test]
+    async fn test_push_fails_after_mempool_dropped() {
+        let (pool, handle) = MemPool::new(1);
+        drop(pool);
+
+        assert!(handle.push(1).await.is_err());
+        assert!(matches!(handle.try_push(2), Err(TrySendError::Closed(2))));
+    }
+
+    #[test]
+    async fn test_pop_selected() {
+        let (mut pool, handle) = MemPool::new(4);
+
+        for item in 1..=4 {
+            handle.push(item).await.unwrap();
+        }
+
+        let selected = pool.pop_selected(|items| {
+            assert_eq!(items, [&1, &2, &3, &4]);
+            vec![2, 0]
+        });
+        assert_eq!(selected, vec![3, 1]);
+
+        // Freed places can be used again
+        handle.try_push(5).unwrap();
+        handle.try_push(6).unwrap();
+        assert!(handle.try_push(7).is_err());
+
+        assert_eq!(pool.pop(), Some(2));
+        assert_eq!(pool.pop(), Some(4));
+        assert_eq!(pool.pop(), Some(5));
+        assert_eq!(pool.pop(), Some(6));
+    }
+
+    #[test]
+    async fn test_pop_selected_nothing() {
+        let (mut pool, handle) = MemPool::new(2);
+
+        handle.push(1).await.unwrap();
+
+        assert!(pool.pop_selected(|_| vec![]).is_empty());
+        assert_eq!(pool.pop(), Some(1));
+    }
+
+    #[test]
+    async fn test_stats() {
+        let (mut pool, handle) = MemPool::new(10);
+        assert_eq!(
+            pool.stats(|item: &Vec<u8>| item.len()),
+            MemPoolStats {
+                count: 0,
+                total_size_bytes: 0,
+                oldest_item_age: Duration::ZERO,
+            }
+        );
+
+        handle.push(vec![0; 3]).await.unwrap();
+        handle.push(vec![0; 5]).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        let stats = pool.stats(|item| item.len());
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.total_size_bytes, 8);
+        assert!(stats.oldest_item_age >= Duration::from_millis(5));
+
+        pool.pop();
+        let stats = pool.stats(|item| item.len());
+        assert_eq!(stats.count, 1);
+        assert_eq!(stats.total_size_bytes, 5);
+    }
 }
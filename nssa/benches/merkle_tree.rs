@@ -0,0 +1,48 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use nssa::MerkleTree;
+
+/// Compares inserting values one by one with inserting them in a single batch
+fn insertion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merkle_tree_insertion");
+
+    for num_values in [1_000usize, 10_000] {
+        let values: Vec<[u8; 32]> = (0..num_values)
+            .map(|i| {
+                let mut value = [0; 32];
+                value[..8].copy_from_slice(&(i as u64).to_le_bytes());
+                value
+            })
+            .collect();
+
+        group.bench_with_input(
+            BenchmarkId::new("one_by_one", num_values),
+            &values,
+            |b, values| {
+                b.iter(|| {
+                    let mut tree = MerkleTree::with_capacity(32);
+                    for value in values {
+                        tree.batch_insert(std::slice::from_ref(value));
+                    }
+                    tree
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("batch", num_values),
+            &values,
+            |b, values| {
+                b.iter(|| {
+                    let mut tree = MerkleTree::with_capacity(32);
+                    tree.batch_insert(values);
+                    tree
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, insertion);
+criterion_main!(benches);
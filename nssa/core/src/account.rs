@@ -23,6 +23,33 @@ pub struct Account {
     pub nonce: Nonce,
 }
 
+#[cfg(feature = "host")]
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum NonceParseError {
+    #[error("nonce does not fit into 16 bytes: leading 16 bytes are non-zero")]
+    Leading16BytesNonZero,
+}
+
+#[cfg(feature = "host")]
+impl Account {
+    /// Nonce as 32 byte big-endian array, zero-padded from the left
+    pub fn nonce_as_big_endian_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        bytes[16..].copy_from_slice(&self.nonce.to_be_bytes());
+        bytes
+    }
+
+    /// Parses nonce from 32 byte big-endian array
+    pub fn from_big_endian_nonce_bytes(bytes: &[u8; 32]) -> Result<Nonce, NonceParseError> {
+        let (leading, nonce) = bytes.split_at(16);
+        if leading.iter().any(|byte| *byte != 0) {
+            return Err(NonceParseError::Leading16BytesNonZero);
+        }
+        // Safe unwrap, `nonce` is exactly 16 bytes long
+        Ok(Nonce::from_be_bytes(nonce.try_into().unwrap()))
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[cfg_attr(any(feature = "host", test), derive(Debug, PartialEq, Eq))]
 pub struct AccountWithMetadata {
@@ -52,7 +79,7 @@ pub struct AccountId {
 }
 
 impl AccountId {
-    pub fn new(value: [u8; 32]) -> Self {
+    pub const fn new(value: [u8; 32]) -> Self {
         Self { value }
     }
 
@@ -176,4 +203,58 @@ mod tests {
         let result = base58_str.parse::<AccountId>().unwrap_err();
         assert!(matches!(result, AccountIdError::InvalidLength(_)));
     }
+
+    #[test]
+    fn test_nonce_big_endian_bytes_zero() {
+        let account = Account::default();
+
+        let bytes = account.nonce_as_big_endian_bytes();
+
+        assert_eq!(bytes, [0; 32]);
+        assert_eq!(Account::from_big_endian_nonce_bytes(&bytes), Ok(0));
+    }
+
+    #[test]
+    fn test_nonce_big_endian_bytes_max() {
+        let account = Account {
+            nonce: Nonce::MAX,
+            ..Account::default()
+        };
+
+        let bytes = account.nonce_as_big_endian_bytes();
+
+        let mut expected_bytes = [0xff; 32];
+        expected_bytes[..16].fill(0);
+        assert_eq!(bytes, expected_bytes);
+        assert_eq!(Account::from_big_endian_nonce_bytes(&bytes), Ok(Nonce::MAX));
+    }
+
+    #[test]
+    fn test_nonce_big_endian_bytes_mid_range() {
+        let account = Account {
+            nonce: 0x0102030405060708,
+            ..Account::default()
+        };
+
+        let bytes = account.nonce_as_big_endian_bytes();
+
+        let mut expected_bytes = [0; 32];
+        expected_bytes[24..].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(bytes, expected_bytes);
+        assert_eq!(
+            Account::from_big_endian_nonce_bytes(&bytes),
+            Ok(0x0102030405060708)
+        );
+    }
+
+    #[test]
+    fn test_nonce_big_endian_bytes_leading_bytes_non_zero() {
+        let mut bytes = [0; 32];
+        bytes[15] = 1;
+
+        assert_eq!(
+            Account::from_big_endian_nonce_bytes(&bytes),
+            Err(NonceParseError::Leading16BytesNonZero)
+        );
+    }
 }
@@ -66,11 +66,13 @@ pub fn compute_digest_for_path(
     commitment: &Commitment,
     proof: &MembershipProof,
 ) -> CommitmentSetDigest {
-    let value_bytes = commitment.to_byte_array();
-    let mut result: [u8; 32] = Impl::hash_bytes(&value_bytes)
-        .as_bytes()
-        .try_into()
-        .unwrap();
+    compute_digest_for_value_path(&commitment.to_byte_array(), proof)
+}
+
+/// Computes the resulting digest for the given membership proof and corresponding value of the
+/// tree, like a commitment or a spent nullifier
+pub fn compute_digest_for_value_path(value: &[u8; 32], proof: &MembershipProof) -> [u8; 32] {
+    let mut result: [u8; 32] = Impl::hash_bytes(value).as_bytes().try_into().unwrap();
     let mut level_index = proof.0;
     for node in &proof.1 {
         let is_left_child = level_index & 1 == 0;
@@ -95,7 +97,8 @@ mod tests {
     use risc0_zkvm::sha::{Impl, Sha256};
 
     use crate::{
-        Commitment, DUMMY_COMMITMENT, DUMMY_COMMITMENT_HASH, NullifierPublicKey, account::Account,
+        Commitment, CommitmentSetDigest, DUMMY_COMMITMENT, DUMMY_COMMITMENT_HASH, MembershipProof,
+        NullifierPublicKey, account::Account, compute_digest_for_path,
     };
 
     #[test]
@@ -115,4 +118,83 @@ mod tests {
                 .unwrap();
         assert_eq!(DUMMY_COMMITMENT_HASH, expected_dummy_commitment_hash);
     }
+
+    /// Test vectors as (commitment, proof, expected digest), generated with
+    /// ```python
+    /// from hashlib import sha256
+    /// def digest(commitment, index, path):
+    ///     result = sha256(commitment).digest()
+    ///     for node in path:
+    ///         result = sha256(result + node if index & 1 == 0 else node + result).digest()
+    ///         index >>= 1
+    ///     return result
+    /// ```
+    fn digest_for_path_test_vectors() -> Vec<(Commitment, MembershipProof, CommitmentSetDigest)> {
+        vec![
+            (
+                Commitment([1; 32]),
+                (0, vec![]),
+                [
+                    114, 205, 110, 132, 34, 196, 7, 251, 109, 9, 134, 144, 241, 19, 11, 125, 237,
+                    126, 194, 247, 245, 225, 211, 11, 217, 213, 33, 240, 21, 54, 55, 147,
+                ],
+            ),
+            (
+                Commitment([2; 32]),
+                (1, vec![[3; 32]]),
+                [
+                    145, 163, 177, 226, 74, 145, 3, 160, 25, 209, 94, 106, 217, 116, 160, 33, 233,
+                    15, 175, 236, 126, 199, 57, 246, 92, 166, 238, 85, 72, 27, 155, 53,
+                ],
+            ),
+            (
+                Commitment([4; 32]),
+                (2, vec![[5; 32], [6; 32]]),
+                [
+                    210, 252, 61, 170, 245, 208, 122, 57, 184, 207, 91, 244, 195, 152, 104, 59,
+                    183, 30, 213, 82, 178, 215, 33, 248, 192, 233, 183, 133, 55, 83, 234, 222,
+                ],
+            ),
+            (
+                Commitment([7; 32]),
+                (5, vec![[8; 32], [9; 32], [10; 32]]),
+                [
+                    142, 182, 143, 9, 201, 57, 70, 254, 229, 89, 227, 127, 170, 116, 141, 30, 227,
+                    25, 52, 21, 44, 211, 14, 100, 134, 146, 31, 159, 14, 82, 178, 103,
+                ],
+            ),
+            (
+                DUMMY_COMMITMENT,
+                (6, vec![[11; 32], [12; 32], [13; 32], [14; 32]]),
+                [
+                    151, 210, 192, 56, 42, 24, 226, 82, 126, 16, 117, 62, 164, 236, 194, 33, 250,
+                    192, 200, 134, 207, 239, 105, 179, 53, 87, 125, 122, 11, 15, 227, 70,
+                ],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_compute_digest_for_path_test_vectors() {
+        for (commitment, proof, expected_digest) in digest_for_path_test_vectors() {
+            assert_eq!(
+                compute_digest_for_path(&commitment, &proof),
+                expected_digest
+            );
+        }
+    }
+
+    #[test]
+    fn test_compute_digest_for_path_changes_with_flipped_path_bit() {
+        for (commitment, (index, path), expected_digest) in digest_for_path_test_vectors() {
+            for node_index in 0..path.len() {
+                let mut tampered_path = path.clone();
+                tampered_path[node_index][0] ^= 1;
+                assert_ne!(
+                    compute_digest_for_path(&commitment, &(index, tampered_path)),
+                    expected_digest
+                );
+            }
+        }
+    }
 }
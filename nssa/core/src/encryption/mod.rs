@@ -1,7 +1,7 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use chacha20::{
-    ChaCha20,
-    cipher::{KeyIvInit, StreamCipher},
+use chacha20poly1305::{
+    ChaCha20Poly1305, KeyInit,
+    aead::{Aead, Payload},
 };
 use risc0_zkvm::sha::{Impl, Sha256};
 use serde::{Deserialize, Serialize};
@@ -32,20 +32,34 @@ impl EncryptionScheme {
         commitment: &Commitment,
         output_index: u32,
     ) -> Ciphertext {
-        let mut buffer = account.to_bytes().to_vec();
-        Self::symmetric_transform(&mut buffer, shared_secret, commitment, output_index);
+        let plaintext = account.to_bytes();
+        let aad = Self::associated_data(commitment, output_index);
+        let payload = Payload {
+            msg: &plaintext,
+            aad: &aad,
+        };
+        let buffer = Self::cipher(shared_secret, commitment, output_index)
+            .encrypt(&[0; 12].into(), payload)
+            .expect("ChaCha20Poly1305 encryption of an account can't fail");
         Ciphertext(buffer)
     }
 
-    fn symmetric_transform(
-        buffer: &mut [u8],
+    /// Every (shared secret, commitment, output index) triple gets its own key, so a key is
+    /// used for a single message and the fixed zero nonce is never reused.
+    fn cipher(
         shared_secret: &SharedSecretKey,
         commitment: &Commitment,
         output_index: u32,
-    ) {
+    ) -> ChaCha20Poly1305 {
         let key = Self::kdf(shared_secret, commitment, output_index);
-        let mut cipher = ChaCha20::new(&key.into(), &[0; 12].into());
-        cipher.apply_keystream(buffer);
+        ChaCha20Poly1305::new(&key.into())
+    }
+
+    /// Binds the ciphertext to its position in the transaction
+    fn associated_data(commitment: &Commitment, output_index: u32) -> Vec<u8> {
+        let mut bytes = commitment.to_byte_array().to_vec();
+        bytes.extend_from_slice(&output_index.to_le_bytes());
+        bytes
     }
 
     fn kdf(
@@ -71,10 +85,112 @@ impl EncryptionScheme {
         output_index: u32,
     ) -> Option<Account> {
         use std::io::Cursor;
-        let mut buffer = ciphertext.0.to_owned();
-        Self::symmetric_transform(&mut buffer, shared_secret, commitment, output_index);
+        let aad = Self::associated_data(commitment, output_index);
+        let payload = Payload {
+            msg: &ciphertext.0,
+            aad: &aad,
+        };
+        let buffer = Self::cipher(shared_secret, commitment, output_index)
+            .decrypt(&[0; 12].into(), payload)
+            .ok()?;
 
         let mut cursor = Cursor::new(buffer.as_slice());
         Account::from_cursor(&mut cursor).ok()
     }
+
+    /// Decrypts many outputs at once, e.g. when scanning blocks for incoming accounts.
+    ///
+    /// Entries failing to decrypt are `None`. With `parallel` feature entries are processed on
+    /// rayon thread pool.
+    #[cfg(feature = "host")]
+    pub fn decrypt_batch(
+        entries: &[(&Ciphertext, &SharedSecretKey, &Commitment, u32)],
+    ) -> Vec<Option<Account>> {
+        #[cfg(feature = "parallel")]
+        use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
+
+        #[cfg(feature = "parallel")]
+        let entries_iter = entries.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let entries_iter = entries.iter();
+
+        entries_iter
+            .map(|(ciphertext, shared_secret, commitment, output_index)| {
+                Self::decrypt(ciphertext, shared_secret, commitment, *output_index)
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "host"))]
+mod tests {
+    use super::*;
+    use crate::NullifierPublicKey;
+
+    #[test]
+    fn test_decrypt_batch_matches_decrypt() {
+        let entries: Vec<_> = (0..5u8)
+            .map(|i| {
+                let account = Account {
+                    balance: i as u128 * 100,
+                    ..Account::default()
+                };
+                let shared_secret = SharedSecretKey([i; 32]);
+                let commitment = Commitment::new(&NullifierPublicKey([i; 32]), &account);
+                let output_index = i as u32;
+                let ciphertext =
+                    EncryptionScheme::encrypt(&account, &shared_secret, &commitment, output_index);
+                (ciphertext, shared_secret, commitment, output_index, account)
+            })
+            .collect();
+
+        let batch: Vec<_> = entries
+            .iter()
+            .map(|(ciphertext, shared_secret, commitment, output_index, _)| {
+                (ciphertext, shared_secret, commitment, *output_index)
+            })
+            .collect();
+
+        let decrypted = EncryptionScheme::decrypt_batch(&batch);
+
+        let expected: Vec<_> = entries
+            .into_iter()
+            .map(|(.., account)| Some(account))
+            .collect();
+        assert_eq!(decrypted, expected);
+    }
+
+    #[test]
+    fn test_decrypt_tampered_ciphertext_fails() {
+        let account = Account {
+            balance: 100,
+            ..Account::default()
+        };
+        let shared_secret = SharedSecretKey([1; 32]);
+        let commitment = Commitment::new(&NullifierPublicKey([2; 32]), &account);
+        let mut ciphertext = EncryptionScheme::encrypt(&account, &shared_secret, &commitment, 0);
+        assert_eq!(
+            EncryptionScheme::decrypt(&ciphertext, &shared_secret, &commitment, 0),
+            Some(account)
+        );
+
+        ciphertext.0[0] ^= 1;
+        assert_eq!(
+            EncryptionScheme::decrypt(&ciphertext, &shared_secret, &commitment, 0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_decrypt_with_other_output_index_fails() {
+        let account = Account::default();
+        let shared_secret = SharedSecretKey([1; 32]);
+        let commitment = Commitment::new(&NullifierPublicKey([2; 32]), &account);
+        let ciphertext = EncryptionScheme::encrypt(&account, &shared_secret, &commitment, 0);
+
+        assert_eq!(
+            EncryptionScheme::decrypt(&ciphertext, &shared_secret, &commitment, 1),
+            None
+        );
+    }
 }
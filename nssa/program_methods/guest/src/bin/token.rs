@@ -5,7 +5,7 @@ use nssa_core::{
     },
 };
 
-// The token program has three functions:
+// The token program has six functions:
 // 1. New token definition.
 //    Arguments to this function are:
 //      * Two **default** accounts: [definition_account, holding_account].
@@ -17,7 +17,9 @@ use nssa_core::{
 //        The name cannot be equal to [0x00, 0x00, 0x00, 0x00, 0x00, 0x00]
 // 2. Token transfer
 //    Arguments to this function are:
-//      * Two accounts: [sender_account, recipient_account].
+//      * Two accounts: [sender_account, recipient_account], with the sender authorized, or
+//        three accounts: [sender_account, recipient_account, spender_account], with the spender
+//        authorized and holding a sufficient allowance on the sender account.
 //      * An instruction data byte string of length 23, indicating the total supply with the following layout
 //        [0x01 || amount (little-endian 16 bytes) || 0x00 || 0x00 || 0x00 || 0x00 || 0x00 || 0x00].
 // 3. Initialize account with zero balance
@@ -25,12 +27,37 @@ use nssa_core::{
 //      * Two accounts: [definition_account, account_to_initialize].
 //      * An dummy byte string of length 23, with the following layout
 //        [0x02 || 0x00 || 0x00 || 0x00 || ... || 0x00 || 0x00].
+// 4. Mint new tokens
+//    Arguments to this function are:
+//      * Two accounts: [definition_account, holding_account].
+//        The definition account must be authorized. The holding account may be a default account,
+//        in which case it is initialized as a holding account for the token.
+//      * An instruction data byte string of length 23, indicating the amount to mint with the following layout
+//        [0x03 || amount (little-endian 16 bytes) || 0x00 || 0x00 || 0x00 || 0x00 || 0x00 || 0x00].
+// 5. Burn tokens
+//    Arguments to this function are:
+//      * Two accounts: [definition_account, holding_account].
+//        The holding account must be authorized.
+//      * An instruction data byte string of length 23, indicating the amount to burn with the following layout
+//        [0x04 || amount (little-endian 16 bytes) || 0x00 || 0x00 || 0x00 || 0x00 || 0x00 || 0x00].
+// 6. Approve spender
+//    Arguments to this function are:
+//      * Two accounts: [holding_account, spender_account].
+//        The holding account must be authorized. The spender account is only used for its ID.
+//      * An instruction data byte string of length 23, indicating the allowance with the following layout
+//        [0x05 || amount (little-endian 16 bytes) || 0x00 || 0x00 || 0x00 || 0x00 || 0x00 || 0x00].
+//        An amount of zero revokes the allowance. A holding account keeps at most
+//        MAX_TOKEN_ALLOWANCES allowances, approving a new spender beyond that fails.
 
 const TOKEN_DEFINITION_TYPE: u8 = 0;
 const TOKEN_DEFINITION_DATA_SIZE: usize = 23;
 
 const TOKEN_HOLDING_TYPE: u8 = 1;
 const TOKEN_HOLDING_DATA_SIZE: usize = 49;
+// Each allowance is appended to the holding data as [spender_id (32 bytes) || amount (16 bytes)]
+const TOKEN_ALLOWANCE_ENTRY_SIZE: usize = 48;
+// Bounds the holding data size, which every transfer of the holding reads and writes
+const MAX_TOKEN_ALLOWANCES: usize = 16;
 
 struct TokenDefinition {
     account_type: u8,
@@ -42,6 +69,7 @@ struct TokenHolding {
     account_type: u8,
     definition_id: AccountId,
     balance: u128,
+    allowances: Vec<(AccountId, u128)>,
 }
 
 impl TokenDefinition {
@@ -79,11 +107,15 @@ impl TokenHolding {
             account_type: TOKEN_HOLDING_TYPE,
             definition_id: definition_id.clone(),
             balance: 0,
+            allowances: Vec::new(),
         }
     }
 
     fn parse(data: &[u8]) -> Option<Self> {
-        if data.len() != TOKEN_HOLDING_DATA_SIZE || data[0] != TOKEN_HOLDING_TYPE {
+        if data.len() < TOKEN_HOLDING_DATA_SIZE
+            || !(data.len() - TOKEN_HOLDING_DATA_SIZE).is_multiple_of(TOKEN_ALLOWANCE_ENTRY_SIZE)
+            || data[0] != TOKEN_HOLDING_TYPE
+        {
             return None;
         }
 
@@ -94,32 +126,64 @@ impl TokenHolding {
                 .expect("Defintion ID must be 32 bytes long"),
         );
         let balance = u128::from_le_bytes(
-            data[33..]
+            data[33..TOKEN_HOLDING_DATA_SIZE]
                 .try_into()
                 .expect("balance must be 16 bytes little-endian"),
         );
+        let allowances = data[TOKEN_HOLDING_DATA_SIZE..]
+            .as_chunks::<TOKEN_ALLOWANCE_ENTRY_SIZE>()
+            .0
+            .iter()
+            .map(|entry| {
+                let spender = AccountId::new(entry[..32].try_into().unwrap());
+                let amount = u128::from_le_bytes(entry[32..].try_into().unwrap());
+                (spender, amount)
+            })
+            .collect();
         Some(Self {
             definition_id,
             balance,
             account_type,
+            allowances,
         })
     }
 
     fn into_data(self) -> Data {
-        let mut bytes = [0; TOKEN_HOLDING_DATA_SIZE];
-        bytes[0] = self.account_type;
-        bytes[1..33].copy_from_slice(&self.definition_id.to_bytes());
-        bytes[33..].copy_from_slice(&self.balance.to_le_bytes());
-        bytes.into()
+        let mut bytes = Vec::with_capacity(
+            TOKEN_HOLDING_DATA_SIZE + self.allowances.len() * TOKEN_ALLOWANCE_ENTRY_SIZE,
+        );
+        bytes.push(self.account_type);
+        bytes.extend_from_slice(&self.definition_id.to_bytes());
+        bytes.extend_from_slice(&self.balance.to_le_bytes());
+        for (spender, amount) in self.allowances {
+            bytes.extend_from_slice(&spender.to_bytes());
+            bytes.extend_from_slice(&amount.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn allowance(&self, spender: &AccountId) -> u128 {
+        self.allowances
+            .iter()
+            .find(|(id, _)| id == spender)
+            .map_or(0, |(_, amount)| *amount)
+    }
+
+    fn set_allowance(&mut self, spender: &AccountId, amount: u128) {
+        self.allowances.retain(|(id, _)| id != spender);
+        if amount > 0 {
+            self.allowances.push((spender.clone(), amount));
+        }
     }
 }
 
 fn transfer(pre_states: &[AccountWithMetadata], balance_to_move: u128) -> Vec<AccountPostState> {
-    if pre_states.len() != 2 {
+    if pre_states.len() != 2 && pre_states.len() != 3 {
         panic!("Invalid number of input accounts");
     }
     let sender = &pre_states[0];
     let recipient = &pre_states[1];
+    let spender = pre_states.get(2);
 
     let mut sender_holding =
         TokenHolding::parse(&sender.account.data).expect("Invalid sender data");
@@ -137,8 +201,22 @@ fn transfer(pre_states: &[AccountWithMetadata], balance_to_move: u128) -> Vec<Ac
         panic!("Insufficient balance");
     }
 
-    if !sender.is_authorized {
-        panic!("Sender authorization is missing");
+    match spender {
+        None => {
+            if !sender.is_authorized {
+                panic!("Sender authorization is missing");
+            }
+        }
+        Some(spender) => {
+            if !spender.is_authorized {
+                panic!("Spender authorization is missing");
+            }
+            let allowance = sender_holding
+                .allowance(&spender.account_id)
+                .checked_sub(balance_to_move)
+                .expect("Insufficient allowance");
+            sender_holding.set_allowance(&spender.account_id, allowance);
+        }
     }
 
     sender_holding.balance -= balance_to_move;
@@ -165,7 +243,11 @@ fn transfer(pre_states: &[AccountWithMetadata], balance_to_move: u128) -> Vec<Ac
         }
     };
 
-    vec![sender_post, recipient_post]
+    let mut post_states = vec![sender_post, recipient_post];
+    if let Some(spender) = spender {
+        post_states.push(AccountPostState::new(spender.account.clone()));
+    }
+    post_states
 }
 
 fn new_definition(
@@ -197,6 +279,7 @@ fn new_definition(
         account_type: TOKEN_HOLDING_TYPE,
         definition_id: definition_target_account.account_id.clone(),
         balance: total_supply,
+        allowances: Vec::new(),
     };
 
     let mut definition_target_account_post = definition_target_account.account.clone();
@@ -241,6 +324,142 @@ fn initialize_account(pre_states: &[AccountWithMetadata]) -> Vec<AccountPostStat
     ]
 }
 
+fn mint(pre_states: &[AccountWithMetadata], amount: u128) -> Vec<AccountPostState> {
+    if pre_states.len() != 2 {
+        panic!("Invalid number of input accounts");
+    }
+    let definition = &pre_states[0];
+    let holding = &pre_states[1];
+
+    if !definition.is_authorized {
+        panic!("Definition authorization is missing");
+    }
+
+    let mut definition_values =
+        TokenDefinition::parse(&definition.account.data).expect("Invalid definition data");
+    let mut holding_values = if holding.account == Account::default() {
+        TokenHolding::new(&definition.account_id)
+    } else {
+        TokenHolding::parse(&holding.account.data).expect("Invalid holding data")
+    };
+
+    if holding_values.definition_id != definition.account_id {
+        panic!("Holding and definition id mismatch");
+    }
+
+    definition_values.total_supply = definition_values
+        .total_supply
+        .checked_add(amount)
+        .expect("Total supply overflow.");
+    holding_values.balance = holding_values
+        .balance
+        .checked_add(amount)
+        .expect("Holding balance overflow.");
+
+    let definition_post = {
+        let mut this = definition.account.clone();
+        this.data = definition_values.into_data();
+        AccountPostState::new(this)
+    };
+
+    let holding_post = {
+        let mut this = holding.account.clone();
+        this.data = holding_values.into_data();
+
+        // Claim the holding account if it has default program owner
+        if this.program_owner == DEFAULT_PROGRAM_ID {
+            AccountPostState::new_claimed(this)
+        } else {
+            AccountPostState::new(this)
+        }
+    };
+
+    vec![definition_post, holding_post]
+}
+
+fn burn(pre_states: &[AccountWithMetadata], amount: u128) -> Vec<AccountPostState> {
+    if pre_states.len() != 2 {
+        panic!("Invalid number of input accounts");
+    }
+    let definition = &pre_states[0];
+    let holding = &pre_states[1];
+
+    if !holding.is_authorized {
+        panic!("Holding authorization is missing");
+    }
+
+    let mut definition_values =
+        TokenDefinition::parse(&definition.account.data).expect("Invalid definition data");
+    let mut holding_values =
+        TokenHolding::parse(&holding.account.data).expect("Invalid holding data");
+
+    if holding_values.definition_id != definition.account_id {
+        panic!("Holding and definition id mismatch");
+    }
+
+    holding_values.balance = holding_values
+        .balance
+        .checked_sub(amount)
+        .expect("Insufficient balance");
+    definition_values.total_supply = definition_values
+        .total_supply
+        .checked_sub(amount)
+        .expect("Total supply underflow.");
+
+    let mut definition_post = definition.account.clone();
+    definition_post.data = definition_values.into_data();
+
+    let mut holding_post = holding.account.clone();
+    holding_post.data = holding_values.into_data();
+
+    vec![
+        AccountPostState::new(definition_post),
+        AccountPostState::new(holding_post),
+    ]
+}
+
+fn approve(pre_states: &[AccountWithMetadata], amount: u128) -> Vec<AccountPostState> {
+    if pre_states.len() != 2 {
+        panic!("Invalid number of input accounts");
+    }
+    let holding = &pre_states[0];
+    let spender = &pre_states[1];
+
+    if !holding.is_authorized {
+        panic!("Holding authorization is missing");
+    }
+
+    let mut holding_values =
+        TokenHolding::parse(&holding.account.data).expect("Invalid holding data");
+    if amount > 0
+        && holding_values.allowance(&spender.account_id) == 0
+        && holding_values.allowances.len() >= MAX_TOKEN_ALLOWANCES
+    {
+        panic!("Too many allowances");
+    }
+    holding_values.set_allowance(&spender.account_id, amount);
+
+    let mut holding_post = holding.account.clone();
+    holding_post.data = holding_values.into_data();
+
+    vec![
+        AccountPostState::new(holding_post),
+        AccountPostState::new(spender.account.clone()),
+    ]
+}
+
+fn parse_amount_instruction(instruction: &Instruction) -> u128 {
+    let amount = u128::from_le_bytes(
+        instruction[1..17]
+            .try_into()
+            .expect("Amount must be 16 bytes little-endian"),
+    );
+    if instruction[17..] != [0; 6] {
+        panic!("Invalid instruction padding");
+    }
+    amount
+}
+
 type Instruction = [u8; 23];
 
 fn main() {
@@ -287,6 +506,9 @@ fn main() {
             }
             initialize_account(&pre_states)
         }
+        3 => mint(&pre_states, parse_amount_instruction(&instruction)),
+        4 => burn(&pre_states, parse_amount_instruction(&instruction)),
+        5 => approve(&pre_states, parse_amount_instruction(&instruction)),
         _ => panic!("Invalid instruction"),
     };
 
@@ -298,8 +520,9 @@ mod tests {
     use nssa_core::account::{Account, AccountId, AccountWithMetadata};
 
     use crate::{
-        TOKEN_DEFINITION_DATA_SIZE, TOKEN_HOLDING_DATA_SIZE, TOKEN_HOLDING_TYPE,
-        initialize_account, new_definition, transfer,
+        MAX_TOKEN_ALLOWANCES, TOKEN_DEFINITION_DATA_SIZE, TOKEN_HOLDING_DATA_SIZE,
+        TOKEN_HOLDING_TYPE, TokenHolding, approve, burn, initialize_account, mint, new_definition,
+        transfer,
     };
 
     #[should_panic(expected = "Invalid number of input accounts")]
@@ -447,6 +670,11 @@ mod tests {
                 is_authorized: true,
                 account_id: AccountId::new([3; 32]),
             },
+            AccountWithMetadata {
+                account: Account::default(),
+                is_authorized: true,
+                account_id: AccountId::new([4; 32]),
+            },
         ];
         let _post_states = transfer(&pre_states, 10);
     }
@@ -680,4 +908,235 @@ mod tests {
             ]
         );
     }
+
+    fn definition_account(total_supply: u128, is_authorized: bool) -> AccountWithMetadata {
+        AccountWithMetadata {
+            account: Account {
+                data: [0; TOKEN_DEFINITION_DATA_SIZE - 16]
+                    .into_iter()
+                    .chain(u128::to_le_bytes(total_supply))
+                    .collect(),
+                ..Account::default()
+            },
+            is_authorized,
+            account_id: AccountId::new([1; 32]),
+        }
+    }
+
+    fn holding_account(balance: u128, is_authorized: bool) -> AccountWithMetadata {
+        AccountWithMetadata {
+            account: Account {
+                // Holding of the definition with ID [1; 32]
+                data: vec![1; TOKEN_HOLDING_DATA_SIZE - 16]
+                    .into_iter()
+                    .chain(u128::to_le_bytes(balance))
+                    .collect(),
+                ..Account::default()
+            },
+            is_authorized,
+            account_id: AccountId::new([2; 32]),
+        }
+    }
+
+    #[test]
+    fn test_mint_with_valid_inputs_succeeds() {
+        let pre_states = vec![definition_account(1000, true), holding_account(37, false)];
+        let post_states = mint(&pre_states, 63);
+        let [definition, holding] = post_states.try_into().ok().unwrap();
+        assert_eq!(
+            definition.account().data,
+            vec![
+                0, 0, 0, 0, 0, 0, 0, 0x27, 0x04, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
+            ]
+        );
+        assert_eq!(
+            holding.account().data,
+            vec![
+                1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                1, 1, 1, 1, 1, 100, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mint_to_default_account_initializes_holding() {
+        let mut recipient = holding_account(0, false);
+        recipient.account = Account::default();
+        let pre_states = vec![definition_account(1000, true), recipient];
+        let post_states = mint(&pre_states, 5);
+        let [_, holding] = post_states.try_into().ok().unwrap();
+        assert!(holding.requires_claim());
+        assert_eq!(
+            holding.account().data,
+            vec![
+                1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                1, 1, 1, 1, 1, 5, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
+            ]
+        );
+    }
+
+    #[should_panic(expected = "Definition authorization is missing")]
+    #[test]
+    fn test_mint_without_definition_authorization_should_fail() {
+        let pre_states = vec![definition_account(1000, false), holding_account(37, true)];
+        let _post_states = mint(&pre_states, 1);
+    }
+
+    #[should_panic(expected = "Holding and definition id mismatch")]
+    #[test]
+    fn test_mint_with_different_definition_ids_should_fail() {
+        let mut holding = holding_account(37, false);
+        holding.account.data[1..33].copy_from_slice(&[3; 32]);
+        let pre_states = vec![definition_account(1000, true), holding];
+        let _post_states = mint(&pre_states, 1);
+    }
+
+    #[should_panic(expected = "Total supply overflow.")]
+    #[test]
+    fn test_mint_total_supply_overflow_should_fail() {
+        let pre_states = vec![
+            definition_account(u128::MAX, true),
+            holding_account(0, false),
+        ];
+        let _post_states = mint(&pre_states, 1);
+    }
+
+    #[test]
+    fn test_burn_with_valid_inputs_succeeds() {
+        let pre_states = vec![definition_account(1000, false), holding_account(37, true)];
+        let post_states = burn(&pre_states, 37);
+        let [definition, holding] = post_states.try_into().ok().unwrap();
+        assert_eq!(
+            definition.account().data,
+            vec![
+                0, 0, 0, 0, 0, 0, 0, 0xc3, 0x03, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
+            ]
+        );
+        assert_eq!(
+            holding.account().data,
+            vec![
+                1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
+            ]
+        );
+    }
+
+    #[should_panic(expected = "Insufficient balance")]
+    #[test]
+    fn test_burn_with_insufficient_balance_should_fail() {
+        let pre_states = vec![definition_account(1000, false), holding_account(37, true)];
+        let _post_states = burn(&pre_states, 38);
+    }
+
+    #[should_panic(expected = "Holding authorization is missing")]
+    #[test]
+    fn test_burn_without_holding_authorization_should_fail() {
+        let pre_states = vec![definition_account(1000, true), holding_account(37, false)];
+        let _post_states = burn(&pre_states, 1);
+    }
+
+    fn spender_account(is_authorized: bool) -> AccountWithMetadata {
+        AccountWithMetadata {
+            account: Account::default(),
+            is_authorized,
+            account_id: AccountId::new([3; 32]),
+        }
+    }
+
+    #[test]
+    fn test_approve_then_transfer_by_spender_succeeds() {
+        let pre_states = vec![holding_account(100, true), spender_account(false)];
+        let post_states = approve(&pre_states, 40);
+        let [owner, _] = post_states.try_into().ok().unwrap();
+        let owner_holding = TokenHolding::parse(&owner.account().data).unwrap();
+        assert_eq!(owner_holding.allowance(&AccountId::new([3; 32])), 40);
+
+        let mut sender = holding_account(100, false);
+        sender.account = owner.account().clone();
+        let mut recipient = holding_account(0, false);
+        recipient.account_id = AccountId::new([4; 32]);
+        let pre_states = vec![sender, recipient, spender_account(true)];
+        let post_states = transfer(&pre_states, 30);
+        let [sender, recipient, _] = post_states.try_into().ok().unwrap();
+        let sender_holding = TokenHolding::parse(&sender.account().data).unwrap();
+        let recipient_holding = TokenHolding::parse(&recipient.account().data).unwrap();
+        assert_eq!(sender_holding.balance, 70);
+        assert_eq!(sender_holding.allowance(&AccountId::new([3; 32])), 10);
+        assert_eq!(recipient_holding.balance, 30);
+    }
+
+    #[should_panic(expected = "Insufficient allowance")]
+    #[test]
+    fn test_transfer_over_allowance_should_fail() {
+        let mut sender_holding =
+            TokenHolding::parse(&holding_account(100, false).account.data).unwrap();
+        sender_holding.set_allowance(&AccountId::new([3; 32]), 20);
+        let mut sender = holding_account(100, false);
+        sender.account.data = sender_holding.into_data();
+        let mut recipient = holding_account(0, false);
+        recipient.account_id = AccountId::new([4; 32]);
+        let pre_states = vec![sender, recipient, spender_account(true)];
+        let _post_states = transfer(&pre_states, 21);
+    }
+
+    #[should_panic(expected = "Spender authorization is missing")]
+    #[test]
+    fn test_transfer_without_spender_authorization_should_fail() {
+        let mut recipient = holding_account(0, false);
+        recipient.account_id = AccountId::new([4; 32]);
+        let pre_states = vec![
+            holding_account(100, true),
+            recipient,
+            spender_account(false),
+        ];
+        let _post_states = transfer(&pre_states, 1);
+    }
+
+    #[test]
+    fn test_approve_zero_revokes_allowance() {
+        let pre_states = vec![holding_account(100, true), spender_account(false)];
+        let [owner, _] = approve(&pre_states, 40).try_into().ok().unwrap();
+        assert_eq!(owner.account().data.len(), TOKEN_HOLDING_DATA_SIZE + 48);
+
+        let mut holding = holding_account(100, true);
+        holding.account = owner.account().clone();
+        let pre_states = vec![holding, spender_account(false)];
+        let [owner, _] = approve(&pre_states, 0).try_into().ok().unwrap();
+        assert_eq!(
+            owner.account().data,
+            holding_account(100, true).account.data
+        );
+    }
+
+    fn holding_with_allowances(count: u8) -> AccountWithMetadata {
+        let mut holding = holding_account(100, true);
+        let mut holding_values = TokenHolding::parse(&holding.account.data).unwrap();
+        for i in 0..count {
+            holding_values.set_allowance(&AccountId::new([100 + i; 32]), 1);
+        }
+        holding.account.data = holding_values.into_data();
+        holding
+    }
+
+    #[should_panic(expected = "Too many allowances")]
+    #[test]
+    fn test_approve_new_spender_over_allowance_limit_should_fail() {
+        let pre_states = vec![
+            holding_with_allowances(MAX_TOKEN_ALLOWANCES as u8),
+            spender_account(false),
+        ];
+        let _post_states = approve(&pre_states, 1);
+    }
+
+    #[test]
+    fn test_approve_at_allowance_limit_updates_existing_spender() {
+        let mut spender = spender_account(false);
+        spender.account_id = AccountId::new([100; 32]);
+        let pre_states = vec![holding_with_allowances(MAX_TOKEN_ALLOWANCES as u8), spender];
+
+        let [owner, _] = approve(&pre_states, 5).try_into().ok().unwrap();
+        let owner_holding = TokenHolding::parse(&owner.account().data).unwrap();
+        assert_eq!(owner_holding.allowances.len(), MAX_TOKEN_ALLOWANCES);
+        assert_eq!(owner_holding.allowance(&AccountId::new([100; 32])), 5);
+    }
 }
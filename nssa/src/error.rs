@@ -2,6 +2,8 @@ use std::io;
 
 use thiserror::Error;
 
+use crate::public_transaction::MessageValidationError;
+
 #[derive(Error, Debug)]
 pub enum NssaError {
     #[error("Invalid input: {0}")]
@@ -13,6 +15,9 @@ pub enum NssaError {
     #[error("Serialization error: {0}")]
     InstructionSerializationError(String),
 
+    #[error("Deserialization error: {0}")]
+    InstructionDeserializationError(String),
+
     #[error("Invalid private key")]
     InvalidPrivateKey,
 
@@ -22,6 +27,9 @@ pub enum NssaError {
     #[error("Invalid Public Key")]
     InvalidPublicKey,
 
+    #[error("Signing failed: {0}")]
+    SigningFailed(String),
+
     #[error("Risc0 error: {0}")]
     ProgramWriteInputFailed(String),
 
@@ -57,4 +65,13 @@ pub enum NssaError {
 
     #[error("Chain of calls is too long")]
     MaxChainedCallsDepthExceeded,
+
+    #[error("Insufficient funds to pay a fee of {fee}")]
+    InsufficientFundsForFee { fee: u128 },
+
+    #[error("Fee payer must be owned by the authenticated transfer program")]
+    InvalidFeePayer,
+
+    #[error("Invalid message: {0}")]
+    InvalidMessage(#[from] MessageValidationError),
 }
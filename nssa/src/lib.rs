@@ -17,12 +17,13 @@ pub mod public_transaction;
 mod signature;
 mod state;
 
+pub use merkle_tree::{MerkleTree, verify_membership_proof};
 pub use nssa_core::account::{Account, AccountId};
 pub use privacy_preserving_transaction::{
     PrivacyPreservingTransaction, circuit::execute_and_prove,
 };
 pub use program_deployment_transaction::ProgramDeploymentTransaction;
-pub use program_methods::PRIVACY_PRESERVING_CIRCUIT_ID;
+pub use program_methods::{AUTHENTICATED_TRANSFER_ID, PRIVACY_PRESERVING_CIRCUIT_ID};
 pub use public_transaction::PublicTransaction;
-pub use signature::{PrivateKey, PublicKey, Signature};
+pub use signature::{PrivateKey, PublicKey, Signature, Signer};
 pub use state::V02State;
@@ -1,3 +1,8 @@
+use std::collections::HashSet;
+
+use nssa_core::{MembershipProof, compute_digest_for_value_path};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
 mod default_values;
@@ -19,11 +24,76 @@ fn hash_value(value: &Value) -> Node {
     hasher.finalize().into()
 }
 
+/// Checks, that `proof` authenticates `value` in a tree with root `expected_root`
+pub fn verify_membership_proof(
+    value: &Value,
+    proof: &MembershipProof,
+    expected_root: &Node,
+) -> bool {
+    let (index, path) = proof;
+
+    // Index must fit into the tree, otherwise same proof would be valid for many indices
+    let index_fits = path.len() >= usize::BITS as usize || index >> path.len() == 0;
+    index_fits && &compute_digest_for_value_path(value, proof) == expected_root
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "MerkleTreeData"))]
 #[cfg_attr(test, derive(Debug, PartialEq, Eq))]
 pub struct MerkleTree {
     nodes: Vec<Node>,
     capacity: usize,
-    length: usize,
+    /// Inserted values, ordered by their index
+    values: Vec<Value>,
+}
+
+/// Unchecked `MerkleTree` fields, validated on deserialization
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct MerkleTreeData {
+    nodes: Vec<Node>,
+    capacity: usize,
+    values: Vec<Value>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<MerkleTreeData> for MerkleTree {
+    type Error = String;
+
+    fn try_from(value: MerkleTreeData) -> Result<Self, Self::Error> {
+        let MerkleTreeData {
+            nodes,
+            capacity,
+            values,
+        } = value;
+
+        if capacity == 0 || !capacity.is_power_of_two() {
+            return Err(format!("Capacity {capacity} is not a power of two"));
+        }
+        let expected_nodes = capacity
+            .checked_mul(2)
+            .and_then(|nodes| nodes.checked_sub(1))
+            .ok_or_else(|| format!("Capacity {capacity} is too large"))?;
+        if nodes.len() != expected_nodes {
+            return Err(format!(
+                "Expected {expected_nodes} nodes for capacity {capacity}, found {}",
+                nodes.len()
+            ));
+        }
+        if values.len() > capacity {
+            return Err(format!(
+                "Number of values {} exceeds capacity {capacity}",
+                values.len()
+            ));
+        }
+
+        Ok(Self {
+            nodes,
+            capacity,
+            values,
+        })
+    }
 }
 
 impl MerkleTree {
@@ -32,6 +102,20 @@ impl MerkleTree {
         *self.get_node(root_index)
     }
 
+    /// Number of values inserted into the tree, duplicates included
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Iterates over inserted values in insertion order, i.e. ordered by their index
+    pub fn iter_values(&self) -> impl Iterator<Item = &Value> {
+        self.values.iter()
+    }
+
     fn root_index(&self) -> usize {
         let tree_depth = self.depth();
         let capacity_depth = self.capacity.trailing_zeros() as usize;
@@ -46,7 +130,7 @@ impl MerkleTree {
 
     /// Number of levels required to hold all nodes
     fn depth(&self) -> usize {
-        self.length.next_power_of_two().trailing_zeros() as usize
+        self.len().next_power_of_two().trailing_zeros() as usize
     }
 
     fn get_node(&self, index: usize) -> &Node {
@@ -73,7 +157,7 @@ impl MerkleTree {
         Self {
             nodes,
             capacity,
-            length: 0,
+            values: vec![],
         }
     }
 
@@ -91,40 +175,55 @@ impl MerkleTree {
             this.set_node(new_index, *value);
         }
 
-        this.length = self.length;
+        this.values = std::mem::take(&mut self.values);
 
         *self = this;
     }
 
-    pub fn insert(&mut self, value: Value) -> usize {
-        if self.length == self.capacity {
-            self.reallocate_to_double_capacity();
+    /// Inserts `values` in order, resulting tree is the same as after inserting them one by one.
+    /// Parent nodes are recomputed once for the whole batch.
+    ///
+    /// Duplicates are not removed, every value gets its own index, the same as with one by one
+    /// insertion. Commitment and nullifier sets rely on this to map their items to indices.
+    /// Returns the number of inserted values
+    pub fn batch_insert(&mut self, values: &[Value]) -> usize {
+        let new_leaves: Vec<Node> = values.iter().map(hash_value).collect();
+
+        let first_new_index = self.len();
+        if new_leaves.is_empty() {
+            return 0;
         }
 
-        let new_index = self.length;
+        let new_length = self.len() + new_leaves.len();
 
-        let mut node_index = new_index + self.capacity - 1;
-        let mut node_hash = hash_value(&value);
+        while self.capacity < new_length {
+            self.reallocate_to_double_capacity();
+        }
 
-        // Insert the new node at the bottom layer
-        self.set_node(node_index, node_hash);
-        self.length += 1;
+        for (offset, leaf) in new_leaves.iter().enumerate() {
+            self.set_node(first_new_index + offset + self.capacity - 1, *leaf);
+        }
+        self.values.extend_from_slice(values);
 
-        // Update upper levels for the newly inserted node
+        // Update upper levels for the range of newly inserted nodes
+        let mut first_node_index = first_new_index + self.capacity - 1;
+        let mut last_node_index = new_length - 1 + self.capacity - 1;
         for _ in 0..self.depth() {
-            let parent_index = (node_index - 1) >> 1;
-            let left_child = self.get_node((parent_index << 1) + 1);
-            let right_child = self.get_node((parent_index << 1) + 2);
-            node_hash = hash_two(left_child, right_child);
-            self.set_node(parent_index, node_hash);
-            node_index = parent_index;
+            first_node_index = (first_node_index - 1) >> 1;
+            last_node_index = (last_node_index - 1) >> 1;
+            for parent_index in first_node_index..=last_node_index {
+                let left_child = self.get_node((parent_index << 1) + 1);
+                let right_child = self.get_node((parent_index << 1) + 2);
+                let node_hash = hash_two(left_child, right_child);
+                self.set_node(parent_index, node_hash);
+            }
         }
 
-        new_index
+        new_leaves.len()
     }
 
     pub fn get_authentication_path_for(&self, index: usize) -> Option<Vec<Node>> {
-        if index >= self.length {
+        if index >= self.len() {
             return None;
         }
 
@@ -148,6 +247,32 @@ impl MerkleTree {
 
         Some(path)
     }
+
+    /// Tree of `values` inserted in the given order
+    fn from_values<'a>(values: impl IntoIterator<Item = &'a Value>) -> Self {
+        let values: Vec<Value> = values.into_iter().cloned().collect();
+        let mut this = Self::with_capacity(values.len());
+        this.batch_insert(&values);
+        this
+    }
+
+    /// Returns a tree of values present in `a` but absent from `b`, in `a`'s insertion order
+    pub fn difference(a: &MerkleTree, b: &MerkleTree) -> MerkleTree {
+        let b_values: HashSet<_> = b.iter_values().collect();
+        Self::from_values(a.iter_values().filter(|value| !b_values.contains(value)))
+    }
+
+    /// Returns a tree of values present in exactly one of `a` and `b`.
+    /// Values of `a` come first, each side keeps its insertion order
+    pub fn symmetric_difference(a: &MerkleTree, b: &MerkleTree) -> MerkleTree {
+        let a_values: HashSet<_> = a.iter_values().collect();
+        let b_values: HashSet<_> = b.iter_values().collect();
+        Self::from_values(
+            a.iter_values()
+                .filter(|value| !b_values.contains(value))
+                .chain(b.iter_values().filter(|value| !a_values.contains(value))),
+        )
+    }
 }
 
 fn prev_power_of_two(x: usize) -> usize {
@@ -160,6 +285,33 @@ fn prev_power_of_two(x: usize) -> usize {
 #[cfg(test)]
 mod tests {
     impl MerkleTree {
+        pub fn insert(&mut self, value: Value) -> usize {
+            if self.len() == self.capacity {
+                self.reallocate_to_double_capacity();
+            }
+
+            let new_index = self.len();
+
+            let mut node_index = new_index + self.capacity - 1;
+            let mut node_hash = hash_value(&value);
+
+            // Insert the new node at the bottom layer
+            self.set_node(node_index, node_hash);
+            self.values.push(value);
+
+            // Update upper levels for the newly inserted node
+            for _ in 0..self.depth() {
+                let parent_index = (node_index - 1) >> 1;
+                let left_child = self.get_node((parent_index << 1) + 1);
+                let right_child = self.get_node((parent_index << 1) + 2);
+                node_hash = hash_two(left_child, right_child);
+                self.set_node(parent_index, node_hash);
+                node_index = parent_index;
+            }
+
+            new_index
+        }
+
         pub fn new(values: &[Value]) -> Self {
             let mut this = Self::with_capacity(values.len());
             for value in values.iter().cloned() {
@@ -170,6 +322,7 @@ mod tests {
     }
 
     use hex_literal::hex;
+    use proptest::prelude::*;
 
     use super::*;
     #[test]
@@ -179,7 +332,7 @@ mod tests {
             hex!("0000000000000000000000000000000000000000000000000000000000000000");
         assert_eq!(tree.root(), expected_root);
         assert_eq!(tree.capacity, 4);
-        assert_eq!(tree.length, 0);
+        assert_eq!(tree.len(), 0);
     }
 
     #[test]
@@ -188,7 +341,7 @@ mod tests {
         let tree = MerkleTree::new(&values);
         assert_eq!(tree.root(), hash_value(&[0; 32]));
         assert_eq!(tree.capacity, 1);
-        assert_eq!(tree.length, 1);
+        assert_eq!(tree.len(), 1);
     }
 
     #[test]
@@ -199,7 +352,7 @@ mod tests {
             hex!("48c73f7821a58a8d2a703e5b39c571c0aa20cf14abcd0af8f2b955bc202998de");
         assert_eq!(tree.root(), expected_root);
         assert_eq!(tree.capacity, 4);
-        assert_eq!(tree.length, 4)
+        assert_eq!(tree.len(), 4)
     }
 
     #[test]
@@ -210,7 +363,7 @@ mod tests {
             hex!("c9bbb83096df85157a146e7d770455a98412dee0633187ee86fee6c8a45b831a");
         assert_eq!(tree.root(), expected_root);
         assert_eq!(tree.capacity, 4);
-        assert_eq!(tree.length, 4);
+        assert_eq!(tree.len(), 4);
     }
 
     #[test]
@@ -221,7 +374,7 @@ mod tests {
             hex!("c8d3d8d2b13f27ceeccdc699119871f9f32ea7ed86ff45d0ad11f77b28cd7568");
         assert_eq!(tree.root(), expected_root);
         assert_eq!(tree.capacity, 4);
-        assert_eq!(tree.length, 3);
+        assert_eq!(tree.len(), 3);
     }
 
     #[test]
@@ -233,7 +386,7 @@ mod tests {
 
         assert_eq!(tree.root(), expected_root);
         assert_eq!(tree.capacity, 8);
-        assert_eq!(tree.length, 5);
+        assert_eq!(tree.len(), 5);
     }
 
     #[test]
@@ -247,7 +400,7 @@ mod tests {
             hex!("3f72d2ff55921a86c48e5988ec3e19ee9d0d5aa3e23197842970a903508ed767");
         assert_eq!(tree.root(), expected_root);
         assert_eq!(tree.capacity, 16);
-        assert_eq!(tree.length, 11);
+        assert_eq!(tree.len(), 11);
     }
 
     #[test]
@@ -263,7 +416,7 @@ mod tests {
     fn test_with_capacity_4() {
         let tree = MerkleTree::with_capacity(4);
 
-        assert_eq!(tree.length, 0);
+        assert_eq!(tree.len(), 0);
         assert_eq!(tree.nodes.len(), 7);
         for i in 3..7 {
             assert_eq!(*tree.get_node(i), default_values::DEFAULT_VALUES[0], "{i}");
@@ -278,7 +431,7 @@ mod tests {
     fn test_with_capacity_5() {
         let tree = MerkleTree::with_capacity(5);
 
-        assert_eq!(tree.length, 0);
+        assert_eq!(tree.len(), 0);
         assert_eq!(tree.nodes.len(), 15);
         for i in 7..15 {
             assert_eq!(*tree.get_node(i), default_values::DEFAULT_VALUES[0])
@@ -549,6 +702,273 @@ mod tests {
         assert_eq!(tree_less_capacity.root(), expected_root);
         assert_eq!(tree_exact_capacity.root(), expected_root);
     }
+
+    #[test]
+    fn test_len_counts_inserted_values() {
+        let mut tree = MerkleTree::with_capacity(1);
+        assert!(tree.is_empty());
+
+        tree.insert([1; 32]);
+        tree.insert([2; 32]);
+        tree.batch_insert(&[[3; 32], [4; 32], [5; 32]]);
+
+        assert_eq!(tree.len(), 5);
+        assert!(!tree.is_empty());
+    }
+
+    #[test]
+    fn test_iter_values_in_insertion_order() {
+        let values: Vec<Value> = (0..11u8).map(|i| [10 - i; 32]).collect();
+
+        // Tree is reallocated several times while values are inserted
+        let mut tree = MerkleTree::with_capacity(1);
+        for value in &values[..3] {
+            tree.insert(*value);
+        }
+        tree.batch_insert(&values[3..]);
+
+        let iterated: Vec<Value> = tree.iter_values().cloned().collect();
+        assert_eq!(iterated, values);
+        for (index, value) in tree.iter_values().enumerate() {
+            let path = tree.get_authentication_path_for(index).unwrap();
+            assert!(verify_membership_proof(value, &(index, path), &tree.root()));
+        }
+    }
+
+    #[test]
+    fn test_difference() {
+        let a = MerkleTree::new(&[[1; 32], [2; 32], [3; 32], [4; 32]]);
+        let b = MerkleTree::new(&[[4; 32], [2; 32], [5; 32]]);
+
+        let difference = MerkleTree::difference(&a, &b);
+        let expected = MerkleTree::new(&[[1; 32], [3; 32]]);
+
+        assert_eq!(difference.len(), 2);
+        assert_eq!(difference.root(), expected.root());
+    }
+
+    #[test]
+    fn test_difference_set_properties() {
+        let a = MerkleTree::new(&[[1; 32], [2; 32], [3; 32]]);
+        let empty = MerkleTree::with_capacity(1);
+
+        assert_eq!(MerkleTree::difference(&a, &empty).root(), a.root());
+        assert_eq!(MerkleTree::difference(&empty, &a).root(), empty.root());
+        assert_eq!(MerkleTree::difference(&a, &a).root(), empty.root());
+        assert_eq!(MerkleTree::difference(&a, &a).len(), 0);
+    }
+
+    #[test]
+    fn test_symmetric_difference() {
+        let a = MerkleTree::new(&[[1; 32], [2; 32], [3; 32], [4; 32]]);
+        let b = MerkleTree::new(&[[4; 32], [2; 32], [5; 32]]);
+
+        let symmetric_difference = MerkleTree::symmetric_difference(&a, &b);
+        let expected = MerkleTree::new(&[[1; 32], [3; 32], [5; 32]]);
+
+        assert_eq!(symmetric_difference.len(), 3);
+        assert_eq!(symmetric_difference.root(), expected.root());
+        assert_eq!(
+            MerkleTree::symmetric_difference(&b, &a).root(),
+            MerkleTree::new(&[[5; 32], [1; 32], [3; 32]]).root()
+        );
+        assert_eq!(MerkleTree::symmetric_difference(&a, &a).len(), 0);
+    }
+
+    #[test]
+    fn test_batch_insert_matches_sequential_insert() {
+        let values: Vec<Value> = (0..40u8).map(|i| [i; 32]).collect();
+
+        for total in 0..values.len() {
+            for split in 0..=total {
+                let mut sequential_tree = MerkleTree::with_capacity(1);
+                for value in &values[..total] {
+                    sequential_tree.insert(*value);
+                }
+
+                let mut batch_tree = MerkleTree::with_capacity(1);
+                for value in &values[..split] {
+                    batch_tree.insert(*value);
+                }
+                let inserted = batch_tree.batch_insert(&values[split..total]);
+
+                assert_eq!(inserted, total - split);
+                assert_eq!(batch_tree, sequential_tree, "total {total}, split {split}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_batch_insert_root_matches_new() {
+        let values: Vec<Value> = (0..33u8).map(|i| [i; 32]).collect();
+
+        let mut tree = MerkleTree::with_capacity(4);
+        tree.batch_insert(&values);
+
+        assert_eq!(tree.root(), MerkleTree::new(&values).root());
+        assert_eq!(
+            tree.get_authentication_path_for(17),
+            MerkleTree::new(&values).get_authentication_path_for(17)
+        );
+    }
+
+    #[test]
+    fn test_batch_insert_keeps_duplicates() {
+        let values = [[1; 32], [2; 32], [1; 32], [3; 32], [2; 32]];
+        let mut tree = MerkleTree::with_capacity(1);
+
+        assert_eq!(tree.batch_insert(&values), 5);
+        assert_eq!(tree, MerkleTree::new(&values));
+    }
+
+    #[test]
+    fn test_batch_insert_empty() {
+        let mut tree = MerkleTree::new(&[[1; 32], [2; 32]]);
+        let expected_tree = tree.clone();
+
+        assert_eq!(tree.batch_insert(&[]), 0);
+        assert_eq!(tree, expected_tree);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        let mut tree = MerkleTree::new(&[[1; 32], [2; 32], [3; 32]]);
+
+        let serialized = serde_json::to_vec(&tree).unwrap();
+        let mut deserialized: MerkleTree = serde_json::from_slice(&serialized).unwrap();
+
+        assert_eq!(deserialized, tree);
+
+        tree.insert([4; 32]);
+        deserialized.insert([4; 32]);
+        assert_eq!(deserialized, tree);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_rejects_inconsistent_tree() {
+        let tree = MerkleTree::new(&[[1; 32], [2; 32], [3; 32]]);
+        let mut serialized = serde_json::to_value(&tree).unwrap();
+        serialized["capacity"] = serde_json::json!(8);
+
+        assert!(serde_json::from_value::<MerkleTree>(serialized).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_rejects_zero_and_overflowing_capacity() {
+        let zero_capacity = serde_json::json!({"nodes": [], "capacity": 0, "values": []});
+        assert!(serde_json::from_value::<MerkleTree>(zero_capacity).is_err());
+
+        let huge_capacity =
+            serde_json::json!({"nodes": [], "capacity": 1usize << (usize::BITS - 1), "values": []});
+        assert!(serde_json::from_value::<MerkleTree>(huge_capacity).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_snapshot_fixture() {
+        let snapshot = include_str!("test_data/tree_snapshot.json");
+
+        let mut tree: MerkleTree = serde_json::from_str(snapshot).unwrap();
+
+        let mut expected_tree = MerkleTree::new(&[[1; 32], [2; 32], [3; 32]]);
+        assert_eq!(tree, expected_tree);
+        assert_eq!(serde_json::to_string(&tree).unwrap(), snapshot.trim_end());
+
+        tree.insert([4; 32]);
+        expected_tree.insert([4; 32]);
+        assert_eq!(tree, expected_tree);
+    }
+
+    #[test]
+    fn test_authentication_paths_verify_with_compute_digest_for_path() {
+        use nssa_core::{
+            Commitment, NullifierPublicKey, account::Account, compute_digest_for_path,
+        };
+
+        let commitments: Vec<_> = (0..9u8)
+            .map(|i| {
+                let account = Account {
+                    balance: i as u128,
+                    ..Account::default()
+                };
+                Commitment::new(&NullifierPublicKey([i; 32]), &account)
+            })
+            .collect();
+
+        for capacity in [1, 8, 32] {
+            let mut tree = MerkleTree::with_capacity(capacity);
+
+            for commitment in &commitments {
+                tree.insert(commitment.to_byte_array());
+
+                for (index, commitment) in commitments[..tree.len()].iter().enumerate() {
+                    let path = tree.get_authentication_path_for(index).unwrap();
+                    assert_eq!(
+                        compute_digest_for_path(commitment, &(index, path)),
+                        tree.root(),
+                        "capacity {capacity}, length {}, index {index}",
+                        tree.len()
+                    );
+                }
+            }
+
+            assert!(tree.get_authentication_path_for(tree.len()).is_none());
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_verify_membership_proof_for_random_trees(
+            values in prop::collection::vec(any::<Value>(), 1..=40),
+            capacity in 1..=64usize,
+        ) {
+            let mut tree = MerkleTree::with_capacity(capacity);
+            tree.batch_insert(&values);
+            let root = tree.root();
+
+            for (index, value) in values.iter().enumerate() {
+                let path = tree.get_authentication_path_for(index).unwrap();
+                prop_assert!(verify_membership_proof(value, &(index, path), &root));
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_membership_proof_rejects_invalid_proofs() {
+        let values: Vec<Value> = (0..5u8).map(|i| [i; 32]).collect();
+        let tree = MerkleTree::new(&values);
+        let root = tree.root();
+        let path = tree.get_authentication_path_for(2).unwrap();
+
+        assert!(verify_membership_proof(
+            &values[2],
+            &(2, path.clone()),
+            &root
+        ));
+        // Wrong value
+        assert!(!verify_membership_proof(
+            &values[3],
+            &(2, path.clone()),
+            &root
+        ));
+        // Wrong index
+        assert!(!verify_membership_proof(
+            &values[2],
+            &(3, path.clone()),
+            &root
+        ));
+        // Index out of tree bounds
+        assert!(!verify_membership_proof(
+            &values[2],
+            &(2 + (1 << path.len()), path.clone()),
+            &root
+        ));
+        // Wrong root
+        assert!(!verify_membership_proof(&values[2], &(2, path), &[0; 32]));
+    }
 }
 
 //
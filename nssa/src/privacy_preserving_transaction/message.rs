@@ -87,6 +87,14 @@ impl Message {
             new_nullifiers: output.new_nullifiers,
         })
     }
+
+    pub fn public_account_ids(&self) -> &[AccountId] {
+        &self.public_account_ids
+    }
+
+    pub fn new_nullifiers(&self) -> &[(Nullifier, CommitmentSetDigest)] {
+        &self.new_nullifiers
+    }
 }
 
 #[cfg(test)]
@@ -4,4 +4,4 @@ pub mod witness_set;
 
 pub mod circuit;
 
-pub use transaction::PrivacyPreservingTransaction;
+pub use transaction::{PrivacyPreservingTransaction, VerificationError};
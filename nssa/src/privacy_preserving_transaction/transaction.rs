@@ -13,6 +13,25 @@ use crate::{
     privacy_preserving_transaction::{circuit::Proof, message::EncryptedAccountData},
 };
 
+/// Reason a privacy-preserving transaction was found malformed without access to the chain state
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum VerificationError {
+    #[error("Empty commitments and empty nullifiers found in message")]
+    EmptyOutputs,
+    #[error("Duplicate account_ids found in message")]
+    DuplicateAccountIds,
+    #[error("Duplicate nullifiers found in message")]
+    DuplicateNullifiers,
+    #[error("Duplicate commitments found in message")]
+    DuplicateCommitments,
+    #[error("Mismatch between number of nonces and signatures/public keys")]
+    NonceCountMismatch { nonces: usize, signatures: usize },
+    #[error("Invalid signature for given message and public key")]
+    InvalidSignature,
+    #[error("Nullifier {index} proven against an unknown commitment set digest")]
+    UnknownNullifierDigest { index: usize },
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub struct PrivacyPreservingTransaction {
     pub message: Message,
@@ -32,50 +51,10 @@ impl PrivacyPreservingTransaction {
         state: &V02State,
     ) -> Result<HashMap<AccountId, Account>, NssaError> {
         let message = &self.message;
-        let witness_set = &self.witness_set;
 
-        // 1. Commitments or nullifiers are non empty
-        if message.new_commitments.is_empty() && message.new_nullifiers.is_empty() {
-            return Err(NssaError::InvalidInput(
-                "Empty commitments and empty nullifiers found in message".into(),
-            ));
-        }
-
-        // 2. Check there are no duplicate account_ids in the public_account_ids list.
-        if n_unique(&message.public_account_ids) != message.public_account_ids.len() {
-            return Err(NssaError::InvalidInput(
-                "Duplicate account_ids found in message".into(),
-            ));
-        }
-
-        // Check there are no duplicate nullifiers in the new_nullifiers list
-        if n_unique(&message.new_nullifiers) != message.new_nullifiers.len() {
-            return Err(NssaError::InvalidInput(
-                "Duplicate nullifiers found in message".into(),
-            ));
-        }
-
-        // Check there are no duplicate commitments in the new_commitments list
-        if n_unique(&message.new_commitments) != message.new_commitments.len() {
-            return Err(NssaError::InvalidInput(
-                "Duplicate commitments found in message".into(),
-            ));
-        }
-
-        // 3. Nonce checks and Valid signatures
-        // Check exactly one nonce is provided for each signature
-        if message.nonces.len() != witness_set.signatures_and_public_keys.len() {
-            return Err(NssaError::InvalidInput(
-                "Mismatch between number of nonces and signatures/public keys".into(),
-            ));
-        }
-
-        // Check the signatures are valid
-        if !witness_set.signatures_are_valid_for(message) {
-            return Err(NssaError::InvalidInput(
-                "Invalid signature for given message and public key".into(),
-            ));
-        }
+        // 1-3. Stateless checks
+        self.check_well_formed()
+            .map_err(|err| NssaError::InvalidInput(err.to_string()))?;
 
         let signer_account_ids = self.signer_account_ids();
         // Check nonces corresponds to the current nonces on the public state.
@@ -101,7 +80,7 @@ impl PrivacyPreservingTransaction {
 
         // 4. Proof verification
         check_privacy_preserving_circuit_proof_is_valid(
-            &witness_set.proof,
+            &self.witness_set.proof,
             &public_pre_states,
             &message.public_post_states,
             &message.encrypted_private_post_states,
@@ -123,6 +102,69 @@ impl PrivacyPreservingTransaction {
             .collect())
     }
 
+    /// Sanity-checks the transaction before broadcasting it, without access to the chain state.
+    /// Every nullifier must have been proven against one of `accepted_roots`, e.g. the digests of
+    /// the commitment set the wallet has seen recently.
+    pub fn verify_off_chain(
+        &self,
+        accepted_roots: &HashSet<CommitmentSetDigest>,
+    ) -> Result<(), VerificationError> {
+        self.check_well_formed()?;
+
+        if let Some(index) = self
+            .message
+            .new_nullifiers
+            .iter()
+            .position(|(_, digest)| !accepted_roots.contains(digest))
+        {
+            return Err(VerificationError::UnknownNullifierDigest { index });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that do not depend on the chain state.
+    fn check_well_formed(&self) -> Result<(), VerificationError> {
+        let message = &self.message;
+        let witness_set = &self.witness_set;
+
+        // 1. Commitments or nullifiers are non empty
+        if message.new_commitments.is_empty() && message.new_nullifiers.is_empty() {
+            return Err(VerificationError::EmptyOutputs);
+        }
+
+        // 2. Check there are no duplicate account_ids in the public_account_ids list.
+        if n_unique(&message.public_account_ids) != message.public_account_ids.len() {
+            return Err(VerificationError::DuplicateAccountIds);
+        }
+
+        // Check there are no duplicate nullifiers in the new_nullifiers list
+        if n_unique(&message.new_nullifiers) != message.new_nullifiers.len() {
+            return Err(VerificationError::DuplicateNullifiers);
+        }
+
+        // Check there are no duplicate commitments in the new_commitments list
+        if n_unique(&message.new_commitments) != message.new_commitments.len() {
+            return Err(VerificationError::DuplicateCommitments);
+        }
+
+        // 3. Nonce checks and Valid signatures
+        // Check exactly one nonce is provided for each signature
+        if message.nonces.len() != witness_set.signatures_and_public_keys.len() {
+            return Err(VerificationError::NonceCountMismatch {
+                nonces: message.nonces.len(),
+                signatures: witness_set.signatures_and_public_keys.len(),
+            });
+        }
+
+        // Check the signatures are valid
+        if !witness_set.signatures_are_valid_for(message) {
+            return Err(VerificationError::InvalidSignature);
+        }
+
+        Ok(())
+    }
+
     pub fn message(&self) -> &Message {
         &self.message
     }
@@ -173,10 +215,18 @@ fn n_unique<T: Eq + Hash>(data: &[T]) -> usize {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
+    use nssa_core::{CommitmentSetDigest, Nullifier, NullifierPublicKey};
+    use proptest::prelude::*;
+
     use crate::{
         AccountId, PrivacyPreservingTransaction, PrivateKey, PublicKey,
         privacy_preserving_transaction::{
-            circuit::Proof, message::tests::message_for_tests, witness_set::WitnessSet,
+            VerificationError,
+            circuit::Proof,
+            message::{Message, tests::message_for_tests},
+            witness_set::WitnessSet,
         },
     };
 
@@ -208,4 +258,108 @@ mod tests {
         let tx_from_bytes = PrivacyPreservingTransaction::from_bytes(&bytes).unwrap();
         assert_eq!(tx, tx_from_bytes);
     }
+
+    fn signed_transaction(message: Message) -> PrivacyPreservingTransaction {
+        let (key1, key2, _, _) = keys_for_tests();
+        let witness_set = WitnessSet::for_message(&message, proof_for_tests(), &[&key1, &key2]);
+        PrivacyPreservingTransaction::new(message, witness_set)
+    }
+
+    fn well_formed_message() -> Message {
+        let mut message = message_for_tests();
+        message.nonces = vec![1, 2];
+        message
+    }
+
+    fn accepted_roots() -> HashSet<CommitmentSetDigest> {
+        HashSet::from([[0; 32], [7; 32]])
+    }
+
+    #[test]
+    fn test_verify_off_chain_accepts_well_formed_transaction() {
+        let tx = signed_transaction(well_formed_message());
+        assert_eq!(tx.verify_off_chain(&accepted_roots()), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_off_chain_accepts_nullifiers_proven_against_different_roots() {
+        let mut message = well_formed_message();
+        let nullifier = Nullifier::for_account_initialization(&NullifierPublicKey([5; 32]));
+        message.new_nullifiers.push((nullifier, [7; 32]));
+
+        let tx = signed_transaction(message);
+        assert_eq!(tx.verify_off_chain(&accepted_roots()), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_off_chain_rejects_malformed_transactions() {
+        let tx = signed_transaction(well_formed_message());
+        assert_eq!(
+            tx.verify_off_chain(&HashSet::from([[1; 32]])),
+            Err(VerificationError::UnknownNullifierDigest { index: 0 })
+        );
+
+        let mut message = well_formed_message();
+        message
+            .new_nullifiers
+            .push(message.new_nullifiers[0].clone());
+        assert_eq!(
+            signed_transaction(message).verify_off_chain(&accepted_roots()),
+            Err(VerificationError::DuplicateNullifiers)
+        );
+
+        let mut message = well_formed_message();
+        message.new_commitments.clear();
+        message.new_nullifiers.clear();
+        assert_eq!(
+            signed_transaction(message).verify_off_chain(&accepted_roots()),
+            Err(VerificationError::EmptyOutputs)
+        );
+
+        let mut message = well_formed_message();
+        message.nonces.push(3);
+        assert_eq!(
+            signed_transaction(message).verify_off_chain(&accepted_roots()),
+            Err(VerificationError::NonceCountMismatch {
+                nonces: 3,
+                signatures: 2
+            })
+        );
+
+        let mut tx = signed_transaction(well_formed_message());
+        tx.message.public_post_states[0].balance = 1;
+        assert_eq!(
+            tx.verify_off_chain(&accepted_roots()),
+            Err(VerificationError::InvalidSignature)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_verify_off_chain_never_accepts_tampered_message(
+            position in any::<prop::sample::Index>(),
+            mask in 1..=u8::MAX,
+        ) {
+            let tx = signed_transaction(well_formed_message());
+            let mut bytes = tx.to_bytes();
+            let position = position.index(bytes.len());
+            bytes[position] ^= mask;
+
+            // Malformed bytes must be rejected by decoding or verification, never by a panic
+            if let Ok(tampered) = PrivacyPreservingTransaction::from_bytes(&bytes)
+                && tampered.message != tx.message
+            {
+                prop_assert!(tampered.verify_off_chain(&accepted_roots()).is_err());
+            }
+        }
+
+        #[test]
+        fn test_verify_off_chain_does_not_panic_on_arbitrary_bytes(
+            bytes in prop::collection::vec(any::<u8>(), 0..512),
+        ) {
+            if let Ok(tx) = PrivacyPreservingTransaction::from_bytes(&bytes) {
+                let _ = tx.verify_off_chain(&accepted_roots());
+            }
+        }
+    }
 }
@@ -31,12 +31,12 @@ impl WitnessSet {
 
     pub fn signatures_are_valid_for(&self, message: &Message) -> bool {
         let message_bytes = message.to_bytes();
-        for (signature, public_key) in self.signatures_and_public_keys() {
-            if !signature.is_valid_for(&message_bytes, public_key) {
-                return false;
-            }
-        }
-        true
+        let items: Vec<_> = self
+            .signatures_and_public_keys()
+            .iter()
+            .map(|(signature, public_key)| (message_bytes.as_slice(), signature, public_key))
+            .collect();
+        Signature::batch_verify(&items)
     }
 
     pub fn signatures_and_public_keys(&self) -> &[(Signature, PublicKey)] {
@@ -2,8 +2,11 @@ use nssa_core::{
     account::AccountWithMetadata,
     program::{InstructionData, ProgramId, ProgramOutput},
 };
-use risc0_zkvm::{ExecutorEnv, ExecutorEnvBuilder, default_executor, serde::to_vec};
-use serde::Serialize;
+use risc0_zkvm::{
+    ExecutorEnv, ExecutorEnvBuilder, default_executor,
+    serde::{from_slice, to_vec},
+};
+use serde::{Serialize, de::DeserializeOwned};
 
 use crate::{
     error::NssaError,
@@ -14,7 +17,8 @@ use crate::{
 /// TODO: Make this variable when fees are implemented
 const MAX_NUM_CYCLES_PUBLIC_EXECUTION: u64 = 1024 * 1024 * 32; // 32M cycles
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Program {
     id: ProgramId,
     elf: Vec<u8>,
@@ -45,6 +49,13 @@ impl Program {
         to_vec(&instruction).map_err(|e| NssaError::InstructionSerializationError(e.to_string()))
     }
 
+    pub fn deserialize_instruction<T: DeserializeOwned>(
+        instruction_data: &InstructionData,
+    ) -> Result<T, NssaError> {
+        from_slice(instruction_data)
+            .map_err(|e| NssaError::InstructionDeserializationError(e.to_string()))
+    }
+
     pub(crate) fn execute(
         &self,
         pre_states: &[AccountWithMetadata],
@@ -257,6 +268,16 @@ mod tests {
         assert_eq!(recipient_post.account(), &expected_recipient_post);
     }
 
+    #[test]
+    fn test_instruction_serialization_roundtrip() {
+        let instruction: u128 = 11223344556677;
+
+        let instruction_data = Program::serialize_instruction(instruction).unwrap();
+        let decoded: u128 = Program::deserialize_instruction(&instruction_data).unwrap();
+
+        assert_eq!(decoded, instruction);
+    }
+
     #[test]
     fn test_builtin_programs() {
         let auth_transfer_program = Program::authenticated_transfer_program();
@@ -7,12 +7,26 @@ use serde::Serialize;
 
 use crate::{AccountId, error::NssaError, program::Program};
 
+/// Maximum size of the instruction data in bytes
+pub const MAX_INSTRUCTION_DATA_SIZE: usize = 65536;
+/// Maximum number of accounts a message can touch
+pub const MAX_ADDRESSES: usize = 64;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum MessageValidationError {
+    #[error("instruction data of {size} bytes exceeds the maximum of {max} bytes")]
+    MessageTooLarge { size: usize, max: usize },
+    #[error("message touches {count} accounts, the maximum is {max}")]
+    TooManyAddresses { count: usize, max: usize },
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub struct Message {
     pub(crate) program_id: ProgramId,
     pub(crate) account_ids: Vec<AccountId>,
     pub(crate) nonces: Vec<Nonce>,
     pub(crate) instruction_data: InstructionData,
+    pub(crate) fee: u128,
 }
 
 impl Message {
@@ -21,13 +35,139 @@ impl Message {
         account_ids: Vec<AccountId>,
         nonces: Vec<Nonce>,
         instruction: T,
+        fee: u128,
     ) -> Result<Self, NssaError> {
         let instruction_data = Program::serialize_instruction(instruction)?;
-        Ok(Self {
+        let message = Self {
+            program_id,
+            account_ids,
+            nonces,
+            instruction_data,
+            fee,
+        };
+        message.validate()?;
+        Ok(message)
+    }
+
+    /// Same as `try_new`, but takes instruction data that is already serialized
+    pub fn new_preserialized(
+        program_id: ProgramId,
+        account_ids: Vec<AccountId>,
+        nonces: Vec<Nonce>,
+        instruction_data: InstructionData,
+        fee: u128,
+    ) -> Self {
+        Self {
             program_id,
             account_ids,
             nonces,
             instruction_data,
-        })
+            fee,
+        }
+    }
+
+    /// Checks the message stays within the size limits the guest programs can handle
+    pub fn validate(&self) -> Result<(), MessageValidationError> {
+        let size = self.instruction_data.len() * size_of::<u32>();
+        if size > MAX_INSTRUCTION_DATA_SIZE {
+            return Err(MessageValidationError::MessageTooLarge {
+                size,
+                max: MAX_INSTRUCTION_DATA_SIZE,
+            });
+        }
+
+        let count = self.account_ids.len();
+        if count > MAX_ADDRESSES {
+            return Err(MessageValidationError::TooManyAddresses {
+                count,
+                max: MAX_ADDRESSES,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn program_id(&self) -> ProgramId {
+        self.program_id
+    }
+
+    pub fn account_ids(&self) -> &[AccountId] {
+        &self.account_ids
+    }
+
+    pub fn instruction_data(&self) -> &InstructionData {
+        &self.instruction_data
+    }
+
+    /// Native token fee paid by the first signer, on top of what the program moves
+    pub fn fee(&self) -> u128 {
+        self.fee
+    }
+
+    /// Same message paying `fee` instead, the encoded size doesn't change
+    pub fn with_fee(self, fee: u128) -> Self {
+        Self { fee, ..self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message_with_sizes(num_words: usize, num_accounts: usize) -> Message {
+        let account_ids = (0..num_accounts)
+            .map(|i| AccountId::new([i as u8; 32]))
+            .collect();
+        Message::new_preserialized([0; 8], account_ids, vec![], vec![0; num_words], 0)
+    }
+
+    #[test]
+    fn test_validate_accepts_instruction_data_at_limit() {
+        let message = message_with_sizes(MAX_INSTRUCTION_DATA_SIZE / 4, 1);
+        assert_eq!(message.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_rejects_instruction_data_over_limit() {
+        let message = message_with_sizes(MAX_INSTRUCTION_DATA_SIZE / 4 + 1, 1);
+        assert_eq!(
+            message.validate(),
+            Err(MessageValidationError::MessageTooLarge {
+                size: MAX_INSTRUCTION_DATA_SIZE + 4,
+                max: MAX_INSTRUCTION_DATA_SIZE,
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_accepts_accounts_at_limit() {
+        let message = message_with_sizes(1, MAX_ADDRESSES);
+        assert_eq!(message.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_rejects_accounts_over_limit() {
+        let message = message_with_sizes(1, MAX_ADDRESSES + 1);
+        assert_eq!(
+            message.validate(),
+            Err(MessageValidationError::TooManyAddresses {
+                count: MAX_ADDRESSES + 1,
+                max: MAX_ADDRESSES,
+            })
+        );
+    }
+
+    #[test]
+    fn test_try_new_rejects_too_many_accounts() {
+        let account_ids = (0..=MAX_ADDRESSES)
+            .map(|i| AccountId::new([i as u8; 32]))
+            .collect();
+        let result = Message::try_new([0; 8], account_ids, vec![], 0u128, 0);
+        assert!(matches!(
+            result,
+            Err(NssaError::InvalidMessage(
+                MessageValidationError::TooManyAddresses { .. }
+            ))
+        ));
     }
 }
@@ -2,6 +2,6 @@ mod message;
 mod transaction;
 mod witness_set;
 
-pub use message::Message;
+pub use message::{MAX_ADDRESSES, MAX_INSTRUCTION_DATA_SIZE, Message, MessageValidationError};
 pub use transaction::PublicTransaction;
 pub use witness_set::WitnessSet;
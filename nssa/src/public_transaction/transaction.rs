@@ -10,7 +10,8 @@ use sha2::{Digest, digest::FixedOutput};
 use crate::{
     V02State,
     error::NssaError,
-    public_transaction::{Message, WitnessSet},
+    program_methods::AUTHENTICATED_TRANSFER_ID,
+    public_transaction::{Message, MessageValidationError, WitnessSet},
     state::MAX_NUMBER_CHAINED_CALLS,
 };
 
@@ -21,11 +22,13 @@ pub struct PublicTransaction {
 }
 
 impl PublicTransaction {
-    pub fn new(message: Message, witness_set: WitnessSet) -> Self {
-        Self {
+    /// Fails if `message` exceeds the limits checked by [`Message::validate`]
+    pub fn new(message: Message, witness_set: WitnessSet) -> Result<Self, MessageValidationError> {
+        message.validate()?;
+        Ok(Self {
             message,
             witness_set,
-        }
+        })
     }
 
     pub fn message(&self) -> &Message {
@@ -58,6 +61,9 @@ impl PublicTransaction {
         let message = self.message();
         let witness_set = self.witness_set();
 
+        // Messages decoded from the wire bypass `Message::try_new`, so check the limits here
+        message.validate()?;
+
         // All account_ids must be different
         if message.account_ids.iter().collect::<HashSet<_>>().len() != message.account_ids.len() {
             return Err(NssaError::InvalidInput(
@@ -189,9 +195,58 @@ impl PublicTransaction {
             chain_calls_counter += 1;
         }
 
+        if message.fee > 0 {
+            self.charge_fee(state, &signer_account_ids, &mut state_diff)?;
+        }
+
         Ok(state_diff)
     }
 
+    /// Moves the message fee from the first signer to the fee collector, on top of the
+    /// post states computed by the program.
+    ///
+    /// Fee is paid in native tokens, so the payer must be owned by the authenticated transfer
+    /// program, the same as for any other native balance decrease.
+    fn charge_fee(
+        &self,
+        state: &V02State,
+        signer_account_ids: &[AccountId],
+        state_diff: &mut HashMap<AccountId, Account>,
+    ) -> Result<(), NssaError> {
+        let fee = self.message.fee;
+        let Some(payer_id) = signer_account_ids.first() else {
+            return Err(NssaError::InvalidInput(
+                "Fee requires at least one signer".into(),
+            ));
+        };
+
+        let mut payer = state_diff
+            .get(payer_id)
+            .cloned()
+            .unwrap_or_else(|| state.get_account_by_id(payer_id));
+        if payer.program_owner != AUTHENTICATED_TRANSFER_ID {
+            return Err(NssaError::InvalidFeePayer);
+        }
+        payer.balance = payer
+            .balance
+            .checked_sub(fee)
+            .ok_or(NssaError::InsufficientFundsForFee { fee })?;
+        state_diff.insert(*payer_id, payer);
+
+        let collector_id = V02State::FEE_COLLECTOR_ACCOUNT_ID;
+        let mut collector = state_diff
+            .get(&collector_id)
+            .cloned()
+            .unwrap_or_else(|| state.get_account_by_id(&collector_id));
+        collector.balance = collector
+            .balance
+            .checked_add(fee)
+            .ok_or_else(|| NssaError::InvalidInput("Fee collector balance overflow".into()))?;
+        state_diff.insert(collector_id, collector);
+
+        Ok(())
+    }
+
     fn compute_authorized_pdas(
         &self,
         caller_program_id: &Option<ProgramId>,
@@ -216,7 +271,9 @@ pub mod tests {
         AccountId, PrivateKey, PublicKey, PublicTransaction, Signature, V02State,
         error::NssaError,
         program::Program,
-        public_transaction::{Message, WitnessSet},
+        public_transaction::{
+            MAX_ADDRESSES, MAX_INSTRUCTION_DATA_SIZE, Message, MessageValidationError, WitnessSet,
+        },
     };
 
     fn keys_for_tests() -> (PrivateKey, PrivateKey, AccountId, AccountId) {
@@ -242,11 +299,12 @@ pub mod tests {
             vec![addr1, addr2],
             nonces,
             instruction,
+            0,
         )
         .unwrap();
 
         let witness_set = WitnessSet::for_message(&message, &[&key1, &key2]);
-        PublicTransaction::new(message, witness_set)
+        PublicTransaction::new(message, witness_set).unwrap()
     }
 
     #[test]
@@ -254,11 +312,61 @@ pub mod tests {
         let tx = transaction_for_tests();
         let message = tx.message().clone();
         let witness_set = tx.witness_set().clone();
-        let tx_from_constructor = PublicTransaction::new(message.clone(), witness_set.clone());
+        let tx_from_constructor =
+            PublicTransaction::new(message.clone(), witness_set.clone()).unwrap();
         assert_eq!(tx_from_constructor.message, message);
         assert_eq!(tx_from_constructor.witness_set, witness_set);
     }
 
+    #[test]
+    fn test_new_accepts_message_at_limits() {
+        let account_ids = (0..MAX_ADDRESSES)
+            .map(|i| AccountId::new([i as u8; 32]))
+            .collect();
+        let message = Message::new_preserialized(
+            [0; 8],
+            account_ids,
+            vec![],
+            vec![0; MAX_INSTRUCTION_DATA_SIZE / 4],
+            0,
+        );
+        let witness_set = WitnessSet::for_message(&message, &[]);
+
+        assert!(PublicTransaction::new(message, witness_set).is_ok());
+    }
+
+    #[test]
+    fn test_new_rejects_message_over_limits() {
+        let message = Message::new_preserialized(
+            [0; 8],
+            vec![],
+            vec![],
+            vec![0; MAX_INSTRUCTION_DATA_SIZE / 4 + 1],
+            0,
+        );
+        let witness_set = WitnessSet::for_message(&message, &[]);
+        assert_eq!(
+            PublicTransaction::new(message, witness_set),
+            Err(MessageValidationError::MessageTooLarge {
+                size: MAX_INSTRUCTION_DATA_SIZE + 4,
+                max: MAX_INSTRUCTION_DATA_SIZE,
+            })
+        );
+
+        let account_ids = (0..=MAX_ADDRESSES)
+            .map(|i| AccountId::new([i as u8; 32]))
+            .collect();
+        let message = Message::new_preserialized([0; 8], account_ids, vec![], vec![], 0);
+        let witness_set = WitnessSet::for_message(&message, &[]);
+        assert_eq!(
+            PublicTransaction::new(message, witness_set),
+            Err(MessageValidationError::TooManyAddresses {
+                count: MAX_ADDRESSES + 1,
+                max: MAX_ADDRESSES,
+            })
+        );
+    }
+
     #[test]
     fn test_message_getter() {
         let tx = transaction_for_tests();
@@ -320,11 +428,12 @@ pub mod tests {
             vec![addr1, addr1],
             nonces,
             instruction,
+            0,
         )
         .unwrap();
 
         let witness_set = WitnessSet::for_message(&message, &[&key1, &key1]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
         let result = tx.validate_and_produce_public_state_diff(&state);
         assert!(matches!(result, Err(NssaError::InvalidInput(_))))
     }
@@ -340,11 +449,12 @@ pub mod tests {
             vec![addr1, addr2],
             nonces,
             instruction,
+            0,
         )
         .unwrap();
 
         let witness_set = WitnessSet::for_message(&message, &[&key1, &key2]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
         let result = tx.validate_and_produce_public_state_diff(&state);
         assert!(matches!(result, Err(NssaError::InvalidInput(_))))
     }
@@ -360,12 +470,13 @@ pub mod tests {
             vec![addr1, addr2],
             nonces,
             instruction,
+            0,
         )
         .unwrap();
 
         let mut witness_set = WitnessSet::for_message(&message, &[&key1, &key2]);
         witness_set.signatures_and_public_keys[0].0 = Signature::new_for_tests([1; 64]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
         let result = tx.validate_and_produce_public_state_diff(&state);
         assert!(matches!(result, Err(NssaError::InvalidInput(_))))
     }
@@ -381,11 +492,12 @@ pub mod tests {
             vec![addr1, addr2],
             nonces,
             instruction,
+            0,
         )
         .unwrap();
 
         let witness_set = WitnessSet::for_message(&message, &[&key1, &key2]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
         let result = tx.validate_and_produce_public_state_diff(&state);
         assert!(matches!(result, Err(NssaError::InvalidInput(_))))
     }
@@ -397,11 +509,17 @@ pub mod tests {
         let nonces = vec![0, 0];
         let instruction = 1337;
         let unknown_program_id = [0xdeadbeef; 8];
-        let message =
-            Message::try_new(unknown_program_id, vec![addr1, addr2], nonces, instruction).unwrap();
+        let message = Message::try_new(
+            unknown_program_id,
+            vec![addr1, addr2],
+            nonces,
+            instruction,
+            0,
+        )
+        .unwrap();
 
         let witness_set = WitnessSet::for_message(&message, &[&key1, &key2]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
         let result = tx.validate_and_produce_public_state_diff(&state);
         assert!(matches!(result, Err(NssaError::InvalidInput(_))))
     }
@@ -1,6 +1,8 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 
-use crate::{PrivateKey, PublicKey, Signature, public_transaction::Message};
+use crate::{
+    PrivateKey, PublicKey, Signature, Signer, error::NssaError, public_transaction::Message,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub struct WitnessSet {
@@ -24,14 +26,29 @@ impl WitnessSet {
         }
     }
 
+    /// Same as `for_message`, but signatures are produced by arbitrary signers
+    pub fn for_message_with_signers(
+        message: &Message,
+        signers: &[&dyn Signer],
+    ) -> Result<Self, NssaError> {
+        let message_bytes = message.to_bytes();
+        let signatures_and_public_keys = signers
+            .iter()
+            .map(|signer| Ok((signer.sign(&message_bytes)?, signer.public_key())))
+            .collect::<Result<_, NssaError>>()?;
+        Ok(Self {
+            signatures_and_public_keys,
+        })
+    }
+
     pub fn is_valid_for(&self, message: &Message) -> bool {
         let message_bytes = message.to_bytes();
-        for (signature, public_key) in self.signatures_and_public_keys() {
-            if !signature.is_valid_for(&message_bytes, public_key) {
-                return false;
-            }
-        }
-        true
+        let items: Vec<_> = self
+            .signatures_and_public_keys()
+            .iter()
+            .map(|(signature, public_key)| (message_bytes.as_slice(), signature, public_key))
+            .collect();
+        Signature::batch_verify(&items)
     }
 
     pub fn signatures_and_public_keys(&self) -> &[(Signature, PublicKey)] {
@@ -54,7 +71,7 @@ mod tests {
         let addr2 = AccountId::from(&pubkey2);
         let nonces = vec![1, 2];
         let instruction = vec![1, 2, 3, 4];
-        let message = Message::try_new([0; 8], vec![addr1, addr2], nonces, instruction).unwrap();
+        let message = Message::try_new([0; 8], vec![addr1, addr2], nonces, instruction, 0).unwrap();
 
         let witness_set = WitnessSet::for_message(&message, &[&key1, &key2]);
 
@@ -70,4 +87,55 @@ mod tests {
             assert!(signature.is_valid_for(&message_bytes, &expected_public_key));
         }
     }
+
+    /// Signer standing in for an external device, which is disconnected
+    struct DisconnectedSigner(PublicKey);
+
+    impl Signer for DisconnectedSigner {
+        fn sign(&self, _message: &[u8]) -> Result<Signature, NssaError> {
+            Err(NssaError::SigningFailed("device disconnected".to_string()))
+        }
+
+        fn public_key(&self) -> PublicKey {
+            self.0.clone()
+        }
+    }
+
+    #[test]
+    fn test_for_message_with_signers() {
+        let key1 = PrivateKey::try_new([1; 32]).unwrap();
+        let key2 = PrivateKey::try_new([2; 32]).unwrap();
+        let addr1 = AccountId::from(&PublicKey::new_from_private_key(&key1));
+        let addr2 = AccountId::from(&PublicKey::new_from_private_key(&key2));
+        let message =
+            Message::try_new([0; 8], vec![addr1, addr2], vec![1, 2], vec![1, 2, 3, 4], 0).unwrap();
+
+        let witness_set = WitnessSet::for_message_with_signers(&message, &[&key1, &key2]).unwrap();
+
+        assert!(witness_set.is_valid_for(&message));
+        assert_eq!(
+            witness_set
+                .signatures_and_public_keys
+                .into_iter()
+                .map(|(_, public_key)| public_key)
+                .collect::<Vec<_>>(),
+            vec![
+                PublicKey::new_from_private_key(&key1),
+                PublicKey::new_from_private_key(&key2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_for_message_with_failing_signer() {
+        let key = PrivateKey::try_new([1; 32]).unwrap();
+        let disconnected_signer = DisconnectedSigner(PublicKey::new_from_private_key(
+            &PrivateKey::try_new([2; 32]).unwrap(),
+        ));
+        let message = Message::try_new([0; 8], vec![], vec![], vec![1, 2, 3, 4], 0).unwrap();
+
+        let result = WitnessSet::for_message_with_signers(&message, &[&key, &disconnected_signer]);
+
+        assert!(matches!(result, Err(NssaError::SigningFailed(_))));
+    }
 }
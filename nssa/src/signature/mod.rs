@@ -6,6 +6,8 @@ pub use private_key::PrivateKey;
 pub use public_key::PublicKey;
 use rand::{RngCore, rngs::OsRng};
 
+use crate::error::NssaError;
+
 #[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub struct Signature {
     value: [u8; 64],
@@ -34,25 +36,83 @@ impl Signature {
     }
 
     pub fn is_valid_for(&self, bytes: &[u8], public_key: &PublicKey) -> bool {
+        let secp = secp256k1::Secp256k1::verification_only();
+        self.is_valid_for_with_context(&secp, bytes, public_key)
+    }
+
+    /// Checks that every signature in `items` is valid for its message and public key.
+    ///
+    /// `secp256k1` does not expose schnorr batch verification, so signatures are checked one by
+    /// one sharing a single verification context.
+    pub fn batch_verify(items: &[(&[u8], &Signature, &PublicKey)]) -> bool {
+        let secp = secp256k1::Secp256k1::verification_only();
+        items.iter().all(|(bytes, signature, public_key)| {
+            signature.is_valid_for_with_context(&secp, bytes, public_key)
+        })
+    }
+
+    fn is_valid_for_with_context(
+        &self,
+        secp: &secp256k1::Secp256k1<secp256k1::VerifyOnly>,
+        bytes: &[u8],
+        public_key: &PublicKey,
+    ) -> bool {
         let pk = secp256k1::XOnlyPublicKey::from_byte_array(*public_key.value()).unwrap();
-        let secp = secp256k1::Secp256k1::new();
         let sig = secp256k1::schnorr::Signature::from_byte_array(self.value);
         secp.verify_schnorr(&sig, bytes, &pk).is_ok()
     }
 }
 
+/// Source of schnorr signatures, allows signing with keys held outside of the process
+/// (e.g. on a hardware wallet)
+pub trait Signer {
+    fn sign(&self, message: &[u8]) -> Result<Signature, NssaError>;
+
+    fn public_key(&self) -> PublicKey;
+}
+
+impl Signer for PrivateKey {
+    fn sign(&self, message: &[u8]) -> Result<Signature, NssaError> {
+        Ok(Signature::new(self, message))
+    }
+
+    fn public_key(&self) -> PublicKey {
+        PublicKey::new_from_private_key(self)
+    }
+}
+
 #[cfg(test)]
 mod bip340_test_vectors;
 
 #[cfg(test)]
 mod tests {
 
-    use crate::{Signature, signature::bip340_test_vectors};
+    use hkdf::Hkdf;
+    use sha2::Sha256;
+
+    use crate::{PrivateKey, Signature, signature::bip340_test_vectors};
 
     impl Signature {
         pub(crate) fn new_for_tests(value: [u8; 64]) -> Self {
             Self { value }
         }
+
+        /// Same as `Signature::new`, but the aux random is derived as
+        /// `HKDF-SHA256(seed || message || key)` instead of being sampled from `OsRng`,
+        /// so that signatures produced in tests are reproducible.
+        pub(crate) fn new_deterministic(key: &PrivateKey, message: &[u8], seed: [u8; 32]) -> Self {
+            let mut input_key_material = Vec::with_capacity(seed.len() + message.len() + 32);
+            input_key_material.extend_from_slice(&seed);
+            input_key_material.extend_from_slice(message);
+            input_key_material.extend_from_slice(key.value());
+
+            let mut aux_random = [0u8; 32];
+            Hkdf::<Sha256>::new(None, &input_key_material)
+                .expand(&[], &mut aux_random)
+                .expect("32 bytes is a valid length for HKDF-SHA256 output");
+
+            Self::new_with_aux_random(key, message, aux_random)
+        }
     }
 
     #[test]
@@ -91,4 +151,79 @@ mod tests {
             assert_eq!(result, expected_result, "Failed test vector {i}");
         }
     }
+
+    #[test]
+    fn test_new_deterministic_is_reproducible() {
+        let key = PrivateKey::try_new([1; 32]).unwrap();
+        let message = b"deterministic message";
+        let seed = [7; 32];
+
+        let signature_1 = Signature::new_deterministic(&key, message, seed);
+        let signature_2 = Signature::new_deterministic(&key, message, seed);
+
+        assert_eq!(signature_1, signature_2);
+    }
+
+    #[test]
+    fn test_new_deterministic_depends_on_seed() {
+        let key = PrivateKey::try_new([1; 32]).unwrap();
+        let message = b"deterministic message";
+
+        let signature_1 = Signature::new_deterministic(&key, message, [7; 32]);
+        let signature_2 = Signature::new_deterministic(&key, message, [8; 32]);
+
+        assert_ne!(signature_1, signature_2);
+    }
+
+    #[test]
+    fn test_new_deterministic_is_valid() {
+        let key = PrivateKey::try_new([1; 32]).unwrap();
+        let public_key = crate::PublicKey::new_from_private_key(&key);
+        let message = b"deterministic message";
+
+        let signature = Signature::new_deterministic(&key, message, [7; 32]);
+
+        assert!(signature.is_valid_for(message, &public_key));
+    }
+
+    #[test]
+    fn test_batch_verify_from_bip340_test_vectors() {
+        let test_vectors = bip340_test_vectors::test_vectors();
+        let messages: Vec<_> = test_vectors
+            .iter()
+            .map(|test_vector| test_vector.message.clone().unwrap_or_default())
+            .collect();
+        let items_with_result: Vec<_> = test_vectors
+            .iter()
+            .zip(&messages)
+            .map(|(test_vector, message)| {
+                (
+                    (
+                        message.as_slice(),
+                        &test_vector.signature,
+                        &test_vector.pubkey,
+                    ),
+                    test_vector.verification_result,
+                )
+            })
+            .collect();
+        let (valid_items, invalid_items): (Vec<_>, Vec<_>) = items_with_result
+            .into_iter()
+            .partition(|(_, verification_result)| *verification_result);
+        let valid_items: Vec<_> = valid_items.into_iter().map(|(item, _)| item).collect();
+        let invalid_items: Vec<_> = invalid_items.into_iter().map(|(item, _)| item).collect();
+        assert!(!valid_items.is_empty() && !invalid_items.is_empty());
+
+        assert!(Signature::batch_verify(&valid_items));
+        for invalid_item in invalid_items {
+            let mut mixed_items = valid_items.clone();
+            mixed_items.insert(mixed_items.len() / 2, invalid_item);
+            assert!(!Signature::batch_verify(&mixed_items));
+        }
+    }
+
+    #[test]
+    fn test_batch_verify_empty_batch_is_valid() {
+        assert!(Signature::batch_verify(&[]));
+    }
 }
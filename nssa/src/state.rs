@@ -5,6 +5,8 @@ use nssa_core::{
     account::{Account, AccountId},
     program::ProgramId,
 };
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 use crate::{
     error::NssaError, merkle_tree::MerkleTree,
@@ -15,6 +17,8 @@ use crate::{
 
 pub const MAX_NUMBER_CHAINED_CALLS: usize = 10;
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub(crate) struct CommitmentSet {
     merkle_tree: MerkleTree,
     commitments: HashMap<Commitment, usize>,
@@ -37,10 +41,11 @@ impl CommitmentSet {
 
     /// Inserts a list of commitments to the `CommitmentSet`.
     pub(crate) fn extend(&mut self, commitments: &[Commitment]) {
-        for commitment in commitments.iter().cloned() {
-            let index = self.merkle_tree.insert(commitment.to_byte_array());
-            self.commitments.insert(commitment, index);
-        }
+        let values: Vec<_> = commitments.iter().map(Commitment::to_byte_array).collect();
+        let first_index = self.merkle_tree.len();
+        self.merkle_tree.batch_insert(&values);
+        self.commitments
+            .extend(commitments.iter().cloned().zip(first_index..));
         self.root_history.insert(self.digest());
     }
 
@@ -60,8 +65,53 @@ impl CommitmentSet {
     }
 }
 
-type NullifierSet = HashSet<Nullifier>;
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) struct NullifierSet {
+    merkle_tree: MerkleTree,
+    nullifiers: HashMap<Nullifier, usize>,
+}
+
+impl NullifierSet {
+    pub(crate) fn digest(&self) -> [u8; 32] {
+        self.merkle_tree.root()
+    }
+
+    /// Queries the `NullifierSet` for a membership proof of nullifier
+    pub fn get_proof_for(&self, nullifier: &Nullifier) -> Option<MembershipProof> {
+        let index = *self.nullifiers.get(nullifier)?;
+
+        self.merkle_tree
+            .get_authentication_path_for(index)
+            .map(|path| (index, path))
+    }
+
+    /// Inserts a list of nullifiers to the `NullifierSet`.
+    pub(crate) fn extend(&mut self, nullifiers: &[Nullifier]) {
+        let values: Vec<_> = nullifiers.iter().map(Nullifier::to_byte_array).collect();
+        let first_index = self.merkle_tree.len();
+        self.merkle_tree.batch_insert(&values);
+        self.nullifiers
+            .extend(nullifiers.iter().cloned().zip(first_index..));
+    }
+
+    fn contains(&self, nullifier: &Nullifier) -> bool {
+        self.nullifiers.contains_key(nullifier)
+    }
+
+    /// Initializes an empty `NullifierSet` with a given capacity.
+    /// If the capacity is not a power_of_two, then capacity is taken
+    /// to be the next power_of_two.
+    pub(crate) fn with_capacity(capacity: usize) -> NullifierSet {
+        Self {
+            merkle_tree: MerkleTree::with_capacity(capacity),
+            nullifiers: HashMap::new(),
+        }
+    }
+}
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct V02State {
     public_state: HashMap<AccountId, Account>,
     private_state: (CommitmentSet, NullifierSet),
@@ -69,6 +119,9 @@ pub struct V02State {
 }
 
 impl V02State {
+    /// Account credited with the fees of public transactions
+    pub const FEE_COLLECTOR_ACCOUNT_ID: AccountId = AccountId::new([0xfe; 32]);
+
     pub fn new_with_genesis_accounts(
         initial_data: &[(AccountId, u128)],
         initial_commitments: &[nssa_core::Commitment],
@@ -93,7 +146,7 @@ impl V02State {
 
         let mut this = Self {
             public_state,
-            private_state: (private_state, NullifierSet::new()),
+            private_state: (private_state, NullifierSet::with_capacity(32)),
             programs: HashMap::new(),
         };
 
@@ -146,7 +199,7 @@ impl V02State {
             .cloned()
             .map(|(nullifier, _)| nullifier)
             .collect::<Vec<Nullifier>>();
-        self.private_state.1.extend(new_nullifiers);
+        self.private_state.1.extend(&new_nullifiers);
 
         // 4. Update public accounts
         for (account_id, post) in public_state_diff.into_iter() {
@@ -181,6 +234,15 @@ impl V02State {
         self.private_state.0.get_proof_for(commitment)
     }
 
+    /// Returns membership proof of spent nullifier together with current nullifier set root
+    pub fn get_proof_for_nullifier(
+        &self,
+        nullifier: &Nullifier,
+    ) -> Option<(MembershipProof, [u8; 32])> {
+        let proof = self.private_state.1.get_proof_for(nullifier)?;
+        Some((proof, self.private_state.1.digest()))
+    }
+
     pub(crate) fn programs(&self) -> &HashMap<ProgramId, Program> {
         &self.programs
     }
@@ -189,6 +251,11 @@ impl V02State {
         self.private_state.0.digest()
     }
 
+    /// Root of the Merkle tree of spent nullifiers, in order of spending
+    pub fn nullifier_set_digest(&self) -> [u8; 32] {
+        self.private_state.1.digest()
+    }
+
     pub(crate) fn check_commitments_are_new(
         &self,
         new_commitments: &[Commitment],
@@ -286,14 +353,26 @@ pub mod tests {
         nonce: u128,
         to: AccountId,
         balance: u128,
+    ) -> PublicTransaction {
+        transfer_transaction_with_fee(from, from_key, nonce, to, balance, 0)
+    }
+
+    fn transfer_transaction_with_fee(
+        from: AccountId,
+        from_key: PrivateKey,
+        nonce: u128,
+        to: AccountId,
+        balance: u128,
+        fee: u128,
     ) -> PublicTransaction {
         let account_ids = vec![from, to];
         let nonces = vec![nonce];
         let program_id = Program::authenticated_transfer_program().id();
         let message =
-            public_transaction::Message::try_new(program_id, account_ids, nonces, balance).unwrap();
+            public_transaction::Message::try_new(program_id, account_ids, nonces, balance, fee)
+                .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[&from_key]);
-        PublicTransaction::new(message, witness_set)
+        PublicTransaction::new(message, witness_set).unwrap()
     }
 
     #[test]
@@ -475,6 +554,121 @@ pub mod tests {
         assert_eq!(state.get_account_by_id(&account_id3).nonce, 0);
     }
 
+    #[test]
+    fn transition_from_replayed_public_transaction_should_fail() {
+        let key = PrivateKey::try_new([1; 32]).unwrap();
+        let account_id = AccountId::from(&PublicKey::new_from_private_key(&key));
+        let initial_data = [(account_id, 100)];
+        let mut state = V02State::new_with_genesis_accounts(&initial_data, &[]);
+        let to = AccountId::new([2; 32]);
+
+        let tx = transfer_transaction(account_id, key, 0, to, 5);
+        state.transition_from_public_transaction(&tx).unwrap();
+        let result = state.transition_from_public_transaction(&tx);
+
+        assert!(matches!(result, Err(NssaError::InvalidInput(_))));
+        assert_eq!(state.get_account_by_id(&account_id).balance, 95);
+        assert_eq!(state.get_account_by_id(&to).balance, 5);
+        assert_eq!(state.get_account_by_id(&account_id).nonce, 1);
+    }
+
+    #[test]
+    fn transition_from_public_transaction_increments_each_signer_nonce_once() {
+        let key1 = PrivateKey::try_new([1; 32]).unwrap();
+        let key2 = PrivateKey::try_new([2; 32]).unwrap();
+        let account_id1 = AccountId::from(&PublicKey::new_from_private_key(&key1));
+        let account_id2 = AccountId::from(&PublicKey::new_from_private_key(&key2));
+        let initial_data = [(account_id1, 100), (account_id2, 200)];
+        let mut state = V02State::new_with_genesis_accounts(&initial_data, &[]);
+
+        for nonce in 0..3 {
+            let tx = transfer_transaction(account_id1, key1.clone(), nonce, account_id2, 1);
+            state.transition_from_public_transaction(&tx).unwrap();
+            assert_eq!(state.get_account_by_id(&account_id1).nonce, nonce + 1);
+            assert_eq!(state.get_account_by_id(&account_id2).nonce, 0);
+        }
+
+        // A transaction signed with an already consumed nonce is rejected
+        let stale_tx = transfer_transaction(account_id1, key1, 1, account_id2, 1);
+        let result = state.transition_from_public_transaction(&stale_tx);
+        assert!(matches!(result, Err(NssaError::InvalidInput(_))));
+        assert_eq!(state.get_account_by_id(&account_id1).nonce, 3);
+    }
+
+    #[test]
+    fn transition_from_public_transaction_charges_fee_to_first_signer() {
+        let key = PrivateKey::try_new([1; 32]).unwrap();
+        let account_id = AccountId::from(&PublicKey::new_from_private_key(&key));
+        let initial_data = [(account_id, 100)];
+        let mut state = V02State::new_with_genesis_accounts(&initial_data, &[]);
+        let to = AccountId::new([2; 32]);
+
+        let tx = transfer_transaction_with_fee(account_id, key, 0, to, 10, 5);
+        state.transition_from_public_transaction(&tx).unwrap();
+
+        assert_eq!(state.get_account_by_id(&account_id).balance, 85);
+        assert_eq!(state.get_account_by_id(&to).balance, 10);
+        assert_eq!(
+            state
+                .get_account_by_id(&V02State::FEE_COLLECTOR_ACCOUNT_ID)
+                .balance,
+            5
+        );
+    }
+
+    #[test]
+    fn transition_from_public_transaction_with_fee_payer_not_owned_by_native_program_should_fail() {
+        let key = PrivateKey::try_new([1; 32]).unwrap();
+        let account_id = AccountId::from(&PublicKey::new_from_private_key(&key));
+        let program_id = Program::simple_balance_transfer().id();
+        let mut state = V02State::new_with_genesis_accounts(&[], &[]).with_test_programs();
+        state.force_insert_account(
+            account_id,
+            Account {
+                program_owner: program_id,
+                balance: 100,
+                ..Account::default()
+            },
+        );
+        let to = AccountId::new([2; 32]);
+        let message = public_transaction::Message::try_new(
+            program_id,
+            vec![account_id, to],
+            vec![0],
+            10u128,
+            5,
+        )
+        .unwrap();
+        let witness_set = public_transaction::WitnessSet::for_message(&message, &[&key]);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
+
+        let result = state.transition_from_public_transaction(&tx);
+
+        assert!(matches!(result, Err(NssaError::InvalidFeePayer)));
+        assert_eq!(state.get_account_by_id(&account_id).balance, 100);
+        assert_eq!(state.get_account_by_id(&to).balance, 0);
+    }
+
+    #[test]
+    fn transition_from_public_transaction_without_funds_for_fee_and_amount_should_fail() {
+        let key = PrivateKey::try_new([1; 32]).unwrap();
+        let account_id = AccountId::from(&PublicKey::new_from_private_key(&key));
+        let initial_data = [(account_id, 100)];
+        let mut state = V02State::new_with_genesis_accounts(&initial_data, &[]);
+        let to = AccountId::new([2; 32]);
+
+        let tx = transfer_transaction_with_fee(account_id, key, 0, to, 60, 50);
+        let result = state.transition_from_public_transaction(&tx);
+
+        assert!(matches!(
+            result,
+            Err(NssaError::InsufficientFundsForFee { fee: 50 })
+        ));
+        assert_eq!(state.get_account_by_id(&account_id).balance, 100);
+        assert_eq!(state.get_account_by_id(&account_id).nonce, 0);
+        assert_eq!(state.get_account_by_id(&to).balance, 0);
+    }
+
     impl V02State {
         pub fn force_insert_account(&mut self, account_id: AccountId, account: Account) {
             self.public_state.insert(account_id, account);
@@ -548,9 +742,9 @@ pub mod tests {
         let account_ids = vec![AccountId::new([1; 32])];
         let program_id = Program::nonce_changer_program().id();
         let message =
-            public_transaction::Message::try_new(program_id, account_ids, vec![], ()).unwrap();
+            public_transaction::Message::try_new(program_id, account_ids, vec![], (), 0).unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         let result = state.transition_from_public_transaction(&tx);
 
@@ -565,9 +759,9 @@ pub mod tests {
         let account_ids = vec![AccountId::new([1; 32])];
         let program_id = Program::extra_output_program().id();
         let message =
-            public_transaction::Message::try_new(program_id, account_ids, vec![], ()).unwrap();
+            public_transaction::Message::try_new(program_id, account_ids, vec![], (), 0).unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         let result = state.transition_from_public_transaction(&tx);
 
@@ -582,9 +776,9 @@ pub mod tests {
         let account_ids = vec![AccountId::new([1; 32]), AccountId::new([2; 32])];
         let program_id = Program::missing_output_program().id();
         let message =
-            public_transaction::Message::try_new(program_id, account_ids, vec![], ()).unwrap();
+            public_transaction::Message::try_new(program_id, account_ids, vec![], (), 0).unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         let result = state.transition_from_public_transaction(&tx);
 
@@ -606,9 +800,10 @@ pub mod tests {
         assert_eq!(account.data, Account::default().data);
         let program_id = Program::program_owner_changer().id();
         let message =
-            public_transaction::Message::try_new(program_id, vec![account_id], vec![], ()).unwrap();
+            public_transaction::Message::try_new(program_id, vec![account_id], vec![], (), 0)
+                .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         let result = state.transition_from_public_transaction(&tx);
 
@@ -630,9 +825,10 @@ pub mod tests {
         assert_eq!(account.data, Account::default().data);
         let program_id = Program::program_owner_changer().id();
         let message =
-            public_transaction::Message::try_new(program_id, vec![account_id], vec![], ()).unwrap();
+            public_transaction::Message::try_new(program_id, vec![account_id], vec![], (), 0)
+                .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         let result = state.transition_from_public_transaction(&tx);
 
@@ -654,9 +850,10 @@ pub mod tests {
         assert_eq!(account.data, Account::default().data);
         let program_id = Program::program_owner_changer().id();
         let message =
-            public_transaction::Message::try_new(program_id, vec![account_id], vec![], ()).unwrap();
+            public_transaction::Message::try_new(program_id, vec![account_id], vec![], (), 0)
+                .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         let result = state.transition_from_public_transaction(&tx);
 
@@ -678,9 +875,10 @@ pub mod tests {
         assert_ne!(account.data, Account::default().data);
         let program_id = Program::program_owner_changer().id();
         let message =
-            public_transaction::Message::try_new(program_id, vec![account_id], vec![], ()).unwrap();
+            public_transaction::Message::try_new(program_id, vec![account_id], vec![], (), 0)
+                .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         let result = state.transition_from_public_transaction(&tx);
 
@@ -705,10 +903,11 @@ pub mod tests {
             vec![sender_account_id, receiver_account_id],
             vec![],
             balance_to_move,
+            0,
         )
         .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         let result = state.transition_from_public_transaction(&tx);
 
@@ -730,9 +929,10 @@ pub mod tests {
             program_id
         );
         let message =
-            public_transaction::Message::try_new(program_id, vec![account_id], vec![], ()).unwrap();
+            public_transaction::Message::try_new(program_id, vec![account_id], vec![], (), 0)
+                .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         let result = state.transition_from_public_transaction(&tx);
 
@@ -748,9 +948,10 @@ pub mod tests {
         let program_id = Program::minter().id();
 
         let message =
-            public_transaction::Message::try_new(program_id, vec![account_id], vec![], ()).unwrap();
+            public_transaction::Message::try_new(program_id, vec![account_id], vec![], (), 0)
+                .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         let result = state.transition_from_public_transaction(&tx);
 
@@ -777,10 +978,11 @@ pub mod tests {
             vec![account_id],
             vec![],
             balance_to_burn,
+            0,
         )
         .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
         let result = state.transition_from_public_transaction(&tx);
 
         assert!(matches!(result, Err(NssaError::InvalidProgramBehavior)));
@@ -2089,10 +2291,10 @@ pub mod tests {
         };
 
         let message =
-            public_transaction::Message::try_new(program.id(), vec![from, to], vec![0], amount)
+            public_transaction::Message::try_new(program.id(), vec![from, to], vec![0], amount, 0)
                 .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[&from_key]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         state.transition_from_public_transaction(&tx).unwrap();
 
@@ -2132,10 +2334,11 @@ pub mod tests {
             // call
             vec![0],
             instruction,
+            0,
         )
         .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[&from_key]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         state.transition_from_public_transaction(&tx).unwrap();
 
@@ -2171,10 +2374,11 @@ pub mod tests {
             // call
             vec![0],
             instruction,
+            0,
         )
         .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[&from_key]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         let result = state.transition_from_public_transaction(&tx);
         assert!(matches!(
@@ -2212,10 +2416,11 @@ pub mod tests {
             // call
             vec![],
             instruction,
+            0,
         )
         .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         state.transition_from_public_transaction(&tx).unwrap();
 
@@ -2268,10 +2473,11 @@ pub mod tests {
             // call
             vec![0],
             instruction,
+            0,
         )
         .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[&from_key]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         state.transition_from_public_transaction(&tx).unwrap();
 
@@ -2317,10 +2523,11 @@ pub mod tests {
             vec![pinata_token_definition_id, pinata_token_holding_id],
             vec![],
             instruction,
+            0,
         )
         .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
         state.transition_from_public_transaction(&tx).unwrap();
 
         // Execution of the token program transfer just to initialize the winner token account
@@ -2331,10 +2538,11 @@ pub mod tests {
             vec![pinata_token_definition_id, winner_token_holding_id],
             vec![],
             instruction,
+            0,
         )
         .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
         state.transition_from_public_transaction(&tx).unwrap();
 
         // Submit a solution to the pinata program to claim the prize
@@ -2348,10 +2556,11 @@ pub mod tests {
             ],
             vec![],
             solution,
+            0,
         )
         .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
         state.transition_from_public_transaction(&tx).unwrap();
 
         let winner_token_holding_post = state.get_account_by_id(&winner_token_holding_id);
@@ -2377,13 +2586,133 @@ pub mod tests {
         );
 
         let message =
-            public_transaction::Message::try_new(claimer.id(), vec![account_id], vec![], ())
+            public_transaction::Message::try_new(claimer.id(), vec![account_id], vec![], (), 0)
                 .unwrap();
         let witness_set = public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = PublicTransaction::new(message, witness_set).unwrap();
 
         let result = state.transition_from_public_transaction(&tx);
 
         assert!(matches!(result, Err(NssaError::InvalidProgramBehavior)))
     }
+
+    #[test]
+    fn test_nullifier_set_proof_for_spent_nullifier() {
+        let mut nullifier_set = super::NullifierSet::with_capacity(4);
+        let nullifiers: Vec<_> = (0..5u8)
+            .map(|i| Nullifier::for_account_initialization(&NullifierPublicKey([i; 32])))
+            .collect();
+        nullifier_set.extend(&nullifiers);
+
+        for nullifier in &nullifiers {
+            let proof = nullifier_set.get_proof_for(nullifier).unwrap();
+            assert_eq!(
+                nssa_core::compute_digest_for_value_path(&nullifier.to_byte_array(), &proof),
+                nullifier_set.digest()
+            );
+        }
+    }
+
+    #[test]
+    fn test_nullifier_set_no_proof_for_unspent_nullifier() {
+        let mut nullifier_set = super::NullifierSet::with_capacity(4);
+        nullifier_set.extend(&[Nullifier::for_account_initialization(&NullifierPublicKey(
+            [1; 32],
+        ))]);
+
+        let unspent = Nullifier::for_account_initialization(&NullifierPublicKey([2; 32]));
+
+        assert!(nullifier_set.get_proof_for(&unspent).is_none());
+    }
+
+    #[test]
+    fn test_nullifier_set_digest_is_deterministic() {
+        let nullifiers: Vec<_> = (0..5u8)
+            .map(|i| Nullifier::for_account_initialization(&NullifierPublicKey([i; 32])))
+            .collect();
+
+        let mut nullifier_set_1 = super::NullifierSet::with_capacity(4);
+        let mut nullifier_set_2 = super::NullifierSet::with_capacity(32);
+        nullifier_set_1.extend(&nullifiers);
+        nullifier_set_2.extend(&nullifiers[..2]);
+        nullifier_set_2.extend(&nullifiers[2..]);
+        assert_eq!(nullifier_set_1.digest(), nullifier_set_2.digest());
+
+        let mut nullifier_set_3 = super::NullifierSet::with_capacity(4);
+        nullifier_set_3.extend(&nullifiers[..4]);
+        assert_ne!(nullifier_set_1.digest(), nullifier_set_3.digest());
+    }
+
+    #[test]
+    fn test_commitment_set_extend_matches_sequential_insert() {
+        let commitments: Vec<_> = (0..9u8)
+            .map(|i| Commitment::new(&NullifierPublicKey([i; 32]), &Account::default()))
+            .collect();
+
+        let mut batch_set = super::CommitmentSet::with_capacity(2);
+        batch_set.extend(&commitments[..3]);
+        batch_set.extend(&commitments[3..]);
+
+        let mut sequential_set = super::CommitmentSet::with_capacity(2);
+        for commitment in &commitments {
+            sequential_set.extend(std::slice::from_ref(commitment));
+        }
+
+        assert_eq!(batch_set.digest(), sequential_set.digest());
+        for commitment in &commitments {
+            assert_eq!(
+                batch_set.get_proof_for(commitment),
+                sequential_set.get_proof_for(commitment)
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_state_serde_roundtrip() {
+        let account_id = AccountId::new([1; 32]);
+        let account = Account {
+            balance: 100,
+            nonce: 3,
+            ..Account::default()
+        };
+        let commitments: Vec<_> = (0..5u8)
+            .map(|i| Commitment::new(&NullifierPublicKey([i; 32]), &account))
+            .collect();
+        let nullifier = Nullifier::for_account_initialization(&NullifierPublicKey([7; 32]));
+
+        let mut commitment_set = super::CommitmentSet::with_capacity(2);
+        commitment_set.extend(&commitments);
+        let mut nullifier_set = super::NullifierSet::with_capacity(2);
+        nullifier_set.extend(std::slice::from_ref(&nullifier));
+        let state = V02State {
+            public_state: HashMap::from([(account_id, account.clone())]),
+            private_state: (commitment_set, nullifier_set),
+            programs: HashMap::new(),
+        };
+
+        let serialized = bincode::serialize(&state).unwrap();
+        let deserialized: V02State = bincode::deserialize(&serialized).unwrap();
+
+        assert_eq!(deserialized.get_account_by_id(&account_id), account);
+        assert_eq!(
+            deserialized.commitment_set_digest(),
+            state.commitment_set_digest()
+        );
+        assert_eq!(
+            deserialized.nullifier_set_digest(),
+            state.nullifier_set_digest()
+        );
+        for commitment in &commitments {
+            assert_eq!(
+                deserialized.get_proof_for_commitment(commitment),
+                state.get_proof_for_commitment(commitment)
+            );
+        }
+        assert_eq!(
+            deserialized.private_state.0.root_history,
+            state.private_state.0.root_history
+        );
+        assert!(deserialized.private_state.1.contains(&nullifier));
+    }
 }
@@ -1,7 +1,11 @@
 use std::{collections::HashMap, path::Path};
 
 use anyhow::Result;
-use common::{HashType, block::Block, transaction::EncodedTransaction};
+use common::{
+    HashType,
+    block::{Block, HashableBlockData},
+    transaction::EncodedTransaction,
+};
 use storage::RocksDBIO;
 
 pub struct SequencerBlockStore {
@@ -56,6 +60,37 @@ impl SequencerBlockStore {
         Ok(())
     }
 
+    /// Returns transactions in which the given account appears from block `from_block_id` on,
+    /// paired with their block id.
+    /// Writes everything stored so far to disk
+    pub fn flush(&self) -> Result<()> {
+        Ok(self.dbio.flush()?)
+    }
+
+    pub fn get_transactions_for_account(
+        &self,
+        account_id: nssa::AccountId,
+        from_block_id: u64,
+    ) -> Result<Vec<(u64, EncodedTransaction)>> {
+        let entries = self
+            .dbio
+            .get_account_tx_index(*account_id.value(), from_block_id)?;
+        let mut transactions = Vec::with_capacity(entries.len());
+        // Entries are sorted by block, so every block is loaded once
+        let mut block: Option<HashableBlockData> = None;
+        for (block_id, tx_index) in entries {
+            let block = match block {
+                Some(ref block) if block.block_id == block_id => block,
+                _ => block.insert(self.dbio.get_block(block_id)?),
+            };
+            let transaction = block.transactions.get(tx_index as usize).ok_or_else(|| {
+                anyhow::anyhow!("Transaction {tx_index} not found in block {block_id}")
+            })?;
+            transactions.push((block_id, transaction.clone()));
+        }
+        Ok(transactions)
+    }
+
     /// Returns the transaction corresponding to the given hash, if it exists in the blockchain.
     pub fn get_transaction_by_hash(&self, hash: HashType) -> Option<EncodedTransaction> {
         let block_id = self.tx_hash_to_block_map.get(&hash);
@@ -70,6 +105,19 @@ impl SequencerBlockStore {
         None
     }
 
+    /// Persists transaction received into mempool, so it survives restarts
+    pub fn put_pending_transaction(&self, tx: &EncodedTransaction) -> Result<()> {
+        Ok(self.dbio.put_mempool_transaction(tx)?)
+    }
+
+    pub fn delete_pending_transaction(&self, tx_hash: HashType) -> Result<()> {
+        Ok(self.dbio.delete_mempool_transaction(tx_hash)?)
+    }
+
+    pub fn get_pending_transactions(&self) -> Result<Vec<EncodedTransaction>> {
+        Ok(self.dbio.get_mempool_transactions()?)
+    }
+
     pub fn insert(&mut self, tx: &EncodedTransaction, block_id: u64) {
         self.tx_hash_to_block_map.insert(tx.hash(), block_id);
     }
@@ -131,4 +179,163 @@ mod tests {
         let retrieved_tx = node_store.get_transaction_by_hash(tx.hash());
         assert_eq!(Some(tx), retrieved_tx);
     }
+
+    #[test]
+    fn test_get_transactions_for_account() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path();
+
+        let signing_key = sequencer_sign_key_for_testing();
+
+        let genesis_block_hashable_data = HashableBlockData {
+            block_id: 0,
+            prev_block_hash: [0; 32],
+            timestamp: 0,
+            transactions: vec![],
+        };
+
+        let genesis_block = genesis_block_hashable_data.into_block(&signing_key);
+        let mut node_store =
+            SequencerBlockStore::open_db_with_genesis(path, Some(genesis_block), signing_key)
+                .unwrap();
+
+        let key_a = nssa::PrivateKey::try_new([1; 32]).unwrap();
+        let key_b = nssa::PrivateKey::try_new([2; 32]).unwrap();
+        let account_a = nssa::AccountId::from(&nssa::PublicKey::new_from_private_key(&key_a));
+        let account_b = nssa::AccountId::from(&nssa::PublicKey::new_from_private_key(&key_b));
+
+        let txs_a: Vec<_> = (0..5)
+            .map(|nonce| {
+                common::test_utils::create_transaction_native_token_transfer(
+                    *account_a.value(),
+                    nonce,
+                    [3; 32],
+                    1,
+                    key_a.clone(),
+                )
+            })
+            .collect();
+        let txs_b: Vec<_> = (0..3)
+            .map(|nonce| {
+                common::test_utils::create_transaction_native_token_transfer(
+                    *account_b.value(),
+                    nonce,
+                    [4; 32],
+                    1,
+                    key_b.clone(),
+                )
+            })
+            .collect();
+
+        let block_1 = common::test_utils::produce_dummy_block(1, None, txs_a[..3].to_vec());
+        let block_2 = common::test_utils::produce_dummy_block(
+            2,
+            None,
+            [txs_a[3..].to_vec(), txs_b.clone()].concat(),
+        );
+        node_store.put_block_at_id(block_1).unwrap();
+        node_store.put_block_at_id(block_2).unwrap();
+
+        let retrieved_a = node_store
+            .get_transactions_for_account(account_a, 0)
+            .unwrap();
+        let retrieved_b = node_store
+            .get_transactions_for_account(account_b, 0)
+            .unwrap();
+
+        assert_eq!(retrieved_a.len(), 5);
+        assert_eq!(retrieved_b.len(), 3);
+        assert_eq!(
+            retrieved_a
+                .into_iter()
+                .map(|(_, tx)| tx)
+                .collect::<Vec<_>>(),
+            txs_a
+        );
+        assert_eq!(
+            retrieved_b,
+            txs_b.into_iter().map(|tx| (2, tx)).collect::<Vec<_>>()
+        );
+
+        // Earlier blocks are skipped
+        assert_eq!(
+            node_store
+                .get_transactions_for_account(account_a, 2)
+                .unwrap(),
+            txs_a[3..]
+                .iter()
+                .map(|tx| (2, tx.clone()))
+                .collect::<Vec<_>>()
+        );
+        assert!(
+            node_store
+                .get_transactions_for_account(account_b, 3)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_account_tx_index_is_backfilled_on_open() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path();
+
+        let signing_key = sequencer_sign_key_for_testing();
+
+        let genesis_block_hashable_data = HashableBlockData {
+            block_id: 0,
+            prev_block_hash: [0; 32],
+            timestamp: 0,
+            transactions: vec![],
+        };
+
+        let genesis_block = genesis_block_hashable_data.into_block(&signing_key);
+        let mut node_store =
+            SequencerBlockStore::open_db_with_genesis(path, Some(genesis_block), signing_key)
+                .unwrap();
+
+        let key = nssa::PrivateKey::try_new([1; 32]).unwrap();
+        let account_id = nssa::AccountId::from(&nssa::PublicKey::new_from_private_key(&key));
+        let txs: Vec<_> = (0..2)
+            .map(|nonce| {
+                common::test_utils::create_transaction_native_token_transfer(
+                    *account_id.value(),
+                    nonce,
+                    [3; 32],
+                    1,
+                    key.clone(),
+                )
+            })
+            .collect();
+        for (block_id, tx) in (1..).zip(&txs) {
+            let block = common::test_utils::produce_dummy_block(block_id, None, vec![tx.clone()]);
+            node_store.put_block_at_id(block).unwrap();
+        }
+
+        // Simulate database written before the index existed
+        node_store
+            .dbio
+            .db
+            .drop_cf(storage::CF_ACCOUNT_TX_INDEX_NAME)
+            .unwrap();
+        node_store
+            .dbio
+            .db
+            .delete_cf(
+                &node_store.dbio.meta_column(),
+                borsh::to_vec(&storage::DB_META_ACCOUNT_TX_INDEX_LAST_BLOCK_KEY).unwrap(),
+            )
+            .unwrap();
+        drop(node_store);
+
+        let node_store =
+            SequencerBlockStore::open_db_restart(path, sequencer_sign_key_for_testing()).unwrap();
+
+        assert_eq!(
+            node_store
+                .get_transactions_for_account(account_id, 0)
+                .unwrap(),
+            vec![(1, txs[0].clone()), (2, txs[1].clone())]
+        );
+    }
 }
@@ -1,6 +1,8 @@
 use std::path::PathBuf;
 
+use common::{fee::FeeParams, rpc_primitives::RpcLimitsConfig};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, digest::FixedOutput};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 /// Helperstruct for account serialization
@@ -17,6 +19,13 @@ pub struct CommitmentsInitialData {
     pub account: nssa_core::account::Account,
 }
 
+/// Default for [`SequencerConfig::max_tx_size_bytes`], 256 KiB
+pub const DEFAULT_MAX_TX_SIZE_BYTES: usize = 256 * 1024;
+
+fn default_max_tx_size_bytes() -> usize {
+    DEFAULT_MAX_TX_SIZE_BYTES
+}
+
 // TODO: Provide default values
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SequencerConfig {
@@ -42,4 +51,40 @@ pub struct SequencerConfig {
     pub initial_commitments: Vec<CommitmentsInitialData>,
     /// Sequencer own signing key
     pub signing_key: [u8; 32],
+    /// Token authorizing admin requests, admin requests are rejected if not set
+    pub admin_token: Option<String>,
+    /// If `True`, then pending transactions are stored in db and restored into mempool on restart
+    #[serde(default)]
+    pub persist_mempool: bool,
+    /// Maximum size of encoded transaction data accepted into mempool, in bytes
+    #[serde(default = "default_max_tx_size_bytes")]
+    pub max_tx_size_bytes: usize,
+    /// Fee schedule of public transactions, transactions are free if not set
+    #[serde(default)]
+    pub fee_params: FeeParams,
+    /// Limits of the RPC server, requests are not rate limited if not set
+    #[serde(default)]
+    pub rpc_limits: RpcLimitsConfig,
+    /// File state snapshot is written to after every block and restored from on restart.
+    /// Snapshots are not used if not set or if sequencer is built without `serde` feature
+    #[serde(default)]
+    pub state_snapshot_path: Option<PathBuf>,
+}
+
+impl SequencerConfig {
+    /// Hash of data defining genesis state, used to tell apart different chains.
+    ///
+    /// Node specific fields (home, port, signing key, etc.) are not included, so all nodes of the
+    /// same chain agree on it.
+    pub fn genesis_hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+
+        hasher.update(self.genesis_id.to_le_bytes());
+        hasher.update(
+            serde_json::to_vec(&(&self.initial_accounts, &self.initial_commitments))
+                .expect("Genesis data must be serializable"),
+        );
+
+        hasher.finalize_fixed().into()
+    }
 }
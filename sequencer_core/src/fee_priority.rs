@@ -0,0 +1,195 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+use common::transaction::{EncodedTransaction, NSSATransaction};
+use nssa::AccountId;
+
+/// Transaction waiting in mempool together with its fee payer and fee.
+///
+/// They are decoded once, when the transaction is pushed, so choosing transactions for a block
+/// under the mempool lock only compares cached keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MempoolTransaction {
+    pub tx: EncodedTransaction,
+    fee_payer: Option<(AccountId, u128)>,
+}
+
+impl MempoolTransaction {
+    /// Wraps `tx`, which is already decoded into `nssa_tx`
+    pub fn new(tx: EncodedTransaction, nssa_tx: &NSSATransaction) -> Self {
+        Self {
+            tx,
+            fee_payer: fee_payer(nssa_tx),
+        }
+    }
+}
+
+impl From<EncodedTransaction> for MempoolTransaction {
+    /// Decodes `tx` to find its fee payer, malformed transactions pay no fee
+    fn from(tx: EncodedTransaction) -> Self {
+        let fee_payer = NSSATransaction::try_from(&tx)
+            .ok()
+            .and_then(|nssa_tx| fee_payer(&nssa_tx));
+        Self { tx, fee_payer }
+    }
+}
+
+/// Chooses up to `limit` waiting transactions for the next block, highest fee first.
+///
+/// Public transactions of the same fee payer are taken in mempool order, as their nonces have to
+/// be applied in sequence. Other transactions pay no fee and go after paying ones, ties are broken
+/// by mempool order.
+pub fn select_by_fee(txs: &[&MempoolTransaction], limit: usize) -> Vec<usize> {
+    // Waiting transactions, which have to be taken one after another, with their fees
+    let mut queues: Vec<VecDeque<(usize, u128)>> = vec![];
+    let mut payer_queues: HashMap<AccountId, usize> = HashMap::new();
+
+    for (index, tx) in txs.iter().enumerate() {
+        let Some((payer, fee)) = tx.fee_payer else {
+            queues.push(VecDeque::from([(index, 0)]));
+            continue;
+        };
+        let queue_id = *payer_queues.entry(payer).or_insert_with(|| {
+            queues.push(VecDeque::new());
+            queues.len() - 1
+        });
+        queues[queue_id].push_back((index, fee));
+    }
+
+    let mut heads: BinaryHeap<(u128, Reverse<usize>, usize)> = queues
+        .iter()
+        .enumerate()
+        .filter_map(|(queue_id, queue)| {
+            let (index, fee) = queue.front()?;
+            Some((*fee, Reverse(*index), queue_id))
+        })
+        .collect();
+
+    let mut selected = Vec::with_capacity(limit.min(txs.len()));
+    while selected.len() < limit {
+        let Some((_, Reverse(index), queue_id)) = heads.pop() else {
+            break;
+        };
+        selected.push(index);
+
+        let queue = &mut queues[queue_id];
+        queue.pop_front();
+        if let Some((index, fee)) = queue.front() {
+            heads.push((*fee, Reverse(*index), queue_id));
+        }
+    }
+
+    selected
+}
+
+/// First signer of public transaction, who pays its fee, and the fee
+fn fee_payer(tx: &NSSATransaction) -> Option<(AccountId, u128)> {
+    let NSSATransaction::Public(tx) = tx else {
+        return None;
+    };
+    let (_, public_key) = tx.witness_set().signatures_and_public_keys().first()?;
+    Some((AccountId::from(public_key), tx.message().fee()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx_with_fee(signing_key: u8, nonce: u128, fee: u128) -> MempoolTransaction {
+        let signing_key = nssa::PrivateKey::try_new([signing_key; 32]).unwrap();
+        let account_id = AccountId::from(&nssa::PublicKey::new_from_private_key(&signing_key));
+        let message = nssa::public_transaction::Message::try_new(
+            [0; 8],
+            vec![account_id],
+            vec![nonce],
+            0u128,
+            fee,
+        )
+        .unwrap();
+        let witness_set =
+            nssa::public_transaction::WitnessSet::for_message(&message, &[&signing_key]);
+        let tx: EncodedTransaction =
+            NSSATransaction::Public(nssa::PublicTransaction::new(message, witness_set).unwrap())
+                .into();
+        tx.into()
+    }
+
+    fn select(txs: &[MempoolTransaction], limit: usize) -> Vec<usize> {
+        select_by_fee(&txs.iter().collect::<Vec<_>>(), limit)
+    }
+
+    #[test]
+    fn test_select_by_fee_takes_highest_fees() {
+        let txs = [
+            tx_with_fee(1, 0, 5),
+            tx_with_fee(2, 0, 20),
+            tx_with_fee(3, 0, 10),
+            tx_with_fee(4, 0, 20),
+        ];
+
+        assert_eq!(select(&txs, 3), vec![1, 3, 2]);
+        assert_eq!(select(&txs, 10), vec![1, 3, 2, 0]);
+    }
+
+    #[test]
+    fn test_select_by_fee_keeps_order_of_same_payer() {
+        let txs = [
+            tx_with_fee(1, 0, 1),
+            tx_with_fee(2, 0, 5),
+            tx_with_fee(1, 1, 10),
+            tx_with_fee(1, 2, 3),
+        ];
+
+        // Second transaction of payer 1 has to wait for the cheap first one
+        assert_eq!(select(&txs, 4), vec![1, 0, 2, 3]);
+    }
+
+    #[test]
+    fn test_select_by_fee_keeps_mempool_order_without_fees() {
+        let malformed = EncodedTransaction {
+            tx_kind: common::transaction::TxKind::Public,
+            encoded_transaction_data: vec![0; 3],
+        };
+        let txs = [
+            tx_with_fee(1, 0, 0),
+            malformed.into(),
+            tx_with_fee(2, 0, 0),
+            tx_with_fee(3, 0, 1),
+        ];
+
+        assert_eq!(select(&txs, 4), vec![3, 0, 1, 2]);
+        assert!(select(&txs, 0).is_empty());
+    }
+
+    #[test]
+    fn test_select_by_fee_puts_transactions_without_signers_last() {
+        let message =
+            nssa::public_transaction::Message::try_new([0; 8], vec![], vec![], 0u128, 100).unwrap();
+        let witness_set = nssa::public_transaction::WitnessSet::for_message(&message, &[]);
+        let without_signers: EncodedTransaction =
+            NSSATransaction::Public(nssa::PublicTransaction::new(message, witness_set).unwrap())
+                .into();
+        let txs = [
+            without_signers.into(),
+            tx_with_fee(1, 0, 1),
+            tx_with_fee(2, 0, 2),
+        ];
+
+        // Fee in the message is ignored, as there is no one to charge it
+        assert_eq!(select(&txs, 3), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_mempool_transaction_new_matches_decoding() {
+        let pending = tx_with_fee(1, 0, 7);
+        let nssa_tx = NSSATransaction::try_from(&pending.tx).unwrap();
+
+        assert_eq!(
+            MempoolTransaction::new(pending.tx.clone(), &nssa_tx),
+            pending
+        );
+        assert!(pending.fee_payer.is_some_and(|(_, fee)| fee == 7));
+    }
+}
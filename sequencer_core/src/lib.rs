@@ -1,11 +1,18 @@
-use std::{fmt::Display, time::Instant};
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    time::Instant,
+};
 
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
 #[cfg(feature = "testnet")]
 use common::PINATA_BASE58;
 use common::{
     HashType,
-    block::HashableBlockData,
+    block::{Block, HashableBlockData},
+    rpc_primitives::requests::GetBlockDataResponse,
     transaction::{EncodedTransaction, NSSATransaction},
 };
 use config::SequencerConfig;
@@ -14,22 +21,40 @@ use mempool::{MemPool, MemPoolHandle};
 use serde::{Deserialize, Serialize};
 
 use crate::block_store::SequencerBlockStore;
+pub use crate::fee_priority::MempoolTransaction;
 
 pub mod block_store;
 pub mod config;
+mod fee_priority;
+#[cfg(feature = "serde")]
+mod state_snapshot;
 
 pub struct SequencerCore {
     state: nssa::V02State,
     block_store: SequencerBlockStore,
-    mempool: MemPool<EncodedTransaction>,
+    mempool: MemPool<MempoolTransaction>,
     sequencer_config: SequencerConfig,
     chain_height: u64,
+    genesis_hash: HashType,
+    block_production_paused: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum TransactionMalformationError {
     InvalidSignature,
-    FailedToDecode { tx: HashType },
+    FailedToDecode {
+        tx: HashType,
+    },
+    TransactionTooLarge {
+        tx: HashType,
+        size: usize,
+        max_size: usize,
+    },
+    InsufficientFee {
+        tx: HashType,
+        fee: u128,
+        required_fee: u128,
+    },
 }
 
 impl Display for TransactionMalformationError {
@@ -40,9 +65,29 @@ impl Display for TransactionMalformationError {
 
 impl std::error::Error for TransactionMalformationError {}
 
+/// Projected outcome of executing transactions on top of the current state
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimulationResult {
+    /// Post states of public accounts, which were changed
+    pub account_diffs: Vec<(nssa::AccountId, nssa::Account)>,
+    pub nullifiers_added: Vec<nssa_core::Nullifier>,
+    pub commitments_added: Vec<nssa_core::Commitment>,
+    /// Indices of rejected transactions with rejection reasons
+    pub failed_txs: Vec<(usize, String)>,
+}
+
+/// Metrics of transactions waiting in mempool
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MempoolStats {
+    pub count: usize,
+    /// Sum of encoded data sizes of waiting transactions
+    pub total_size_bytes: usize,
+    pub oldest_tx_age_ms: u64,
+}
+
 impl SequencerCore {
     /// Start Sequencer from configuration and construct transaction sender
-    pub fn start_from_config(config: SequencerConfig) -> (Self, MemPoolHandle<EncodedTransaction>) {
+    pub fn start_from_config(config: SequencerConfig) -> (Self, MemPoolHandle<MempoolTransaction>) {
         let hashable_data = HashableBlockData {
             block_id: config.genesis_id,
             transactions: vec![],
@@ -92,19 +137,125 @@ impl SequencerCore {
             block_store,
             mempool,
             chain_height: config.genesis_id,
+            genesis_hash: config.genesis_hash(),
             sequencer_config: config,
+            block_production_paused: false,
         };
 
+        #[cfg(feature = "serde")]
+        this.restore_state_snapshot();
         this.sync_state_with_stored_blocks();
 
+        if this.sequencer_config.persist_mempool {
+            this.restore_pending_transactions(&mempool_handle);
+        }
+
         (this, mempool_handle)
     }
 
+    /// Pushes transactions persisted before restart back into mempool.
+    ///
+    /// Transactions not fitting into mempool stay in db and are restored on next restart.
+    fn restore_pending_transactions(&self, mempool_handle: &MemPoolHandle<MempoolTransaction>) {
+        // Sequencer should panic if unable to read db, same as on opening it
+        let pending_transactions = self.block_store.get_pending_transactions().unwrap();
+        let num_pending = pending_transactions.len();
+
+        for (restored, tx) in pending_transactions.into_iter().enumerate() {
+            if mempool_handle.try_push(tx.into()).is_err() {
+                warn!("Mempool is full, restored {restored} of {num_pending} pending transactions");
+                return;
+            }
+        }
+
+        if num_pending > 0 {
+            log::info!("Restored {num_pending} pending transactions into mempool");
+        }
+    }
+
+    /// Rejects transactions with encoded data larger than configured `max_tx_size_bytes`
+    pub fn check_transaction_size(
+        &self,
+        tx: &EncodedTransaction,
+    ) -> Result<(), TransactionMalformationError> {
+        let size = tx.encoded_transaction_data.len();
+        let max_size = self.sequencer_config.max_tx_size_bytes;
+        if size > max_size {
+            return Err(TransactionMalformationError::TransactionTooLarge {
+                tx: tx.hash(),
+                size,
+                max_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Minimal fee of public transaction `tx` according to configured fee schedule
+    pub fn required_fee(&self, tx: &EncodedTransaction) -> u128 {
+        self.sequencer_config
+            .fee_params
+            .fee_for_size(tx.encoded_transaction_data.len())
+    }
+
+    /// Rejects public transactions paying less than [`SequencerCore::required_fee`].
+    ///
+    /// Fee is paid by the first signer, so public transactions without signers are not checked,
+    /// the same as other transaction kinds, which carry no fee. This exempts pinata claims and
+    /// token definitions, which need no signer. They enter the mempool for free, but block
+    /// production takes them only after all transactions paying a fee.
+    pub fn check_transaction_fee(
+        &self,
+        tx: &EncodedTransaction,
+        nssa_tx: &NSSATransaction,
+    ) -> Result<(), TransactionMalformationError> {
+        let NSSATransaction::Public(public_tx) = nssa_tx else {
+            return Ok(());
+        };
+        if public_tx
+            .witness_set()
+            .signatures_and_public_keys()
+            .is_empty()
+        {
+            return Ok(());
+        }
+
+        let fee = public_tx.message().fee();
+        let required_fee = self.required_fee(tx);
+        if fee < required_fee {
+            return Err(TransactionMalformationError::InsufficientFee {
+                tx: tx.hash(),
+                fee,
+                required_fee,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn get_transaction_pool_stats(&self) -> MempoolStats {
+        let stats = self
+            .mempool
+            .stats(|pending| pending.tx.encoded_transaction_data.len());
+
+        MempoolStats {
+            count: stats.count,
+            total_size_bytes: stats.total_size_bytes,
+            oldest_tx_age_ms: stats.oldest_item_age.as_millis() as u64,
+        }
+    }
+
+    /// Stores transaction received into mempool, if mempool persistence is enabled
+    pub fn persist_pending_transaction(&self, tx: &EncodedTransaction) -> Result<()> {
+        if self.sequencer_config.persist_mempool {
+            self.block_store.put_pending_transaction(tx)?;
+        }
+        Ok(())
+    }
+
     /// If there are stored blocks ahead of the current height, this method will load and process
     /// all transaction in them in the order they are stored. The NSSA state will be updated
     /// accordingly.
     fn sync_state_with_stored_blocks(&mut self) {
-        let mut next_block_id = self.sequencer_config.genesis_id + 1;
+        let mut next_block_id = self.chain_height + 1;
         while let Ok(block) = self.block_store.get_block_at_id(next_block_id) {
             for encoded_transaction in block.body.transactions {
                 let transaction = NSSATransaction::try_from(&encoded_transaction).unwrap();
@@ -119,43 +270,172 @@ impl SequencerCore {
         }
     }
 
+    /// Restores state from the configured snapshot if it belongs to the stored chain.
+    ///
+    /// Blocks covered by the snapshot are only indexed, their transactions are not executed.
+    #[cfg(feature = "serde")]
+    fn restore_state_snapshot(&mut self) {
+        let Some(path) = self.sequencer_config.state_snapshot_path.clone() else {
+            return;
+        };
+        if !path.exists() {
+            return;
+        }
+
+        let snapshot = match state_snapshot::StateSnapshot::load(&path) {
+            Ok(snapshot) => snapshot,
+            Err(err) => {
+                warn!("Failed to load state snapshot from {path:?}, replaying all blocks: {err:#}");
+                return;
+            }
+        };
+        match self.block_store.get_block_at_id(snapshot.block_id) {
+            Ok(block) if block.header.hash == snapshot.block_hash => {}
+            _ => {
+                warn!("State snapshot {path:?} doesn't match stored blocks, replaying all blocks");
+                return;
+            }
+        }
+
+        for block_id in self.sequencer_config.genesis_id + 1..=snapshot.block_id {
+            // Sequencer should panic if unable to read db, same as on opening it
+            let block = self.block_store.get_block_at_id(block_id).unwrap();
+            for encoded_transaction in &block.body.transactions {
+                self.block_store.insert(encoded_transaction, block_id);
+            }
+        }
+
+        self.state = snapshot.state;
+        self.chain_height = snapshot.block_id;
+
+        log::info!("Restored state snapshot of block {}", snapshot.block_id);
+    }
+
+    /// Writes snapshot of the current state if snapshot path is configured.
+    ///
+    /// Failing to write a snapshot only slows down the next restart, so it is not an error.
+    #[cfg(feature = "serde")]
+    fn save_state_snapshot(&self, block_hash: HashType) {
+        let Some(path) = &self.sequencer_config.state_snapshot_path else {
+            return;
+        };
+
+        let snapshot = state_snapshot::StateSnapshotRef {
+            block_id: self.chain_height,
+            block_hash,
+            state: &self.state,
+        };
+        if let Err(err) = snapshot.save(path) {
+            warn!("Failed to write state snapshot to {path:?}: {err:#}");
+        }
+    }
+
     fn execute_check_transaction_on_state(
         &mut self,
         tx: NSSATransaction,
     ) -> Result<NSSATransaction, nssa::error::NssaError> {
-        match &tx {
-            NSSATransaction::Public(tx) => self.state.transition_from_public_transaction(tx),
-            NSSATransaction::PrivacyPreserving(tx) => self
-                .state
-                .transition_from_privacy_preserving_transaction(tx),
-            NSSATransaction::ProgramDeployment(tx) => self
-                .state
-                .transition_from_program_deployment_transaction(tx),
-        }
-        .inspect_err(|err| warn!("Error at transition {err:#?}"))?;
+        execute_transaction_on_state(&mut self.state, &tx)
+            .inspect_err(|err| warn!("Error at transition {err:#?}"))?;
 
         Ok(tx)
     }
 
+    /// Executes transactions on a copy of the current state and returns the resulting changes.
+    /// Current state is left untouched.
+    pub fn simulate_block(&self, txs: Vec<EncodedTransaction>) -> SimulationResult {
+        let mut state = self.state.clone();
+        let mut result = SimulationResult::default();
+        let mut touched_account_ids = vec![];
+
+        for (tx_index, tx) in txs.iter().enumerate() {
+            let nssa_transaction = match NSSATransaction::try_from(tx) {
+                Ok(nssa_transaction) => nssa_transaction,
+                Err(err) => {
+                    result.failed_txs.push((tx_index, err.to_string()));
+                    continue;
+                }
+            };
+
+            if let Err(err) = execute_transaction_on_state(&mut state, &nssa_transaction) {
+                result.failed_txs.push((tx_index, err.to_string()));
+                continue;
+            }
+
+            if let NSSATransaction::PrivacyPreserving(tx) = &nssa_transaction {
+                let message = tx.message();
+                result
+                    .commitments_added
+                    .extend(message.new_commitments.iter().cloned());
+                result.nullifiers_added.extend(
+                    message
+                        .new_nullifiers()
+                        .iter()
+                        .map(|(nullifier, _)| nullifier.clone()),
+                );
+            }
+
+            let mut account_ids = nssa_transaction.public_account_ids();
+            if let NSSATransaction::Public(tx) = &nssa_transaction
+                && tx.message().fee() > 0
+            {
+                // Fee is credited to the collector, which is not among the message accounts
+                account_ids.push(nssa::V02State::FEE_COLLECTOR_ACCOUNT_ID);
+            }
+
+            for account_id in account_ids {
+                if !touched_account_ids.contains(&account_id) {
+                    touched_account_ids.push(account_id);
+                }
+            }
+        }
+
+        result.account_diffs = touched_account_ids
+            .into_iter()
+            .filter_map(|account_id| {
+                let post = state.get_account_by_id(&account_id);
+                (post != self.state.get_account_by_id(&account_id)).then_some((account_id, post))
+            })
+            .collect();
+
+        result
+    }
+
     /// Produces new block from transactions in mempool
     pub fn produce_new_block_with_mempool_transactions(&mut self) -> Result<u64> {
         let now = Instant::now();
         let new_block_height = self.chain_height + 1;
 
+        let max_num_tx_in_block = self.sequencer_config.max_num_tx_in_block;
         let mut valid_transactions = vec![];
+        let mut popped_tx_hashes = vec![];
+
+        // Rejected transactions leave free places in block, which are filled in next rounds
+        while valid_transactions.len() < max_num_tx_in_block {
+            let missing = max_num_tx_in_block - valid_transactions.len();
+            let txs = self
+                .mempool
+                .pop_selected(|txs| fee_priority::select_by_fee(txs, missing));
+            if txs.is_empty() {
+                break;
+            }
 
-        while let Some(tx) = self.mempool.pop() {
-            let nssa_transaction = NSSATransaction::try_from(&tx)
-                .map_err(|_| TransactionMalformationError::FailedToDecode { tx: tx.hash() })?;
-
-            if let Ok(valid_tx) = self.execute_check_transaction_on_state(nssa_transaction) {
-                valid_transactions.push(valid_tx.into());
-
-                if valid_transactions.len() >= self.sequencer_config.max_num_tx_in_block {
-                    break;
+            for MempoolTransaction { tx, .. } in txs {
+                popped_tx_hashes.push(tx.hash());
+
+                // Skipping malformed transaction, failing here would leave state diverged from
+                // stored blocks, as previous transactions are already applied
+                let Ok(nssa_transaction) = NSSATransaction::try_from(&tx) else {
+                    warn!(
+                        "Rejecting transaction {:?}",
+                        TransactionMalformationError::FailedToDecode { tx: tx.hash() }
+                    );
+                    continue;
+                };
+
+                // Failed execution is logged at transition
+                if let Ok(valid_tx) = self.execute_check_transaction_on_state(nssa_transaction) {
+                    valid_transactions.push(valid_tx.into());
                 }
-            } else {
-                // Probably need to handle unsuccessful transaction execution?
             }
         }
 
@@ -177,11 +457,24 @@ impl SequencerCore {
         };
 
         let block = hashable_data.into_block(self.block_store.signing_key());
+        #[cfg(feature = "serde")]
+        let block_hash = block.header.hash;
 
         self.block_store.put_block_at_id(block)?;
 
+        // Popped transactions are either included in the block or rejected,
+        // neither should be replayed after restart
+        if self.sequencer_config.persist_mempool {
+            for tx_hash in popped_tx_hashes {
+                self.block_store.delete_pending_transaction(tx_hash)?;
+            }
+        }
+
         self.chain_height = new_block_height;
 
+        #[cfg(feature = "serde")]
+        self.save_state_snapshot(block_hash);
+
         // TODO: Consider switching to `tracing` crate to have more structured and consistent logs
         // e.g.
         //
@@ -201,6 +494,82 @@ impl SequencerCore {
         Ok(self.chain_height)
     }
 
+    /// Writes signed blocks from `from` to `to` (inclusive) into a file as newline-delimited
+    /// JSON, one block per line in the format of `GetBlockDataResponse`
+    pub fn export_blocks_to_file(&self, from: u64, to: u64, path: &Path) -> Result<()> {
+        if from > to || to > self.chain_height {
+            bail!(
+                "Invalid block range {from}..={to}, chain height is {}",
+                self.chain_height
+            );
+        }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        for block_id in from..=to {
+            let block = self.block_store.get_block_at_id(block_id)?;
+            let line = GetBlockDataResponse {
+                block: borsh::to_vec(&block)?,
+            };
+            serde_json::to_writer(&mut writer, &line)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Imports blocks written by [`SequencerCore::export_blocks_to_file`] on top of the current
+    /// chain and returns the resulting chain height.
+    ///
+    /// Every block has to be signed by the sequencer key, extend the previous one and contain
+    /// only valid transactions. Blocks are stored only if all of them are valid.
+    pub fn import_blocks_from_file(&mut self, path: &Path) -> Result<u64> {
+        let public_key = nssa::PublicKey::new_from_private_key(self.block_store.signing_key());
+        let mut tip_id = self.chain_height;
+        let mut tip_hash = self.block_store.get_block_at_id(tip_id)?.header.hash;
+
+        // All blocks are executed on a single copy, which replaces the state once they are valid
+        let mut state = self.state.clone();
+        let mut blocks = vec![];
+        let reader = BufReader::new(File::open(path)?);
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let context = || format!("Failed to decode block after block {tip_id}");
+            let response: GetBlockDataResponse =
+                serde_json::from_str(&line).with_context(context)?;
+            let block: Block = borsh::from_slice(&response.block).with_context(context)?;
+            check_imported_block(&block, tip_id, tip_hash, &public_key)?;
+
+            let block_id = block.header.block_id;
+            for (tx_index, encoded_transaction) in block.body.transactions.iter().enumerate() {
+                let context = || format!("Invalid transaction {tx_index} in block {block_id}");
+                let transaction =
+                    NSSATransaction::try_from(encoded_transaction).with_context(context)?;
+                let transaction = transaction_pre_check(transaction).with_context(context)?;
+                execute_transaction_on_state(&mut state, &transaction).with_context(context)?;
+            }
+
+            tip_id = block_id;
+            tip_hash = block.header.hash;
+            blocks.push(block);
+        }
+
+        for block in blocks {
+            self.block_store.put_block_at_id(block)?;
+        }
+        self.state = state;
+        self.chain_height = tip_id;
+
+        #[cfg(feature = "serde")]
+        self.save_state_snapshot(tip_hash);
+
+        Ok(self.chain_height)
+    }
+
     pub fn state(&self) -> &nssa::V02State {
         &self.state
     }
@@ -216,6 +585,68 @@ impl SequencerCore {
     pub fn sequencer_config(&self) -> &SequencerConfig {
         &self.sequencer_config
     }
+
+    /// Applies block production parameters of a reloaded config.
+    /// Other fields only take effect on restart.
+    pub fn apply_config_update(&mut self, config: &SequencerConfig) {
+        self.sequencer_config.max_num_tx_in_block = config.max_num_tx_in_block;
+        self.sequencer_config.block_create_timeout_millis = config.block_create_timeout_millis;
+    }
+
+    /// Chain identifier, derived from genesis configuration
+    pub fn get_genesis_hash(&self) -> HashType {
+        self.genesis_hash
+    }
+
+    /// Stops main loop from producing blocks, transactions keep being accepted into mempool
+    pub fn pause_block_production(&mut self) {
+        self.block_production_paused = true;
+    }
+
+    pub fn resume_block_production(&mut self) {
+        self.block_production_paused = false;
+    }
+
+    pub fn is_block_production_paused(&self) -> bool {
+        self.block_production_paused
+    }
+}
+
+fn execute_transaction_on_state(
+    state: &mut nssa::V02State,
+    tx: &NSSATransaction,
+) -> Result<(), nssa::error::NssaError> {
+    match tx {
+        NSSATransaction::Public(tx) => state.transition_from_public_transaction(tx),
+        NSSATransaction::PrivacyPreserving(tx) => {
+            state.transition_from_privacy_preserving_transaction(tx)
+        }
+        NSSATransaction::ProgramDeployment(tx) => {
+            state.transition_from_program_deployment_transaction(tx)
+        }
+    }
+}
+
+/// Checks that imported `block` is signed by `public_key` and extends block `tip_id` with hash
+/// `tip_hash`
+fn check_imported_block(
+    block: &Block,
+    tip_id: u64,
+    tip_hash: HashType,
+    public_key: &nssa::PublicKey,
+) -> Result<()> {
+    let block_id = block.header.block_id;
+    let expected_block_id = tip_id + 1;
+    if block_id != expected_block_id {
+        bail!("Expected block {expected_block_id}, got block {block_id}");
+    }
+    if block.header.prev_block_hash != tip_hash {
+        bail!("Block {block_id} does not link to block {tip_id}");
+    }
+    if !block.is_valid_for(public_key) {
+        bail!("Block {block_id} has invalid hash or is not signed by the sequencer key");
+    }
+    Ok(())
 }
 
 // TODO: Introduce type-safe wrapper around checked transaction, e.g. AuthenticatedTransaction
@@ -247,11 +678,13 @@ mod tests {
     use std::pin::pin;
 
     use base58::{FromBase58, ToBase58};
-    use common::test_utils::sequencer_sign_key_for_testing;
+    use common::{
+        fee::FeeParams, rpc_primitives::RpcLimitsConfig, test_utils::sequencer_sign_key_for_testing,
+    };
     use nssa::PrivateKey;
 
     use super::*;
-    use crate::config::AccountInitialData;
+    use crate::config::{AccountInitialData, DEFAULT_MAX_TX_SIZE_BYTES};
 
     fn parse_unwrap_tx_body_into_nssa_tx(tx_body: EncodedTransaction) -> NSSATransaction {
         NSSATransaction::try_from(&tx_body)
@@ -277,6 +710,12 @@ mod tests {
             initial_accounts,
             initial_commitments: vec![],
             signing_key: *sequencer_sign_key_for_testing().value(),
+            admin_token: None,
+            persist_mempool: false,
+            max_tx_size_bytes: DEFAULT_MAX_TX_SIZE_BYTES,
+            fee_params: FeeParams::default(),
+            rpc_limits: RpcLimitsConfig::default(),
+            state_snapshot_path: None,
         }
     }
 
@@ -314,18 +753,18 @@ mod tests {
         nssa::PrivateKey::try_new([2; 32]).unwrap()
     }
 
-    async fn common_setup() -> (SequencerCore, MemPoolHandle<EncodedTransaction>) {
+    async fn common_setup() -> (SequencerCore, MemPoolHandle<MempoolTransaction>) {
         let config = setup_sequencer_config();
         common_setup_with_config(config).await
     }
 
     async fn common_setup_with_config(
         config: SequencerConfig,
-    ) -> (SequencerCore, MemPoolHandle<EncodedTransaction>) {
+    ) -> (SequencerCore, MemPoolHandle<MempoolTransaction>) {
         let (mut sequencer, mempool_handle) = SequencerCore::start_from_config(config);
 
         let tx = common::test_utils::produce_dummy_empty_transaction();
-        mempool_handle.push(tx).await.unwrap();
+        mempool_handle.push(tx.into()).await.unwrap();
 
         sequencer
             .produce_new_block_with_mempool_transactions()
@@ -334,6 +773,22 @@ mod tests {
         (sequencer, mempool_handle)
     }
 
+    #[test]
+    fn test_apply_config_update_changes_block_parameters_only() {
+        let config = setup_sequencer_config();
+        let (mut sequencer, _mempool_handle) = SequencerCore::start_from_config(config.clone());
+
+        let mut new_config = config.clone();
+        new_config.max_num_tx_in_block = 3;
+        new_config.block_create_timeout_millis = 50;
+        new_config.port = 9090;
+        sequencer.apply_config_update(&new_config);
+
+        assert_eq!(sequencer.sequencer_config.max_num_tx_in_block, 3);
+        assert_eq!(sequencer.sequencer_config.block_create_timeout_millis, 50);
+        assert_eq!(sequencer.sequencer_config.port, config.port);
+    }
+
     #[test]
     fn test_start_from_config() {
         let config = setup_sequencer_config();
@@ -584,6 +1039,105 @@ mod tests {
         assert_eq!(bal_to, 20100);
     }
 
+    #[tokio::test]
+    async fn test_get_transaction_pool_stats() {
+        let (mut sequencer, mempool_handle) = common_setup().await;
+        assert_eq!(sequencer.get_transaction_pool_stats().count, 0);
+
+        let tx = common::test_utils::produce_dummy_empty_transaction();
+        mempool_handle.push(tx.clone().into()).await.unwrap();
+        mempool_handle.push(tx.clone().into()).await.unwrap();
+
+        let stats = sequencer.get_transaction_pool_stats();
+        assert_eq!(stats.count, 2);
+        assert_eq!(
+            stats.total_size_bytes,
+            2 * tx.encoded_transaction_data.len()
+        );
+
+        sequencer
+            .produce_new_block_with_mempool_transactions()
+            .unwrap();
+        assert_eq!(sequencer.get_transaction_pool_stats().count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_check_transaction_size() {
+        let config = SequencerConfig {
+            max_tx_size_bytes: 100,
+            ..setup_sequencer_config()
+        };
+        let (sequencer, _mempool_handle) = common_setup_with_config(config).await;
+        let tx_of_size = |size| EncodedTransaction {
+            tx_kind: common::transaction::TxKind::Public,
+            encoded_transaction_data: vec![0; size],
+        };
+
+        assert!(sequencer.check_transaction_size(&tx_of_size(99)).is_ok());
+        assert!(sequencer.check_transaction_size(&tx_of_size(100)).is_ok());
+
+        let tx = tx_of_size(101);
+        assert_eq!(
+            sequencer.check_transaction_size(&tx),
+            Err(TransactionMalformationError::TransactionTooLarge {
+                tx: tx.hash(),
+                size: 101,
+                max_size: 100,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_transaction_fee() {
+        let config = SequencerConfig {
+            fee_params: FeeParams {
+                base_fee: 10,
+                fee_per_kb: 5,
+            },
+            ..setup_sequencer_config()
+        };
+        let (sequencer, _mempool_handle) = common_setup_with_config(config).await;
+        let signing_key = create_signing_key_for_account1();
+        let tx_with_fee = |fee, signing_keys: &[&nssa::PrivateKey]| {
+            let account_id =
+                nssa::AccountId::from(&nssa::PublicKey::new_from_private_key(&signing_key));
+            let message = nssa::public_transaction::Message::try_new(
+                [0; 8],
+                vec![account_id],
+                vec![0],
+                0u128,
+                fee,
+            )
+            .unwrap();
+            let witness_set =
+                nssa::public_transaction::WitnessSet::for_message(&message, signing_keys);
+            let nssa_tx = NSSATransaction::Public(
+                nssa::PublicTransaction::new(message, witness_set).unwrap(),
+            );
+            (EncodedTransaction::from(nssa_tx.clone()), nssa_tx)
+        };
+
+        // Encoded size doesn't depend on fee value
+        let (tx, nssa_tx) = tx_with_fee(0, &[&signing_key]);
+        let required_fee = sequencer.required_fee(&tx);
+        assert_eq!(required_fee, 15);
+        assert_eq!(
+            sequencer.check_transaction_fee(&tx, &nssa_tx),
+            Err(TransactionMalformationError::InsufficientFee {
+                tx: tx.hash(),
+                fee: 0,
+                required_fee,
+            })
+        );
+
+        let (tx, nssa_tx) = tx_with_fee(required_fee, &[&signing_key]);
+        assert!(sequencer.check_transaction_fee(&tx, &nssa_tx).is_ok());
+
+        // There is no one to pay the fee without signers
+        let (tx, nssa_tx) = tx_with_fee(0, &[]);
+        assert!(sequencer.check_transaction_fee(&tx, &nssa_tx).is_ok());
+    }
+
     #[tokio::test]
     async fn test_push_tx_into_mempool_blocks_until_mempool_is_full() {
         let config = SequencerConfig {
@@ -595,10 +1149,10 @@ mod tests {
         let tx = common::test_utils::produce_dummy_empty_transaction();
 
         // Fill the mempool
-        mempool_handle.push(tx.clone()).await.unwrap();
+        mempool_handle.push(tx.clone().into()).await.unwrap();
 
         // Check that pushing another transaction will block
-        let mut push_fut = pin!(mempool_handle.push(tx.clone()));
+        let mut push_fut = pin!(mempool_handle.push(tx.clone().into()));
         let poll = futures::poll!(push_fut.as_mut());
         assert!(poll.is_pending());
 
@@ -617,7 +1171,7 @@ mod tests {
         let genesis_height = sequencer.chain_height;
 
         let tx = common::test_utils::produce_dummy_empty_transaction();
-        mempool_handle.push(tx).await.unwrap();
+        mempool_handle.push(tx.into()).await.unwrap();
 
         let block_id = sequencer.produce_new_block_with_mempool_transactions();
         assert!(block_id.is_ok());
@@ -652,8 +1206,8 @@ mod tests {
         let tx_original = tx.clone();
         let tx_replay = tx.clone();
         // Pushing two copies of the same tx to the mempool
-        mempool_handle.push(tx_original).await.unwrap();
-        mempool_handle.push(tx_replay).await.unwrap();
+        mempool_handle.push(tx_original.into()).await.unwrap();
+        mempool_handle.push(tx_replay.into()).await.unwrap();
 
         // Create block
         let current_height = sequencer
@@ -668,6 +1222,86 @@ mod tests {
         assert_eq!(block.body.transactions, vec![tx.clone()]);
     }
 
+    #[tokio::test]
+    async fn test_block_includes_highest_fee_transactions_first() {
+        let signing_keys: Vec<_> = (1..=10)
+            .map(|i| PrivateKey::try_new([i; 32]).unwrap())
+            .collect();
+        let account_ids: Vec<nssa::AccountId> = signing_keys
+            .iter()
+            .map(|key| nssa::AccountId::from(&nssa::PublicKey::new_from_private_key(key)))
+            .collect();
+        let initial_accounts = account_ids
+            .iter()
+            .map(|account_id| AccountInitialData {
+                account_id: account_id.to_string(),
+                balance: 1000,
+            })
+            .collect();
+        let config = SequencerConfig {
+            max_num_tx_in_block: 3,
+            ..setup_sequencer_config_variable_initial_accounts(initial_accounts)
+        };
+        let (mut sequencer, mempool_handle) = common_setup_with_config(config).await;
+
+        // Transaction of i-th account pays fee i
+        let txs: Vec<_> = signing_keys
+            .into_iter()
+            .zip(&account_ids)
+            .zip(1..)
+            .map(|((key, account_id), fee)| {
+                common::test_utils::create_transaction_native_token_transfer_with_fee(
+                    *account_id.value(),
+                    0,
+                    [0xaa; 32],
+                    10,
+                    fee,
+                    key,
+                )
+            })
+            .collect();
+        for tx in &txs {
+            mempool_handle.push(tx.clone().into()).await.unwrap();
+        }
+
+        let current_height = sequencer
+            .produce_new_block_with_mempool_transactions()
+            .unwrap();
+        let block = sequencer
+            .block_store
+            .get_block_at_id(current_height)
+            .unwrap();
+
+        assert_eq!(
+            block.body.transactions,
+            vec![txs[9].clone(), txs[8].clone(), txs[7].clone()]
+        );
+        assert_eq!(
+            sequencer.state.get_account_by_id(&account_ids[9]).balance,
+            1000 - 10 - 10
+        );
+        assert_eq!(
+            sequencer
+                .state
+                .get_account_by_id(&nssa::V02State::FEE_COLLECTOR_ACCOUNT_ID)
+                .balance,
+            10 + 9 + 8
+        );
+
+        // Cheaper transactions wait for following blocks
+        let current_height = sequencer
+            .produce_new_block_with_mempool_transactions()
+            .unwrap();
+        let block = sequencer
+            .block_store
+            .get_block_at_id(current_height)
+            .unwrap();
+        assert_eq!(
+            block.body.transactions,
+            vec![txs[6].clone(), txs[5].clone(), txs[4].clone()]
+        );
+    }
+
     #[tokio::test]
     async fn test_replay_transactions_are_rejected_in_different_blocks() {
         let (mut sequencer, mempool_handle) = common_setup().await;
@@ -694,7 +1328,7 @@ mod tests {
         );
 
         // The transaction should be included the first time
-        mempool_handle.push(tx.clone()).await.unwrap();
+        mempool_handle.push(tx.clone().into()).await.unwrap();
         let current_height = sequencer
             .produce_new_block_with_mempool_transactions()
             .unwrap();
@@ -705,7 +1339,7 @@ mod tests {
         assert_eq!(block.body.transactions, vec![tx.clone()]);
 
         // Add same transaction should fail
-        mempool_handle.push(tx.clone()).await.unwrap();
+        mempool_handle.push(tx.clone().into()).await.unwrap();
         let current_height = sequencer
             .produce_new_block_with_mempool_transactions()
             .unwrap();
@@ -716,6 +1350,105 @@ mod tests {
         assert!(block.body.transactions.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_simulate_block_matches_block_production() {
+        let (mut sequencer, mempool_handle) = common_setup().await;
+
+        let acc1 = sequencer.sequencer_config.initial_accounts[0]
+            .account_id
+            .clone()
+            .from_base58()
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let acc2 = sequencer.sequencer_config.initial_accounts[1]
+            .account_id
+            .clone()
+            .from_base58()
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        let sign_key1 = create_signing_key_for_account1();
+        let sign_key2 = create_signing_key_for_account2();
+
+        let tx_1 = common::test_utils::create_transaction_native_token_transfer(
+            acc1,
+            0,
+            acc2,
+            100,
+            sign_key1.clone(),
+        );
+        let tx_2 = common::test_utils::create_transaction_native_token_transfer(
+            acc2, 0, acc1, 50, sign_key2,
+        );
+        // Replay of the first transaction, must be rejected
+        let tx_3 = tx_1.clone();
+        let txs = vec![tx_1, tx_2, tx_3];
+
+        let simulation = sequencer.simulate_block(txs.clone());
+
+        // Simulation must not change the state
+        assert_eq!(
+            sequencer
+                .state
+                .get_account_by_id(&nssa::AccountId::new(acc1))
+                .balance,
+            10000
+        );
+        assert_eq!(simulation.failed_txs.len(), 1);
+        assert_eq!(simulation.failed_txs[0].0, 2);
+        assert!(simulation.nullifiers_added.is_empty());
+        assert!(simulation.commitments_added.is_empty());
+
+        for tx in txs {
+            mempool_handle.push(tx.into()).await.unwrap();
+        }
+        sequencer
+            .produce_new_block_with_mempool_transactions()
+            .unwrap();
+
+        assert_eq!(simulation.account_diffs.len(), 2);
+        for (account_id, account) in simulation.account_diffs {
+            assert_eq!(sequencer.state.get_account_by_id(&account_id), account);
+        }
+        assert_eq!(
+            sequencer
+                .state
+                .get_account_by_id(&nssa::AccountId::new(acc1))
+                .balance,
+            9950
+        );
+    }
+
+    #[test]
+    fn test_simulate_block_reports_fee_collector() {
+        let config = setup_sequencer_config();
+        let acc1_account_id: nssa::AccountId =
+            config.initial_accounts[0].account_id.parse().unwrap();
+        let acc2_account_id: nssa::AccountId =
+            config.initial_accounts[1].account_id.parse().unwrap();
+        let (sequencer, _mempool_handle) = SequencerCore::start_from_config(config);
+
+        let tx = common::test_utils::create_transaction_native_token_transfer_with_fee(
+            *acc1_account_id.value(),
+            0,
+            *acc2_account_id.value(),
+            100,
+            7,
+            create_signing_key_for_account1(),
+        );
+        let simulation = sequencer.simulate_block(vec![tx]);
+
+        assert!(simulation.failed_txs.is_empty());
+        let collector = simulation
+            .account_diffs
+            .iter()
+            .find(|(account_id, _)| *account_id == nssa::V02State::FEE_COLLECTOR_ACCOUNT_ID)
+            .map(|(_, account)| account.balance);
+        assert_eq!(collector, Some(7));
+    }
+
     #[tokio::test]
     async fn test_restart_from_storage() {
         let config = setup_sequencer_config();
@@ -740,7 +1473,7 @@ mod tests {
                 signing_key,
             );
 
-            mempool_handle.push(tx.clone()).await.unwrap();
+            mempool_handle.push(tx.clone().into()).await.unwrap();
             let current_height = sequencer
                 .produce_new_block_with_mempool_transactions()
                 .unwrap();
@@ -767,4 +1500,401 @@ mod tests {
             config.initial_accounts[1].balance + balance_to_move
         );
     }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_restart_from_state_snapshot() {
+        let home = tempfile::tempdir().unwrap();
+        let config = SequencerConfig {
+            home: home.path().to_path_buf(),
+            state_snapshot_path: Some(home.path().join("state_snapshot.bin")),
+            ..setup_sequencer_config()
+        };
+        let acc1_account_id: nssa::AccountId =
+            config.initial_accounts[0].account_id.parse().unwrap();
+        let acc2_account_id: nssa::AccountId =
+            config.initial_accounts[1].account_id.parse().unwrap();
+        let balance_to_move = 13;
+
+        let tx = common::test_utils::create_transaction_native_token_transfer(
+            *acc1_account_id.value(),
+            0,
+            *acc2_account_id.value(),
+            balance_to_move,
+            PrivateKey::try_new([1; 32]).unwrap(),
+        );
+        {
+            let (mut sequencer, mempool_handle) = SequencerCore::start_from_config(config.clone());
+            mempool_handle.push(tx.clone().into()).await.unwrap();
+            sequencer
+                .produce_new_block_with_mempool_transactions()
+                .unwrap();
+        }
+
+        // Genesis accounts only affect replayed state, so the changed balance is not visible if
+        // the state is restored from the snapshot
+        let mut restart_config = config.clone();
+        restart_config.initial_accounts[0].balance = 5000;
+        let (sequencer, _mempool_handle) = SequencerCore::start_from_config(restart_config);
+
+        assert_eq!(sequencer.chain_height(), config.genesis_id + 1);
+        assert_eq!(
+            sequencer.state.get_account_by_id(&acc1_account_id).balance,
+            config.initial_accounts[0].balance - balance_to_move
+        );
+        assert_eq!(
+            sequencer.block_store.get_transaction_by_hash(tx.hash()),
+            Some(tx)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_state_snapshot_of_other_chain_is_ignored() {
+        let snapshot_home = tempfile::tempdir().unwrap();
+        let config = SequencerConfig {
+            home: snapshot_home.path().to_path_buf(),
+            state_snapshot_path: Some(snapshot_home.path().join("state_snapshot.bin")),
+            ..setup_sequencer_config()
+        };
+        {
+            let (mut sequencer, _mempool_handle) = SequencerCore::start_from_config(config.clone());
+            sequencer
+                .produce_new_block_with_mempool_transactions()
+                .unwrap();
+        }
+
+        // Store of the other chain doesn't have the block the snapshot was taken at
+        let other_home = tempfile::tempdir().unwrap();
+        let other_config = SequencerConfig {
+            home: other_home.path().to_path_buf(),
+            initial_accounts: vec![],
+            ..config.clone()
+        };
+        let (sequencer, _mempool_handle) = SequencerCore::start_from_config(other_config);
+
+        assert_eq!(sequencer.chain_height(), config.genesis_id);
+        assert_eq!(
+            sequencer
+                .state
+                .get_account_by_id(&config.initial_accounts[0].account_id.parse().unwrap())
+                .balance,
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_resume_block_production() {
+        let (mut sequencer, _mempool_handle) = common_setup().await;
+
+        assert!(!sequencer.is_block_production_paused());
+
+        sequencer.pause_block_production();
+        assert!(sequencer.is_block_production_paused());
+
+        sequencer.resume_block_production();
+        assert!(!sequencer.is_block_production_paused());
+    }
+
+    #[test]
+    fn test_genesis_hash_depends_on_genesis_id() {
+        let config = setup_sequencer_config();
+        let other_config = SequencerConfig {
+            genesis_id: 2,
+            home: tempfile::tempdir().unwrap().path().to_path_buf(),
+            ..config.clone()
+        };
+
+        let (sequencer, _mempool_handle) = SequencerCore::start_from_config(config);
+        let (other_sequencer, _mempool_handle) = SequencerCore::start_from_config(other_config);
+
+        assert_ne!(
+            sequencer.get_genesis_hash(),
+            other_sequencer.get_genesis_hash()
+        );
+    }
+
+    #[test]
+    fn test_genesis_hash_ignores_node_specific_config() {
+        let config = setup_sequencer_config();
+        let other_config = SequencerConfig {
+            port: 9090,
+            home: tempfile::tempdir().unwrap().path().to_path_buf(),
+            ..config.clone()
+        };
+
+        assert_eq!(config.genesis_hash(), other_config.genesis_hash());
+    }
+
+    #[test]
+    fn test_pending_transactions_are_restored_after_restart() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let config = SequencerConfig {
+            home: tempdir.path().to_path_buf(),
+            persist_mempool: true,
+            ..setup_sequencer_config()
+        };
+        let tx = common::test_utils::produce_dummy_empty_transaction();
+
+        {
+            let (sequencer, _mempool_handle) = SequencerCore::start_from_config(config.clone());
+            sequencer.persist_pending_transaction(&tx).unwrap();
+        }
+
+        {
+            let (mut sequencer, _mempool_handle) = SequencerCore::start_from_config(config.clone());
+            assert_eq!(
+                sequencer.block_store.get_pending_transactions().unwrap(),
+                vec![tx.clone()]
+            );
+
+            // Restored transaction is popped from mempool and removed from db
+            sequencer
+                .produce_new_block_with_mempool_transactions()
+                .unwrap();
+            assert!(
+                sequencer
+                    .block_store
+                    .get_pending_transactions()
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+
+        let (mut sequencer, _mempool_handle) = SequencerCore::start_from_config(config);
+        assert_eq!(sequencer.mempool.pop(), None);
+    }
+
+    #[test]
+    fn test_pending_transactions_are_restored_in_arrival_order() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let config = SequencerConfig {
+            home: tempdir.path().to_path_buf(),
+            persist_mempool: true,
+            ..setup_sequencer_config()
+        };
+        let acc1_account_id: nssa::AccountId =
+            config.initial_accounts[0].account_id.parse().unwrap();
+        let acc2_account_id: nssa::AccountId =
+            config.initial_accounts[1].account_id.parse().unwrap();
+        let txs: Vec<_> = (0..3)
+            .map(|nonce| {
+                common::test_utils::create_transaction_native_token_transfer(
+                    *acc1_account_id.value(),
+                    nonce,
+                    *acc2_account_id.value(),
+                    1,
+                    create_signing_key_for_account1(),
+                )
+            })
+            .collect();
+
+        {
+            let (sequencer, _mempool_handle) = SequencerCore::start_from_config(config.clone());
+            for tx in &txs {
+                sequencer.persist_pending_transaction(tx).unwrap();
+            }
+        }
+
+        let (mut sequencer, _mempool_handle) = SequencerCore::start_from_config(config.clone());
+        assert_eq!(
+            sequencer.block_store.get_pending_transactions().unwrap(),
+            txs
+        );
+
+        // Restored in nonce order, all transactions are valid
+        let block_id = sequencer
+            .produce_new_block_with_mempool_transactions()
+            .unwrap();
+        let block = sequencer.block_store.get_block_at_id(block_id).unwrap();
+        assert_eq!(block.body.transactions, txs);
+        assert_eq!(
+            sequencer.state.get_account_by_id(&acc1_account_id).balance,
+            config.initial_accounts[0].balance - 3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_malformed_transaction_is_skipped_in_block_production() {
+        let (mut sequencer, mempool_handle) = common_setup().await;
+
+        let malformed_tx = EncodedTransaction {
+            tx_kind: common::transaction::TxKind::Public,
+            encoded_transaction_data: vec![0xff; 4],
+        };
+        mempool_handle
+            .push(malformed_tx.clone().into())
+            .await
+            .unwrap();
+
+        let block_id = sequencer
+            .produce_new_block_with_mempool_transactions()
+            .unwrap();
+        let block = sequencer.block_store.get_block_at_id(block_id).unwrap();
+
+        assert_eq!(block_id, sequencer.chain_height());
+        assert!(!block.body.transactions.contains(&malformed_tx));
+    }
+
+    async fn sequencer_with_transfer_blocks(
+        config: SequencerConfig,
+        num_blocks: u128,
+    ) -> SequencerCore {
+        let acc1_account_id: nssa::AccountId =
+            config.initial_accounts[0].account_id.parse().unwrap();
+        let acc2_account_id: nssa::AccountId =
+            config.initial_accounts[1].account_id.parse().unwrap();
+        let (mut sequencer, mempool_handle) = SequencerCore::start_from_config(config);
+
+        for nonce in 0..num_blocks {
+            let tx = common::test_utils::create_transaction_native_token_transfer(
+                *acc1_account_id.value(),
+                nonce,
+                *acc2_account_id.value(),
+                1,
+                create_signing_key_for_account1(),
+            );
+            mempool_handle.push(tx.into()).await.unwrap();
+            sequencer
+                .produce_new_block_with_mempool_transactions()
+                .unwrap();
+        }
+
+        sequencer
+    }
+
+    #[tokio::test]
+    async fn test_export_import_blocks_roundtrip() {
+        let config = setup_sequencer_config();
+        let sequencer = sequencer_with_transfer_blocks(config.clone(), 100).await;
+
+        let export_dir = tempfile::tempdir().unwrap();
+        let path = export_dir.path().join("blocks.ndjson");
+        let from = config.genesis_id + 1;
+        let to = sequencer.chain_height();
+        assert_eq!(to - from + 1, 100);
+        sequencer.export_blocks_to_file(from, to, &path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 100);
+
+        let (mut fresh_sequencer, _mempool_handle) =
+            SequencerCore::start_from_config(setup_sequencer_config());
+        let height = fresh_sequencer.import_blocks_from_file(&path).unwrap();
+
+        assert_eq!(height, to);
+        assert_eq!(fresh_sequencer.chain_height(), to);
+        for block_id in from..=to {
+            let original = sequencer.block_store.get_block_at_id(block_id).unwrap();
+            let imported = fresh_sequencer
+                .block_store
+                .get_block_at_id(block_id)
+                .unwrap();
+            assert_eq!(imported.header.hash, original.header.hash);
+            assert_eq!(imported.body.transactions, original.body.transactions);
+        }
+        for account in &config.initial_accounts {
+            let account_id: nssa::AccountId = account.account_id.parse().unwrap();
+            assert_eq!(
+                fresh_sequencer.state.get_account_by_id(&account_id),
+                sequencer.state.get_account_by_id(&account_id)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_blocks_rejects_broken_chain() {
+        let config = setup_sequencer_config();
+        let sequencer = sequencer_with_transfer_blocks(config.clone(), 3).await;
+
+        let export_dir = tempfile::tempdir().unwrap();
+        let path = export_dir.path().join("blocks.ndjson");
+        // Skipping the first block leaves a gap after genesis of a fresh sequencer
+        sequencer
+            .export_blocks_to_file(config.genesis_id + 2, sequencer.chain_height(), &path)
+            .unwrap();
+
+        let (mut fresh_sequencer, _mempool_handle) =
+            SequencerCore::start_from_config(setup_sequencer_config());
+
+        assert!(fresh_sequencer.import_blocks_from_file(&path).is_err());
+        assert_eq!(fresh_sequencer.chain_height(), config.genesis_id);
+    }
+
+    #[tokio::test]
+    async fn test_import_blocks_rejects_blocks_of_other_sequencer() {
+        let config = setup_sequencer_config();
+        let sequencer = sequencer_with_transfer_blocks(config.clone(), 2).await;
+
+        let export_dir = tempfile::tempdir().unwrap();
+        let path = export_dir.path().join("blocks.ndjson");
+        sequencer
+            .export_blocks_to_file(config.genesis_id + 1, sequencer.chain_height(), &path)
+            .unwrap();
+
+        // Genesis hash doesn't depend on the key, so only signatures tell the chains apart
+        let other_config = SequencerConfig {
+            signing_key: [37; 32],
+            ..setup_sequencer_config()
+        };
+        let (mut fresh_sequencer, _mempool_handle) = SequencerCore::start_from_config(other_config);
+
+        assert!(fresh_sequencer.import_blocks_from_file(&path).is_err());
+        assert_eq!(fresh_sequencer.chain_height(), config.genesis_id);
+    }
+
+    #[tokio::test]
+    async fn test_import_blocks_stores_nothing_if_any_block_is_invalid() {
+        let config = setup_sequencer_config();
+        let sequencer = sequencer_with_transfer_blocks(config.clone(), 3).await;
+
+        let export_dir = tempfile::tempdir().unwrap();
+        let path = export_dir.path().join("blocks.ndjson");
+        sequencer
+            .export_blocks_to_file(config.genesis_id + 1, sequencer.chain_height(), &path)
+            .unwrap();
+
+        // Tampering with the last block breaks its hash
+        let mut blocks: Vec<Block> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let response: GetBlockDataResponse = serde_json::from_str(line).unwrap();
+                borsh::from_slice(&response.block).unwrap()
+            })
+            .collect();
+        blocks.last_mut().unwrap().header.timestamp += 1;
+        let mut tampered = String::new();
+        for block in &blocks {
+            let line = GetBlockDataResponse {
+                block: borsh::to_vec(block).unwrap(),
+            };
+            tampered.push_str(&serde_json::to_string(&line).unwrap());
+            tampered.push('\n');
+        }
+        std::fs::write(&path, tampered).unwrap();
+
+        let (mut fresh_sequencer, _mempool_handle) =
+            SequencerCore::start_from_config(setup_sequencer_config());
+        let acc1_account_id: nssa::AccountId =
+            config.initial_accounts[0].account_id.parse().unwrap();
+        let balance_before = fresh_sequencer
+            .state
+            .get_account_by_id(&acc1_account_id)
+            .balance;
+
+        assert!(fresh_sequencer.import_blocks_from_file(&path).is_err());
+        assert_eq!(fresh_sequencer.chain_height(), config.genesis_id);
+        assert!(
+            fresh_sequencer
+                .block_store
+                .get_block_at_id(config.genesis_id + 1)
+                .is_err()
+        );
+        assert_eq!(
+            fresh_sequencer
+                .state
+                .get_account_by_id(&acc1_account_id)
+                .balance,
+            balance_before
+        );
+    }
 }
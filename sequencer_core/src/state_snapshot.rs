@@ -0,0 +1,52 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::Result;
+use common::HashType;
+use serde::{Deserialize, Serialize};
+
+/// State after executing all blocks up to and including `block_id`
+#[derive(Deserialize)]
+pub struct StateSnapshot {
+    pub block_id: u64,
+    /// Hash of block `block_id`, used to check that the snapshot belongs to the stored chain
+    pub block_hash: HashType,
+    pub state: nssa::V02State,
+}
+
+/// Borrowed [`StateSnapshot`], so that the state doesn't have to be cloned to be saved
+#[derive(Serialize)]
+pub struct StateSnapshotRef<'a> {
+    pub block_id: u64,
+    pub block_hash: HashType,
+    pub state: &'a nssa::V02State,
+}
+
+impl StateSnapshot {
+    pub fn load(path: &Path) -> Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(bincode::deserialize_from(reader)?)
+    }
+}
+
+impl StateSnapshotRef<'_> {
+    /// Replaces snapshot at `path`.
+    ///
+    /// Snapshot is written into a temporary file first, so that a crash while writing leaves
+    /// the previous snapshot intact.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp_path = path.with_extension("tmp");
+
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        bincode::serialize_into(&mut writer, self)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+
+        std::fs::rename(tmp_path, path)?;
+
+        Ok(())
+    }
+}
@@ -1,26 +1,26 @@
 pub mod net_utils;
 pub mod process;
+pub mod rate_limiter;
 pub mod types;
 
 use std::sync::Arc;
 
-use common::{
-    rpc_primitives::errors::{RpcError, RpcErrorKind},
-    transaction::EncodedTransaction,
-};
+use common::rpc_primitives::errors::{RpcError, RpcErrorKind};
 use mempool::MemPoolHandle;
 pub use net_utils::*;
-use sequencer_core::SequencerCore;
+use sequencer_core::{MempoolTransaction, SequencerCore};
 use serde::Serialize;
 use serde_json::Value;
 use tokio::sync::Mutex;
 
-use self::types::err_rpc::RpcErr;
+use self::{rate_limiter::RateLimiter, types::err_rpc::RpcErr};
 
 // ToDo: Add necessary fields
 pub struct JsonHandler {
     sequencer_state: Arc<Mutex<SequencerCore>>,
-    mempool_handle: MemPoolHandle<EncodedTransaction>,
+    mempool_handle: MemPoolHandle<MempoolTransaction>,
+    read_rate_limiter: RateLimiter,
+    write_rate_limiter: RateLimiter,
 }
 
 fn respond<T: Serialize>(val: T) -> Result<Value, RpcErr> {
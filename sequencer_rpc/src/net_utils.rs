@@ -1,34 +1,68 @@
 use std::{io, sync::Arc};
 
 use actix_cors::Cors;
-use actix_web::{App, Error as HttpError, HttpResponse, HttpServer, http, middleware, web};
-use common::{
-    rpc_primitives::{RpcConfig, message::Message},
-    transaction::EncodedTransaction,
+use actix_web::{
+    App, Error as HttpError, HttpRequest, HttpResponse, HttpServer, http, middleware, web,
 };
+use common::rpc_primitives::{RpcConfig, message::Message};
 use futures::{Future, FutureExt};
 use log::info;
 use mempool::MemPoolHandle;
-use sequencer_core::SequencerCore;
+use sequencer_core::{MempoolTransaction, SequencerCore};
 use tokio::sync::Mutex;
 
-use super::JsonHandler;
+use super::{
+    JsonHandler,
+    process::SEND_TX,
+    rate_limiter::{RateLimiter, SWEEP_INTERVAL},
+};
 
 pub const SHUTDOWN_TIMEOUT_SECS: u64 = 10;
 
 pub const NETWORK: &str = "network";
 
 pub(crate) fn rpc_handler(
+    req: HttpRequest,
     message: web::Json<Message>,
     handler: web::Data<JsonHandler>,
 ) -> impl Future<Output = Result<HttpResponse, HttpError>> {
     let response = async move {
+        if let Some(peer_addr) = req.peer_addr() {
+            let rate_limiter = match &message.0 {
+                Message::Request(request) if request.method == SEND_TX => {
+                    &handler.write_rate_limiter
+                }
+                _ => &handler.read_rate_limiter,
+            };
+            if !rate_limiter.check(peer_addr.ip()) {
+                return Ok(HttpResponse::TooManyRequests().finish());
+            }
+        }
+
         let message = handler.process(message.0).await?;
         Ok(HttpResponse::Ok().json(&message))
     };
     response.boxed()
 }
 
+pub(crate) async fn mempool_stats_handler(
+    req: HttpRequest,
+    handler: web::Data<JsonHandler>,
+) -> HttpResponse {
+    if let Some(peer_addr) = req.peer_addr()
+        && !handler.read_rate_limiter.check(peer_addr.ip())
+    {
+        return HttpResponse::TooManyRequests().finish();
+    }
+
+    let stats = handler
+        .sequencer_state
+        .lock()
+        .await
+        .get_transaction_pool_stats();
+    HttpResponse::Ok().json(stats)
+}
+
 fn get_cors(cors_allowed_origins: &[String]) -> Cors {
     let mut cors = Cors::permissive();
     if cors_allowed_origins != ["*".to_string()] {
@@ -46,7 +80,7 @@ fn get_cors(cors_allowed_origins: &[String]) -> Cors {
 pub fn new_http_server(
     config: RpcConfig,
     seuquencer_core: Arc<Mutex<SequencerCore>>,
-    mempool_handle: MemPoolHandle<EncodedTransaction>,
+    mempool_handle: MemPoolHandle<MempoolTransaction>,
 ) -> io::Result<actix_web::dev::Server> {
     let RpcConfig {
         addr,
@@ -57,8 +91,26 @@ pub fn new_http_server(
     let handler = web::Data::new(JsonHandler {
         sequencer_state: seuquencer_core.clone(),
         mempool_handle,
+        read_rate_limiter: RateLimiter::new(limits_config.max_requests_per_second_per_ip),
+        write_rate_limiter: RateLimiter::new(limits_config.max_write_requests_per_second_per_ip),
     });
 
+    if handler.read_rate_limiter.is_enabled() || handler.write_rate_limiter.is_enabled() {
+        let handler = Arc::downgrade(&handler.clone().into_inner());
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+            loop {
+                interval.tick().await;
+                // Server is gone
+                let Some(handler) = handler.upgrade() else {
+                    break;
+                };
+                handler.read_rate_limiter.sweep_idle();
+                handler.write_rate_limiter.sweep_idle();
+            }
+        });
+    }
+
     // HTTP server
     Ok(HttpServer::new(move || {
         App::new()
@@ -67,6 +119,7 @@ pub fn new_http_server(
             .app_data(web::JsonConfig::default().limit(limits_config.json_payload_max_size))
             .wrap(middleware::Logger::default())
             .service(web::resource("/").route(web::post().to(rpc_handler)))
+            .service(web::resource("/mempool/stats").route(web::get().to(mempool_stats_handler)))
     })
     .bind(addr)?
     .shutdown_timeout(SHUTDOWN_TIMEOUT_SECS)
@@ -5,20 +5,26 @@ use base58::FromBase58;
 use base64::{Engine, engine::general_purpose};
 use common::{
     HashType,
+    activity::{ACTIVITY_WINDOW_BLOCKS, ActivityScore},
     block::HashableBlockData,
     rpc_primitives::{
         errors::RpcError,
         message::{Message, Request},
         parser::RpcRequest,
         requests::{
-            GetAccountBalanceRequest, GetAccountBalanceResponse, GetAccountRequest,
-            GetAccountResponse, GetAccountsNoncesRequest, GetAccountsNoncesResponse,
-            GetBlockDataRequest, GetBlockDataResponse, GetBlockRangeDataRequest,
-            GetBlockRangeDataResponse, GetGenesisIdRequest, GetGenesisIdResponse,
+            BlockProductionStatusResponse, EstimateFeeRequest, EstimateFeeResponse,
+            GetAccountActivityRequest, GetAccountActivityResponse, GetAccountBalanceRequest,
+            GetAccountBalanceResponse, GetAccountRequest, GetAccountResponse,
+            GetAccountsNoncesRequest, GetAccountsNoncesResponse, GetBlockDataRequest,
+            GetBlockDataResponse, GetBlockRangeDataRequest, GetBlockRangeDataResponse,
+            GetFeeParamsRequest, GetFeeParamsResponse, GetGenesisHashRequest,
+            GetGenesisHashResponse, GetGenesisIdRequest, GetGenesisIdResponse,
             GetInitialTestnetAccountsRequest, GetLastBlockRequest, GetLastBlockResponse,
             GetProgramIdsRequest, GetProgramIdsResponse, GetProofForCommitmentRequest,
-            GetProofForCommitmentResponse, GetTransactionByHashRequest,
-            GetTransactionByHashResponse, HelloRequest, HelloResponse, SendTxRequest,
+            GetProofForCommitmentResponse, GetProofForNullifierRequest,
+            GetProofForNullifierResponse, GetTransactionByHashRequest,
+            GetTransactionByHashResponse, HelloRequest, HelloResponse, MAX_BLOCK_RANGE_SIZE,
+            PauseBlockProductionRequest, ResumeBlockProductionRequest, SendTxRequest,
             SendTxResponse,
         },
     },
@@ -27,8 +33,13 @@ use common::{
 use itertools::Itertools as _;
 use log::warn;
 use nssa::{self, program::Program};
-use sequencer_core::{TransactionMalformationError, config::AccountInitialData};
+use sequencer_core::{
+    MempoolTransaction, TransactionMalformationError,
+    config::{AccountInitialData, SequencerConfig},
+};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq as _;
 
 use super::{JsonHandler, respond, types::err_rpc::RpcErr};
 
@@ -37,13 +48,20 @@ pub const SEND_TX: &str = "send_tx";
 pub const GET_BLOCK: &str = "get_block";
 pub const GET_BLOCK_RANGE: &str = "get_block_range";
 pub const GET_GENESIS: &str = "get_genesis";
+pub const GET_GENESIS_HASH: &str = "get_genesis_hash";
 pub const GET_LAST_BLOCK: &str = "get_last_block";
 pub const GET_ACCOUNT_BALANCE: &str = "get_account_balance";
 pub const GET_TRANSACTION_BY_HASH: &str = "get_transaction_by_hash";
 pub const GET_ACCOUNTS_NONCES: &str = "get_accounts_nonces";
 pub const GET_ACCOUNT: &str = "get_account";
 pub const GET_PROOF_FOR_COMMITMENT: &str = "get_proof_for_commitment";
+pub const GET_PROOF_FOR_NULLIFIER: &str = "get_proof_for_nullifier";
 pub const GET_PROGRAM_IDS: &str = "get_program_ids";
+pub const GET_ACCOUNT_ACTIVITY: &str = "get_account_activity";
+pub const PAUSE_BLOCK_PRODUCTION: &str = "pause_block_production";
+pub const RESUME_BLOCK_PRODUCTION: &str = "resume_block_production";
+pub const GET_FEE_PARAMS: &str = "get_fee_params";
+pub const ESTIMATE_FEE: &str = "estimate_fee";
 
 pub const HELLO_FROM_SEQUENCER: &str = "HELLO_FROM_SEQUENCER";
 
@@ -84,16 +102,28 @@ impl JsonHandler {
         let tx = borsh::from_slice::<EncodedTransaction>(&send_tx_req.transaction).unwrap();
         let tx_hash = hex::encode(tx.hash());
 
+        self.sequencer_state
+            .lock()
+            .await
+            .check_transaction_size(&tx)?;
+
+        // Decoding and signature checks don't need the sequencer, so they don't hold up block
+        // production
         let transaction = NSSATransaction::try_from(&tx)
             .map_err(|_| TransactionMalformationError::FailedToDecode { tx: tx.hash() })?;
-
         let authenticated_tx = sequencer_core::transaction_pre_check(transaction)
             .inspect_err(|err| warn!("Error at pre_check {err:#?}"))?;
 
+        {
+            let state = self.sequencer_state.lock().await;
+            state.check_transaction_fee(&tx, &authenticated_tx)?;
+            state.persist_pending_transaction(&tx)?;
+        }
+
         // TODO: Do we need a timeout here? It will be usable if we have too many transactions to
         // process
         self.mempool_handle
-            .push(authenticated_tx.into())
+            .push(MempoolTransaction::new(tx, &authenticated_tx))
             .await
             .expect("Mempool is closed, this is a bug");
 
@@ -126,6 +156,17 @@ impl JsonHandler {
     async fn process_get_block_range_data(&self, request: Request) -> Result<Value, RpcErr> {
         let get_block_req = GetBlockRangeDataRequest::parse(Some(request.params))?;
 
+        let range_size = get_block_req
+            .end_block_id
+            .checked_sub(get_block_req.start_block_id)
+            .ok_or_else(|| RpcError::invalid_params("empty block range".to_string()))?
+            + 1;
+        if range_size > MAX_BLOCK_RANGE_SIZE {
+            return Err(RpcErr(RpcError::invalid_params(format!(
+                "block range size {range_size} exceeds maximum of {MAX_BLOCK_RANGE_SIZE}"
+            ))));
+        }
+
         let blocks = {
             let state = self.sequencer_state.lock().await;
             (get_block_req.start_block_id..=get_block_req.end_block_id)
@@ -156,6 +197,20 @@ impl JsonHandler {
         respond(response)
     }
 
+    async fn process_get_genesis_hash(&self, request: Request) -> Result<Value, RpcErr> {
+        let _get_genesis_hash_req = GetGenesisHashRequest::parse(Some(request.params))?;
+
+        let genesis_hash = {
+            let state = self.sequencer_state.lock().await;
+
+            state.get_genesis_hash()
+        };
+
+        let response = GetGenesisHashResponse { genesis_hash };
+
+        respond(response)
+    }
+
     async fn process_get_last_block(&self, request: Request) -> Result<Value, RpcErr> {
         let _get_last_block_req = GetLastBlockRequest::parse(Some(request.params))?;
 
@@ -258,6 +313,43 @@ impl JsonHandler {
         respond(response)
     }
 
+    /// Returns activity score of account over the latest blocks.
+    /// AccountId must be a valid base58 string of the correct length.
+    async fn process_get_account_activity(&self, request: Request) -> Result<Value, RpcErr> {
+        let get_activity_req = GetAccountActivityRequest::parse(Some(request.params))?;
+
+        let account_id = get_activity_req
+            .account_id
+            .parse::<nssa::AccountId>()
+            .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+
+        // Only blocks in the activity window are read under the lock
+        let (transactions, chain_height) = {
+            let state = self.sequencer_state.lock().await;
+            let chain_height = state.chain_height();
+            let from_block_id = (chain_height + 1).saturating_sub(ACTIVITY_WINDOW_BLOCKS);
+
+            let transactions = state
+                .block_store()
+                .get_transactions_for_account(account_id, from_block_id)?;
+            (transactions, chain_height)
+        };
+
+        let transactions = transactions
+            .into_iter()
+            .map(|(block_id, tx)| {
+                NSSATransaction::try_from(&tx)
+                    .map(|tx| (block_id, tx))
+                    .map_err(|_| TransactionMalformationError::FailedToDecode { tx: tx.hash() })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let activity = ActivityScore::compute(&account_id, &transactions, chain_height);
+
+        let response = GetAccountActivityResponse { activity };
+
+        respond(response)
+    }
+
     /// Returns the transaction corresponding to the given hash, if it exists in the blockchain.
     /// The hash must be a valid hex string of the correct length.
     async fn process_get_transaction_by_hash(&self, request: Request) -> Result<Value, RpcErr> {
@@ -296,6 +388,20 @@ impl JsonHandler {
         respond(response)
     }
 
+    /// Returns the membership proof of spent nullifier together with nullifier set root
+    async fn process_get_proof_by_nullifier(&self, request: Request) -> Result<Value, RpcErr> {
+        let get_proof_req = GetProofForNullifierRequest::parse(Some(request.params))?;
+
+        let membership_proof = {
+            let state = self.sequencer_state.lock().await;
+            state
+                .state()
+                .get_proof_for_nullifier(&get_proof_req.nullifier)
+        };
+        let response = GetProofForNullifierResponse { membership_proof };
+        respond(response)
+    }
+
     async fn process_get_program_ids(&self, request: Request) -> Result<Value, RpcErr> {
         let _get_proof_req = GetProgramIdsRequest::parse(Some(request.params))?;
 
@@ -314,6 +420,64 @@ impl JsonHandler {
         respond(response)
     }
 
+    /// Pauses block production, requires admin token from sequencer config
+    async fn process_pause_block_production(&self, request: Request) -> Result<Value, RpcErr> {
+        let pause_req = PauseBlockProductionRequest::parse(Some(request.params))?;
+
+        let paused = {
+            let mut state = self.sequencer_state.lock().await;
+            check_admin_token(state.sequencer_config(), &pause_req.admin_token)?;
+
+            state.pause_block_production();
+            state.is_block_production_paused()
+        };
+
+        let response = BlockProductionStatusResponse { paused };
+        respond(response)
+    }
+
+    /// Resumes block production, requires admin token from sequencer config
+    async fn process_resume_block_production(&self, request: Request) -> Result<Value, RpcErr> {
+        let resume_req = ResumeBlockProductionRequest::parse(Some(request.params))?;
+
+        let paused = {
+            let mut state = self.sequencer_state.lock().await;
+            check_admin_token(state.sequencer_config(), &resume_req.admin_token)?;
+
+            state.resume_block_production();
+            state.is_block_production_paused()
+        };
+
+        let response = BlockProductionStatusResponse { paused };
+        respond(response)
+    }
+
+    async fn process_get_fee_params(&self, request: Request) -> Result<Value, RpcErr> {
+        let _fee_params_req = GetFeeParamsRequest::parse(Some(request.params))?;
+
+        let fee_params = {
+            let state = self.sequencer_state.lock().await;
+            state.sequencer_config().fee_params
+        };
+
+        let response = GetFeeParamsResponse { fee_params };
+        respond(response)
+    }
+
+    async fn process_estimate_fee(&self, request: Request) -> Result<Value, RpcErr> {
+        let estimate_req = EstimateFeeRequest::parse(Some(request.params))?;
+        let tx = borsh::from_slice::<EncodedTransaction>(&estimate_req.transaction)
+            .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+
+        let fee = {
+            let state = self.sequencer_state.lock().await;
+            state.required_fee(&tx)
+        };
+
+        let response = EstimateFeeResponse { fee };
+        respond(response)
+    }
+
     pub async fn process_request_internal(&self, request: Request) -> Result<Value, RpcErr> {
         match request.method.as_ref() {
             HELLO => self.process_temp_hello(request).await,
@@ -321,6 +485,7 @@ impl JsonHandler {
             GET_BLOCK => self.process_get_block_data(request).await,
             GET_BLOCK_RANGE => self.process_get_block_range_data(request).await,
             GET_GENESIS => self.process_get_genesis(request).await,
+            GET_GENESIS_HASH => self.process_get_genesis_hash(request).await,
             GET_LAST_BLOCK => self.process_get_last_block(request).await,
             GET_INITIAL_TESTNET_ACCOUNTS => self.get_initial_testnet_accounts(request).await,
             GET_ACCOUNT_BALANCE => self.process_get_account_balance(request).await,
@@ -328,28 +493,59 @@ impl JsonHandler {
             GET_ACCOUNT => self.process_get_account(request).await,
             GET_TRANSACTION_BY_HASH => self.process_get_transaction_by_hash(request).await,
             GET_PROOF_FOR_COMMITMENT => self.process_get_proof_by_commitment(request).await,
+            GET_PROOF_FOR_NULLIFIER => self.process_get_proof_by_nullifier(request).await,
             GET_PROGRAM_IDS => self.process_get_program_ids(request).await,
+            GET_ACCOUNT_ACTIVITY => self.process_get_account_activity(request).await,
+            PAUSE_BLOCK_PRODUCTION => self.process_pause_block_production(request).await,
+            RESUME_BLOCK_PRODUCTION => self.process_resume_block_production(request).await,
+            GET_FEE_PARAMS => self.process_get_fee_params(request).await,
+            ESTIMATE_FEE => self.process_estimate_fee(request).await,
             _ => Err(RpcErr(RpcError::method_not_found(request.method))),
         }
     }
 }
 
+fn check_admin_token(config: &SequencerConfig, admin_token: &str) -> Result<(), RpcErr> {
+    // Digests of equal length are compared in constant time, so timing leaks neither the token
+    // nor its length
+    let is_valid = config.admin_token.as_ref().is_some_and(|expected_token| {
+        let expected_digest = Sha256::digest(expected_token.as_bytes());
+        let digest = Sha256::digest(admin_token.as_bytes());
+        bool::from(expected_digest.ct_eq(&digest))
+    });
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(RpcErr(RpcError::invalid_params(
+            "invalid admin token".to_string(),
+        )))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
     use base58::ToBase58;
     use base64::{Engine, engine::general_purpose};
-    use common::{test_utils::sequencer_sign_key_for_testing, transaction::EncodedTransaction};
+    use common::{
+        fee::FeeParams,
+        rpc_primitives::{RpcLimitsConfig, requests::MAX_BLOCK_RANGE_SIZE},
+        test_utils::sequencer_sign_key_for_testing,
+        transaction::EncodedTransaction,
+    };
     use sequencer_core::{
         SequencerCore,
-        config::{AccountInitialData, SequencerConfig},
+        config::{AccountInitialData, DEFAULT_MAX_TX_SIZE_BYTES, SequencerConfig},
     };
     use serde_json::Value;
     use tempfile::tempdir;
     use tokio::sync::Mutex;
 
-    use crate::{JsonHandler, rpc_handler};
+    use crate::{JsonHandler, mempool_stats_handler, rate_limiter::RateLimiter, rpc_handler};
+
+    const ADMIN_TOKEN_FOR_TESTS: &str = "admin_token_for_tests";
 
     fn sequencer_config_for_tests() -> SequencerConfig {
         let tempdir = tempdir().unwrap();
@@ -388,11 +584,22 @@ mod tests {
             initial_accounts,
             initial_commitments: vec![],
             signing_key: *sequencer_sign_key_for_testing().value(),
+            admin_token: Some(ADMIN_TOKEN_FOR_TESTS.to_string()),
+            persist_mempool: false,
+            max_tx_size_bytes: DEFAULT_MAX_TX_SIZE_BYTES,
+            fee_params: FeeParams::default(),
+            rpc_limits: RpcLimitsConfig::default(),
+            state_snapshot_path: None,
         }
     }
 
     async fn components_for_tests() -> (JsonHandler, Vec<AccountInitialData>, EncodedTransaction) {
-        let config = sequencer_config_for_tests();
+        components_for_tests_with_config(sequencer_config_for_tests()).await
+    }
+
+    async fn components_for_tests_with_config(
+        config: SequencerConfig,
+    ) -> (JsonHandler, Vec<AccountInitialData>, EncodedTransaction) {
         let (mut sequencer_core, mempool_handle) = SequencerCore::start_from_config(config);
         let initial_accounts = sequencer_core.sequencer_config().initial_accounts.clone();
 
@@ -410,7 +617,7 @@ mod tests {
         );
 
         mempool_handle
-            .push(tx.clone())
+            .push(tx.clone().into())
             .await
             .expect("Mempool is closed, this is a bug");
 
@@ -424,6 +631,8 @@ mod tests {
             JsonHandler {
                 sequencer_state: sequencer_core,
                 mempool_handle,
+                read_rate_limiter: RateLimiter::new(0),
+                write_rate_limiter: RateLimiter::new(0),
             },
             initial_accounts,
             tx,
@@ -619,6 +828,36 @@ mod tests {
         assert_eq!(response, expected_response);
     }
 
+    #[actix_web::test]
+    async fn test_get_account_activity() {
+        let (json_handler, initial_accounts, _) = components_for_tests().await;
+
+        let acc1_id = initial_accounts[0].account_id.clone();
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "get_account_activity",
+            "params": { "account_id": acc1_id },
+            "id": 1
+        });
+        let expected_response = serde_json::json!({
+            "id": 1,
+            "jsonrpc": "2.0",
+            "result": {
+                "activity": {
+                    "tx_count_last_100_blocks": 1,
+                    "unique_recipients": 1,
+                    "avg_amount": 10,
+                    "score": 210,
+                }
+            }
+        });
+
+        let response = call_rpc_handler_with_json(json_handler, request).await;
+
+        assert_eq!(response, expected_response);
+    }
+
     #[actix_web::test]
     async fn test_get_transaction_by_hash_for_non_existent_hash() {
         let (json_handler, _, _) = components_for_tests().await;
@@ -713,4 +952,436 @@ mod tests {
 
         assert_eq!(response, expected_response);
     }
+
+    #[actix_web::test]
+    async fn test_pause_block_production() {
+        let (json_handler, _, _) = components_for_tests().await;
+        let sequencer_state = json_handler.sequencer_state.clone();
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "pause_block_production",
+            "params": { "admin_token": ADMIN_TOKEN_FOR_TESTS },
+            "id": 1
+        });
+        let expected_response = serde_json::json!({
+            "id": 1,
+            "jsonrpc": "2.0",
+            "result": {
+                "paused": true
+            }
+        });
+
+        let response = call_rpc_handler_with_json(json_handler, request).await;
+
+        assert_eq!(response, expected_response);
+        assert!(sequencer_state.lock().await.is_block_production_paused());
+    }
+
+    #[actix_web::test]
+    async fn test_resume_block_production() {
+        let (json_handler, _, _) = components_for_tests().await;
+        let sequencer_state = json_handler.sequencer_state.clone();
+        sequencer_state.lock().await.pause_block_production();
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "resume_block_production",
+            "params": { "admin_token": ADMIN_TOKEN_FOR_TESTS },
+            "id": 1
+        });
+        let expected_response = serde_json::json!({
+            "id": 1,
+            "jsonrpc": "2.0",
+            "result": {
+                "paused": false
+            }
+        });
+
+        let response = call_rpc_handler_with_json(json_handler, request).await;
+
+        assert_eq!(response, expected_response);
+        assert!(!sequencer_state.lock().await.is_block_production_paused());
+    }
+
+    #[test]
+    fn test_check_admin_token() {
+        let mut config = sequencer_config_for_tests();
+
+        assert!(super::check_admin_token(&config, ADMIN_TOKEN_FOR_TESTS).is_ok());
+        assert!(super::check_admin_token(&config, "admin_token_for_test").is_err());
+        assert!(super::check_admin_token(&config, "").is_err());
+
+        config.admin_token = None;
+        assert!(super::check_admin_token(&config, ADMIN_TOKEN_FOR_TESTS).is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_pause_block_production_with_invalid_admin_token() {
+        let (json_handler, _, _) = components_for_tests().await;
+        let sequencer_state = json_handler.sequencer_state.clone();
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "pause_block_production",
+            "params": { "admin_token": "not_an_admin_token" },
+            "id": 1
+        });
+        let expected_response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": {
+                "code": -32602,
+                "message": "Invalid params",
+                "data": "invalid admin token"
+            }
+        });
+
+        let response = call_rpc_handler_with_json(json_handler, request).await;
+
+        assert_eq!(response, expected_response);
+        assert!(!sequencer_state.lock().await.is_block_production_paused());
+    }
+
+    #[actix_web::test]
+    async fn test_get_block_range_exceeding_max_size() {
+        let (json_handler, _, _) = components_for_tests().await;
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "get_block_range",
+            "params": { "start_block_id": 1, "end_block_id": MAX_BLOCK_RANGE_SIZE + 1 },
+            "id": 1
+        });
+        let expected_response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": {
+                "code": -32602,
+                "message": "Invalid params",
+                "data": format!(
+                    "block range size {} exceeds maximum of {MAX_BLOCK_RANGE_SIZE}",
+                    MAX_BLOCK_RANGE_SIZE + 1
+                )
+            }
+        });
+
+        let response = call_rpc_handler_with_json(json_handler, request).await;
+
+        assert_eq!(response, expected_response);
+    }
+
+    #[actix_web::test]
+    async fn test_send_tx_exceeding_max_size() {
+        let tx_of_size = |size| EncodedTransaction {
+            tx_kind: common::transaction::TxKind::Public,
+            encoded_transaction_data: vec![0; size],
+        };
+        let send_tx_request = |tx: &EncodedTransaction| {
+            let transaction = general_purpose::STANDARD.encode(borsh::to_vec(tx).unwrap());
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "send_tx",
+                "params": { "transaction": transaction },
+                "id": 1
+            })
+        };
+
+        let tx = tx_of_size(DEFAULT_MAX_TX_SIZE_BYTES + 1);
+        let expected_response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": {
+                "code": -32602,
+                "message": "Invalid params",
+                "data": {
+                    "TransactionTooLarge": {
+                        "tx": tx.hash(),
+                        "size": DEFAULT_MAX_TX_SIZE_BYTES + 1,
+                        "max_size": DEFAULT_MAX_TX_SIZE_BYTES,
+                    }
+                }
+            }
+        });
+
+        let (json_handler, _, _) = components_for_tests().await;
+        let response = call_rpc_handler_with_json(json_handler, send_tx_request(&tx)).await;
+
+        assert_eq!(response, expected_response);
+
+        // Transaction of exactly maximum size passes the size check
+        let tx = tx_of_size(DEFAULT_MAX_TX_SIZE_BYTES);
+        let (json_handler, _, _) = components_for_tests().await;
+        let response = call_rpc_handler_with_json(json_handler, send_tx_request(&tx)).await;
+
+        assert!(
+            response["error"]["data"]
+                .get("TransactionTooLarge")
+                .is_none()
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_mempool_stats() {
+        use actix_web::{App, test, web};
+
+        let (json_handler, _, _) = components_for_tests().await;
+        let num_txs = 3;
+        for _ in 0..num_txs {
+            json_handler
+                .mempool_handle
+                .push(common::test_utils::produce_dummy_empty_transaction().into())
+                .await
+                .unwrap();
+        }
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(json_handler))
+                .route("/mempool/stats", web::get().to(mempool_stats_handler)),
+        )
+        .await;
+        let req = test::TestRequest::get().uri("/mempool/stats").to_request();
+        let stats: Value = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(stats["count"], num_txs);
+        assert!(stats["total_size_bytes"].as_u64().unwrap() > 0);
+        assert!(stats["oldest_tx_age_ms"].is_u64());
+    }
+
+    #[actix_web::test]
+    async fn test_mempool_stats_shares_read_rate_limit() {
+        use actix_web::{App, http::StatusCode, test, web};
+
+        let (mut json_handler, _, _) = components_for_tests().await;
+        json_handler.read_rate_limiter = RateLimiter::new(2);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(json_handler))
+                .route("/", web::post().to(rpc_handler))
+                .route("/mempool/stats", web::get().to(mempool_stats_handler)),
+        )
+        .await;
+
+        let client_1 = "10.0.0.1:5000";
+        let client_2 = "10.0.0.2:5000";
+        let stats = |peer: &str| {
+            let req = test::TestRequest::get()
+                .uri("/mempool/stats")
+                .peer_addr(peer.parse().unwrap())
+                .to_request();
+            test::call_service(&app, req)
+        };
+
+        let hello = test::TestRequest::post()
+            .uri("/")
+            .peer_addr(client_1.parse().unwrap())
+            .set_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "hello",
+                "params": {},
+            }))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, hello).await.status(),
+            StatusCode::OK
+        );
+        assert_eq!(stats(client_1).await.status(), StatusCode::OK);
+        assert_eq!(
+            stats(client_1).await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(stats(client_2).await.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_get_fee_params() {
+        let fee_params = FeeParams {
+            base_fee: 10,
+            fee_per_kb: 5,
+        };
+        let config = SequencerConfig {
+            fee_params,
+            ..sequencer_config_for_tests()
+        };
+        let (json_handler, _, _) = components_for_tests_with_config(config).await;
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "get_fee_params",
+            "params": {},
+            "id": 1
+        });
+        let expected_response = serde_json::json!({
+            "id": 1,
+            "jsonrpc": "2.0",
+            "result": {
+                "fee_params": {
+                    "base_fee": 10,
+                    "fee_per_kb": 5,
+                }
+            }
+        });
+
+        let response = call_rpc_handler_with_json(json_handler, request).await;
+
+        assert_eq!(response, expected_response);
+    }
+
+    #[actix_web::test]
+    async fn test_estimate_fee_grows_with_tx_size() {
+        use actix_web::{App, test, web};
+
+        let config = SequencerConfig {
+            fee_params: FeeParams {
+                base_fee: 10,
+                fee_per_kb: 5,
+            },
+            ..sequencer_config_for_tests()
+        };
+        let (json_handler, _, _) = components_for_tests_with_config(config).await;
+        let json_handler = web::Data::new(json_handler);
+        let app = test::init_service(
+            App::new()
+                .app_data(json_handler)
+                .route("/", web::post().to(rpc_handler)),
+        )
+        .await;
+
+        let estimate_fee = |size| {
+            let tx = EncodedTransaction {
+                tx_kind: common::transaction::TxKind::Public,
+                encoded_transaction_data: vec![0; size],
+            };
+            let request = serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "estimate_fee",
+                "params": {
+                    "transaction": general_purpose::STANDARD.encode(borsh::to_vec(&tx).unwrap()),
+                },
+                "id": 1
+            });
+            let req = test::TestRequest::post()
+                .uri("/")
+                .set_json(request)
+                .to_request();
+            test::call_and_read_body_json(&app, req)
+        };
+
+        let small_tx_response: Value = estimate_fee(100).await;
+        let large_tx_response: Value = estimate_fee(5000).await;
+
+        assert_eq!(small_tx_response["result"]["fee"], 15);
+        assert_eq!(large_tx_response["result"]["fee"], 35);
+    }
+
+    #[actix_web::test]
+    async fn test_rpc_handler_rate_limits_per_ip() {
+        use actix_web::{App, http::StatusCode, test, web};
+
+        let (mut json_handler, _, tx) = components_for_tests().await;
+        json_handler.read_rate_limiter = RateLimiter::new(3);
+        json_handler.write_rate_limiter = RateLimiter::new(1);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(json_handler))
+                .route("/", web::post().to(rpc_handler)),
+        )
+        .await;
+
+        let hello = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "hello",
+            "params": {},
+        });
+        let send_tx = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "send_tx",
+            "params": {
+                "transaction": general_purpose::STANDARD.encode(borsh::to_vec(&tx).unwrap()),
+            },
+        });
+        let call = |request_json: &Value, peer: &str| {
+            let req = test::TestRequest::post()
+                .uri("/")
+                .peer_addr(peer.parse().unwrap())
+                .set_json(request_json)
+                .to_request();
+            test::call_service(&app, req)
+        };
+
+        let client_1 = "10.0.0.1:5000";
+        let client_2 = "10.0.0.2:5000";
+
+        for _ in 0..3 {
+            assert_eq!(call(&hello, client_1).await.status(), StatusCode::OK);
+        }
+        assert_eq!(
+            call(&hello, client_1).await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(call(&hello, client_2).await.status(), StatusCode::OK);
+
+        assert_eq!(call(&send_tx, client_2).await.status(), StatusCode::OK);
+        assert_eq!(
+            call(&send_tx, client_2).await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(call(&hello, client_2).await.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_get_genesis_hash() {
+        let (json_handler, _, _) = components_for_tests().await;
+        let genesis_hash = json_handler
+            .sequencer_state
+            .lock()
+            .await
+            .sequencer_config()
+            .genesis_hash();
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "get_genesis_hash",
+            "params": {},
+            "id": 1
+        });
+        let expected_response = serde_json::json!({
+            "id": 1,
+            "jsonrpc": "2.0",
+            "result": {
+                "genesis_hash": genesis_hash
+            }
+        });
+
+        let response = call_rpc_handler_with_json(json_handler, request).await;
+
+        assert_eq!(response, expected_response);
+    }
+
+    #[actix_web::test]
+    async fn test_get_proof_for_unspent_nullifier() {
+        let (json_handler, _, _) = components_for_tests().await;
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "get_proof_for_nullifier",
+            "params": { "nullifier": vec![7u8; 32] },
+            "id": 1
+        });
+        let expected_response = serde_json::json!({
+            "id": 1,
+            "jsonrpc": "2.0",
+            "result": {
+                "membership_proof": null
+            }
+        });
+
+        let response = call_rpc_handler_with_json(json_handler, request).await;
+
+        assert_eq!(response, expected_response);
+    }
 }
@@ -0,0 +1,176 @@
+use std::{
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use lru::LruCache;
+
+/// Maximum number of tracked IPs, the least recently seen IP is forgotten when it is exceeded
+const MAX_TRACKED_IPS: usize = 10_000;
+
+/// Interval in which buckets of idle IPs are dropped
+pub const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Per-IP token bucket rate limiter.
+///
+/// Every IP gets a bucket of `max_per_second` tokens, refilled continuously at `max_per_second`
+/// tokens per second. Each accepted request takes one token.
+pub struct RateLimiter {
+    max_per_second: u32,
+    buckets: Mutex<LruCache<IpAddr, TokenBucket>>,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// Limiter accepting up to `max_per_second` requests per second per IP, `0` disables it
+    pub fn new(max_per_second: u32) -> Self {
+        Self::with_max_tracked_ips(max_per_second, MAX_TRACKED_IPS)
+    }
+
+    fn with_max_tracked_ips(max_per_second: u32, max_tracked_ips: usize) -> Self {
+        Self {
+            max_per_second,
+            buckets: Mutex::new(LruCache::new(max_tracked_ips)),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_per_second != 0
+    }
+
+    /// Takes a token from the bucket of `ip`, returns `false` if there is none left
+    pub fn check(&self, ip: IpAddr) -> bool {
+        self.check_at(ip, Instant::now())
+    }
+
+    fn check_at(&self, ip: IpAddr, now: Instant) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+
+        let capacity = f64::from(self.max_per_second);
+        let mut buckets = self.buckets.lock().unwrap();
+
+        let Some(bucket) = buckets.get_mut(&ip) else {
+            buckets.put(
+                ip,
+                TokenBucket {
+                    tokens: capacity - 1.0,
+                    last_refill: now,
+                },
+            );
+            return true;
+        };
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * capacity).min(capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Drops buckets of IPs, which were refilled completely, as they are equal to fresh ones
+    pub fn sweep_idle(&self) {
+        self.sweep_idle_at(Instant::now());
+    }
+
+    fn sweep_idle_at(&self, now: Instant) {
+        let refill_time = Duration::from_secs(1);
+        let mut buckets = self.buckets.lock().unwrap();
+
+        let idle_ips: Vec<IpAddr> = buckets
+            .iter()
+            .filter(|(_, bucket)| now.duration_since(bucket.last_refill) >= refill_time)
+            .map(|(ip, _)| *ip)
+            .collect();
+        for ip in idle_ips {
+            buckets.pop(&ip);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IP_1: IpAddr = IpAddr::V4(std::net::Ipv4Addr::new(10, 0, 0, 1));
+    const IP_2: IpAddr = IpAddr::V4(std::net::Ipv4Addr::new(10, 0, 0, 2));
+    const IP_3: IpAddr = IpAddr::V4(std::net::Ipv4Addr::new(10, 0, 0, 3));
+
+    #[test]
+    fn test_rate_limiter_rejects_over_limit() {
+        let limiter = RateLimiter::new(3);
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.check_at(IP_1, now));
+        }
+        assert!(!limiter.check_at(IP_1, now));
+        assert!(limiter.check_at(IP_2, now));
+    }
+
+    #[test]
+    fn test_rate_limiter_refills_over_time() {
+        let limiter = RateLimiter::new(2);
+        let now = Instant::now();
+
+        assert!(limiter.check_at(IP_1, now));
+        assert!(limiter.check_at(IP_1, now));
+        assert!(!limiter.check_at(IP_1, now));
+
+        let later = now + Duration::from_millis(500);
+        assert!(limiter.check_at(IP_1, later));
+        assert!(!limiter.check_at(IP_1, later));
+    }
+
+    #[test]
+    fn test_rate_limiter_disabled() {
+        let limiter = RateLimiter::new(0);
+        let now = Instant::now();
+
+        for _ in 0..1000 {
+            assert!(limiter.check_at(IP_1, now));
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter_forgets_least_recently_seen_ip_at_capacity() {
+        let limiter = RateLimiter::with_max_tracked_ips(1, 2);
+        let now = Instant::now();
+
+        assert!(limiter.check_at(IP_1, now));
+        assert!(limiter.check_at(IP_2, now));
+        assert!(!limiter.check_at(IP_1, now));
+        // IP_2 is the least recently seen one now
+        assert!(limiter.check_at(IP_3, now));
+
+        assert_eq!(limiter.buckets.lock().unwrap().len(), 2);
+        assert!(limiter.check_at(IP_2, now));
+        assert!(!limiter.check_at(IP_3, now));
+    }
+
+    #[test]
+    fn test_sweep_drops_only_refilled_buckets() {
+        let limiter = RateLimiter::new(1);
+        let now = Instant::now();
+
+        assert!(limiter.check_at(IP_1, now));
+        assert!(limiter.check_at(IP_2, now + Duration::from_millis(900)));
+
+        limiter.sweep_idle_at(now + Duration::from_secs(1));
+
+        let buckets = limiter.buckets.lock().unwrap();
+        assert!(!buckets.contains(&IP_1));
+        assert!(buckets.contains(&IP_2));
+    }
+}
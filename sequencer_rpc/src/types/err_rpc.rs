@@ -42,10 +42,16 @@ impl RpcErrKind for RpcErrInternal {
 
 impl RpcErrKind for TransactionMalformationError {
     fn into_rpc_err(self) -> RpcError {
-        RpcError::new_internal_error(
-            Some(serde_json::to_value(self).unwrap()),
-            "transaction not accepted",
-        )
+        match self {
+            TransactionMalformationError::TransactionTooLarge { .. }
+            | TransactionMalformationError::InsufficientFee { .. } => {
+                RpcError::invalid_params(self)
+            }
+            _ => RpcError::new_internal_error(
+                Some(serde_json::to_value(self).unwrap()),
+                "transaction not accepted",
+            ),
+        }
     }
 }
 
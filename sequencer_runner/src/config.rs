@@ -1,7 +1,14 @@
-use std::{fs::File, io::BufReader, path::PathBuf};
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
 
 use anyhow::Result;
+use log::warn;
+use notify::{Event, RecursiveMode, Watcher};
 use sequencer_core::config::SequencerConfig;
+use tokio::sync::{mpsc, watch};
 
 pub fn from_file(config_home: PathBuf) -> Result<SequencerConfig> {
     let file = File::open(config_home)?;
@@ -9,3 +16,95 @@ pub fn from_file(config_home: PathBuf) -> Result<SequencerConfig> {
 
     Ok(serde_json::from_reader(reader)?)
 }
+
+/// Watches the config file and publishes the reparsed config on every change.
+/// Changes that fail to parse are logged and skipped. Must be called within a tokio runtime.
+pub fn watch_config_file(config_path: PathBuf) -> Result<watch::Receiver<SequencerConfig>> {
+    let (config_sender, config_receiver) = watch::channel(from_file(config_path.clone())?);
+
+    let (event_sender, mut event_receiver) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        let _ = event_sender.send(event);
+    })?;
+    // Editors often replace the file instead of writing it in place, so watch its directory
+    let config_dir = match config_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    watcher.watch(config_dir, RecursiveMode::NonRecursive)?;
+
+    tokio::spawn(async move {
+        // Keep the watcher alive for as long as someone listens for updates
+        let _watcher = watcher;
+        while let Some(event) = event_receiver.recv().await {
+            if config_sender.is_closed() {
+                break;
+            }
+            let event = match event {
+                Ok(event) => event,
+                Err(err) => {
+                    warn!("Config watcher error: {err}");
+                    continue;
+                }
+            };
+            let touches_config = event
+                .paths
+                .iter()
+                .any(|path| path.file_name() == config_path.file_name());
+            if !touches_config || !(event.kind.is_modify() || event.kind.is_create()) {
+                continue;
+            }
+            match from_file(config_path.clone()) {
+                Ok(config) => {
+                    config_sender.send_replace(config);
+                }
+                Err(err) => warn!("Failed to reload config from {config_path:?}: {err:#}"),
+            }
+        }
+    });
+
+    Ok(config_receiver)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn write_config_with_block_timeout(path: &Path, block_create_timeout_millis: u64) {
+        let debug_config =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("configs/debug/sequencer_config.json");
+        let mut config: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(debug_config).unwrap()).unwrap();
+        config["block_create_timeout_millis"] = block_create_timeout_millis.into();
+        std::fs::write(path, serde_json::to_vec(&config).unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_watch_config_file_publishes_changes() {
+        let home = tempfile::tempdir().unwrap();
+        let config_path = home.path().join("sequencer_config.json");
+        write_config_with_block_timeout(&config_path, 1000);
+
+        let mut config_receiver = watch_config_file(config_path.clone()).unwrap();
+        assert_eq!(config_receiver.borrow().block_create_timeout_millis, 1000);
+
+        write_config_with_block_timeout(&config_path, 50);
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                config_receiver.changed().await.unwrap();
+                if config_receiver
+                    .borrow_and_update()
+                    .block_create_timeout_millis
+                    == 50
+                {
+                    break;
+                }
+            }
+        })
+        .await
+        .expect("Config change must be published");
+    }
+}
@@ -1,13 +1,18 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{path::PathBuf, sync::Arc, time::Duration};
 
 use actix_web::dev::ServerHandle;
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use common::rpc_primitives::RpcConfig;
-use log::info;
+use log::{info, warn};
 use sequencer_core::{SequencerCore, config::SequencerConfig};
 use sequencer_rpc::new_http_server;
-use tokio::{sync::Mutex, task::JoinHandle};
+use tokio::{
+    signal::unix::{SignalKind, signal},
+    sync::{Mutex, watch},
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
 
 pub mod config;
 
@@ -18,13 +23,43 @@ pub const RUST_LOG: &str = "RUST_LOG";
 struct Args {
     /// Path to configs
     home_dir: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Export signed blocks into a file and exit. The file is newline-delimited JSON with one
+    /// block per line, in the format of `get_block` RPC responses
+    Export {
+        /// First block to export
+        #[arg(long)]
+        from: u64,
+        /// Last block to export
+        #[arg(long)]
+        to: u64,
+        path: PathBuf,
+    },
+    /// Import blocks exported by `export` on top of the stored chain and exit
+    Import { path: PathBuf },
+}
+
+/// Time given to the main loop to finish the block in progress on shutdown
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Starts the sequencer. If `config_updates` is given, block production parameters of
+/// every published config are applied between blocks. Once `shutdown` is cancelled the
+/// main loop finishes the block in progress, flushes the store and exits.
 pub async fn startup_sequencer(
     app_config: SequencerConfig,
+    config_updates: Option<watch::Receiver<SequencerConfig>>,
+    shutdown: CancellationToken,
 ) -> Result<(ServerHandle, JoinHandle<Result<()>>)> {
     let block_timeout = app_config.block_create_timeout_millis;
-    let port = app_config.port;
+    let rpc_config = RpcConfig {
+        limits_config: app_config.rpc_limits.clone(),
+        ..RpcConfig::with_port(app_config.port)
+    };
 
     let (sequencer_core, mempool_handle) = SequencerCore::start_from_config(app_config);
 
@@ -32,45 +67,83 @@ pub async fn startup_sequencer(
 
     let seq_core_wrapped = Arc::new(Mutex::new(sequencer_core));
 
-    let http_server = new_http_server(
-        RpcConfig::with_port(port),
-        Arc::clone(&seq_core_wrapped),
-        mempool_handle,
-    )?;
+    let http_server = new_http_server(rpc_config, Arc::clone(&seq_core_wrapped), mempool_handle)?;
     info!("HTTP server started");
     let http_server_handle = http_server.handle();
     tokio::spawn(http_server);
 
     info!("Starting main sequencer loop");
 
-    let main_loop_handle = tokio::spawn(async move {
-        loop {
-            tokio::time::sleep(std::time::Duration::from_millis(block_timeout)).await;
+    let main_loop_handle = tokio::spawn(main_loop(
+        seq_core_wrapped,
+        block_timeout,
+        config_updates,
+        shutdown,
+    ));
+
+    Ok((http_server_handle, main_loop_handle))
+}
+
+async fn main_loop(
+    seq_core_wrapped: Arc<Mutex<SequencerCore>>,
+    mut block_timeout: u64,
+    mut config_updates: Option<watch::Receiver<SequencerConfig>>,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    loop {
+        if let Some(config_updates) = config_updates.as_mut()
+            && config_updates.has_changed().unwrap_or(false)
+        {
+            let new_config = config_updates.borrow_and_update().clone();
+            block_timeout = new_config.block_create_timeout_millis;
+            seq_core_wrapped
+                .lock()
+                .await
+                .apply_config_update(&new_config);
+            info!(
+                "Config reloaded, block timeout {block_timeout}ms, max {} transactions in block",
+                new_config.max_num_tx_in_block
+            );
+        }
+
+        // Shutdown is only observed here, so a block that has been started is always finished
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = tokio::time::sleep(Duration::from_millis(block_timeout)) => {}
+        }
+
+        let id = {
+            let mut state = seq_core_wrapped.lock().await;
+
+            if state.is_block_production_paused() {
+                info!("Block production paused, skipping block creation");
+                continue;
+            }
 
             info!("Collecting transactions from mempool, block creation");
 
-            let id = {
-                let mut state = seq_core_wrapped.lock().await;
+            state.produce_new_block_with_mempool_transactions()?
+        };
 
-                state.produce_new_block_with_mempool_transactions()?
-            };
+        info!("Block with id {id} created");
 
-            info!("Block with id {id} created");
+        info!("Waiting for new transactions");
+    }
 
-            info!("Waiting for new transactions");
-        }
-    });
+    seq_core_wrapped.lock().await.block_store().flush()?;
+    info!("Block production stopped, store flushed");
 
-    Ok((http_server_handle, main_loop_handle))
+    Ok(())
 }
 
 pub async fn main_runner() -> Result<()> {
     env_logger::init();
 
     let args = Args::parse();
-    let Args { home_dir } = args;
+    let Args { home_dir, command } = args;
 
-    let app_config = config::from_file(home_dir.join("sequencer_config.json"))?;
+    let config_path = home_dir.join("sequencer_config.json");
+    let app_config = config::from_file(config_path.clone())?;
 
     if let Some(ref rust_log) = app_config.override_rust_log {
         info!("RUST_LOG env var set to {rust_log:?}");
@@ -80,10 +153,148 @@ pub async fn main_runner() -> Result<()> {
         }
     }
 
+    if let Some(command) = command {
+        let (mut sequencer_core, _) = SequencerCore::start_from_config(app_config);
+        match command {
+            Command::Export { from, to, path } => {
+                sequencer_core.export_blocks_to_file(from, to, &path)?;
+                info!("Exported blocks {from}..={to} to {path:?}");
+            }
+            Command::Import { path } => {
+                let height = sequencer_core.import_blocks_from_file(&path)?;
+                info!("Imported blocks from {path:?}, chain height is {height}");
+            }
+        }
+        return Ok(());
+    }
+
     // ToDo: Add restart on failures
-    let (_, main_loop_handle) = startup_sequencer(app_config).await?;
+    let config_updates = config::watch_config_file(config_path)?;
+    let shutdown = CancellationToken::new();
+    let (http_server_handle, mut main_loop_handle) =
+        startup_sequencer(app_config, Some(config_updates), shutdown.clone()).await?;
+
+    let mut sigterm = signal(SignalKind::terminate())?;
+
+    tokio::select! {
+        main_loop_result = &mut main_loop_handle => return main_loop_result?,
+        _ = tokio::signal::ctrl_c() => info!("Received Ctrl-C"),
+        _ = sigterm.recv() => info!("Received SIGTERM"),
+    }
 
-    main_loop_handle.await??;
+    info!("Shutting down sequencer");
+
+    // Finish in-flight requests before stopping block production
+    http_server_handle.stop(true).await;
+    shutdown.cancel();
+
+    match tokio::time::timeout(SHUTDOWN_TIMEOUT, &mut main_loop_handle).await {
+        Ok(main_loop_result) => main_loop_result??,
+        Err(_) => {
+            warn!("Block production did not stop within {SHUTDOWN_TIMEOUT:?}, aborting");
+            main_loop_handle.abort();
+        }
+    }
+
+    info!("Sequencer stopped");
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn config_for_tests(home: &Path) -> SequencerConfig {
+        let debug_config =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("configs/debug/sequencer_config.json");
+        let mut config = config::from_file(debug_config).unwrap();
+        config.home = home.to_path_buf();
+        config.state_snapshot_path = Some(home.join("state_snapshot.bin"));
+        config.block_create_timeout_millis = 10;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_finishes_block_in_progress() {
+        let home = tempfile::tempdir().unwrap();
+        let config = config_for_tests(home.path());
+        let (sequencer_core, _mempool_handle) = SequencerCore::start_from_config(config.clone());
+        let seq_core_wrapped = Arc::new(Mutex::new(sequencer_core));
+        let shutdown = CancellationToken::new();
+
+        // Holding the lock stops the loop right before it produces a block
+        let state = seq_core_wrapped.lock().await;
+        let main_loop_handle = tokio::spawn(main_loop(
+            Arc::clone(&seq_core_wrapped),
+            config.block_create_timeout_millis,
+            None,
+            shutdown.clone(),
+        ));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let height_before_shutdown = state.chain_height();
+        shutdown.cancel();
+        drop(state);
+
+        tokio::time::timeout(SHUTDOWN_TIMEOUT, main_loop_handle)
+            .await
+            .expect("Main loop must stop within shutdown timeout")
+            .unwrap()
+            .unwrap();
+
+        let seq_core = Arc::into_inner(seq_core_wrapped).unwrap().into_inner();
+        let height = seq_core.chain_height();
+        assert_eq!(height, height_before_shutdown + 1);
+        drop(seq_core);
+
+        let (restarted_core, _mempool_handle) = SequencerCore::start_from_config(config);
+        assert_eq!(restarted_core.chain_height(), height);
+        let last_block = restarted_core
+            .block_store()
+            .get_block_at_id(height)
+            .unwrap();
+        assert_eq!(last_block.header.block_id, height);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_main_loop_sleeps_reloaded_block_timeout() {
+        let home = tempfile::tempdir().unwrap();
+        let config = SequencerConfig {
+            block_create_timeout_millis: 60_000,
+            ..config_for_tests(home.path())
+        };
+        let (sequencer_core, _mempool_handle) = SequencerCore::start_from_config(config.clone());
+        let genesis_height = sequencer_core.chain_height();
+        let seq_core_wrapped = Arc::new(Mutex::new(sequencer_core));
+        let (config_sender, config_receiver) = watch::channel(config.clone());
+        let shutdown = CancellationToken::new();
+
+        let main_loop_handle = tokio::spawn(main_loop(
+            Arc::clone(&seq_core_wrapped),
+            config.block_create_timeout_millis,
+            Some(config_receiver),
+            shutdown.clone(),
+        ));
+        // Lets the loop start sleeping with the initial timeout
+        tokio::task::yield_now().await;
+
+        config_sender
+            .send(SequencerConfig {
+                block_create_timeout_millis: 100,
+                ..config
+            })
+            .unwrap();
+
+        // The sleep in progress is not interrupted, the one after the next block is shorter
+        tokio::time::sleep(Duration::from_millis(60_000 + 150)).await;
+        assert_eq!(
+            seq_core_wrapped.lock().await.chain_height(),
+            genesis_height + 2
+        );
+
+        shutdown.cancel();
+        main_loop_handle.await.unwrap().unwrap();
+    }
+}
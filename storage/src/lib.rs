@@ -1,9 +1,20 @@
-use std::{path::Path, sync::Arc};
+use std::{
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
-use common::block::{Block, HashableBlockData};
+use common::{
+    HashType,
+    block::{Block, HashableBlockData},
+    transaction::{EncodedTransaction, NSSATransaction},
+};
 use error::DbError;
 use rocksdb::{
-    BoundColumnFamily, ColumnFamilyDescriptor, DBWithThreadMode, MultiThreaded, Options,
+    BoundColumnFamily, ColumnFamilyDescriptor, DBWithThreadMode, Direction, IteratorMode,
+    MultiThreaded, Options, WriteBatch,
 };
 
 pub mod error;
@@ -26,6 +37,8 @@ pub const DB_META_FIRST_BLOCK_IN_DB_KEY: &str = "first_block_in_db";
 pub const DB_META_LAST_BLOCK_IN_DB_KEY: &str = "last_block_in_db";
 /// Key base for storing metainformation which describe if first block has been set
 pub const DB_META_FIRST_BLOCK_SET_KEY: &str = "first_block_set";
+/// Key base for storing metainformation about id of last block in account transactions index
+pub const DB_META_ACCOUNT_TX_INDEX_LAST_BLOCK_KEY: &str = "account_tx_index_last_block";
 
 /// Key base for storing snapshot which describe block id
 pub const DB_SNAPSHOT_BLOCK_ID_KEY: &str = "block_id";
@@ -36,11 +49,20 @@ pub const CF_BLOCK_NAME: &str = "cf_block";
 pub const CF_META_NAME: &str = "cf_meta";
 /// Name of snapshot column family
 pub const CF_SNAPSHOT_NAME: &str = "cf_snapshot";
+/// Name of account to transactions index column family, keyed by
+/// `account_id || block_id || tx_index`
+pub const CF_ACCOUNT_TX_INDEX_NAME: &str = "cf_account_tx_index";
+/// Name of pending transactions column family, keyed by arrival sequence number
+pub const CF_MEMPOOL_NAME: &str = "cf_mempool";
+/// Name of pending transaction hash to arrival sequence number column family
+pub const CF_MEMPOOL_INDEX_NAME: &str = "cf_mempool_index";
 
 pub type DbResult<T> = Result<T, DbError>;
 
 pub struct RocksDBIO {
     pub db: DBWithThreadMode<MultiThreaded>,
+    /// Sequence number of the next pending transaction
+    next_mempool_seq: AtomicU64,
 }
 
 impl RocksDBIO {
@@ -51,6 +73,9 @@ impl RocksDBIO {
         let cfb = ColumnFamilyDescriptor::new(CF_BLOCK_NAME, cf_opts.clone());
         let cfmeta = ColumnFamilyDescriptor::new(CF_META_NAME, cf_opts.clone());
         let cfsnapshot = ColumnFamilyDescriptor::new(CF_SNAPSHOT_NAME, cf_opts.clone());
+        let cfaccindex = ColumnFamilyDescriptor::new(CF_ACCOUNT_TX_INDEX_NAME, cf_opts.clone());
+        let cfmempool = ColumnFamilyDescriptor::new(CF_MEMPOOL_NAME, cf_opts.clone());
+        let cfmempoolindex = ColumnFamilyDescriptor::new(CF_MEMPOOL_INDEX_NAME, cf_opts.clone());
 
         let mut db_opts = Options::default();
         db_opts.create_missing_column_families(true);
@@ -58,17 +83,29 @@ impl RocksDBIO {
         let db = DBWithThreadMode::<MultiThreaded>::open_cf_descriptors(
             &db_opts,
             path,
-            vec![cfb, cfmeta, cfsnapshot],
+            vec![
+                cfb,
+                cfmeta,
+                cfsnapshot,
+                cfaccindex,
+                cfmempool,
+                cfmempoolindex,
+            ],
         );
 
         let dbio = Self {
             // There is no point in handling this from runner code
             db: db.unwrap(),
+            next_mempool_seq: AtomicU64::new(0),
         };
+        let next_mempool_seq = dbio.get_last_mempool_seq()?.map_or(0, |seq| seq + 1);
+        dbio.next_mempool_seq
+            .store(next_mempool_seq, Ordering::Relaxed);
 
         let is_start_set = dbio.get_meta_is_first_block_set()?;
 
         if is_start_set {
+            dbio.backfill_account_tx_index()?;
             Ok(dbio)
         } else if let Some(block) = start_block {
             let block_id = block.header.block_id;
@@ -90,6 +127,9 @@ impl RocksDBIO {
         let _cfb = ColumnFamilyDescriptor::new(CF_BLOCK_NAME, cf_opts.clone());
         let _cfmeta = ColumnFamilyDescriptor::new(CF_META_NAME, cf_opts.clone());
         let _cfsnapshot = ColumnFamilyDescriptor::new(CF_SNAPSHOT_NAME, cf_opts.clone());
+        let _cfaccindex = ColumnFamilyDescriptor::new(CF_ACCOUNT_TX_INDEX_NAME, cf_opts.clone());
+        let _cfmempool = ColumnFamilyDescriptor::new(CF_MEMPOOL_NAME, cf_opts.clone());
+        let _cfmempoolindex = ColumnFamilyDescriptor::new(CF_MEMPOOL_INDEX_NAME, cf_opts.clone());
 
         let mut db_opts = Options::default();
         db_opts.create_missing_column_families(true);
@@ -98,6 +138,23 @@ impl RocksDBIO {
             .map_err(|rerr| DbError::rocksdb_cast_message(rerr, None))
     }
 
+    /// Flushes memtables of all column families to disk
+    pub fn flush(&self) -> DbResult<()> {
+        for cf in [
+            self.block_column(),
+            self.meta_column(),
+            self.snapshot_column(),
+            self.account_tx_index_column(),
+            self.mempool_column(),
+            self.mempool_index_column(),
+        ] {
+            self.db
+                .flush_cf(&cf)
+                .map_err(|rerr| DbError::rocksdb_cast_message(rerr, None))?;
+        }
+        Ok(())
+    }
+
     pub fn meta_column(&self) -> Arc<BoundColumnFamily<'_>> {
         self.db.cf_handle(CF_META_NAME).unwrap()
     }
@@ -110,6 +167,18 @@ impl RocksDBIO {
         self.db.cf_handle(CF_SNAPSHOT_NAME).unwrap()
     }
 
+    pub fn account_tx_index_column(&self) -> Arc<BoundColumnFamily<'_>> {
+        self.db.cf_handle(CF_ACCOUNT_TX_INDEX_NAME).unwrap()
+    }
+
+    pub fn mempool_column(&self) -> Arc<BoundColumnFamily<'_>> {
+        self.db.cf_handle(CF_MEMPOOL_NAME).unwrap()
+    }
+
+    pub fn mempool_index_column(&self) -> Arc<BoundColumnFamily<'_>> {
+        self.db.cf_handle(CF_MEMPOOL_INDEX_NAME).unwrap()
+    }
+
     pub fn get_meta_first_block_in_db(&self) -> DbResult<u64> {
         let cf_meta = self.meta_column();
         let res = self
@@ -249,33 +318,60 @@ impl RocksDBIO {
         Ok(())
     }
 
+    /// Stores block together with account transactions index entries of its transactions
     pub fn put_block(&self, block: Block, first: bool) -> DbResult<()> {
         let cf_block = self.block_column();
+        let cf_meta = self.meta_column();
+        let block_id = block.header.block_id;
 
-        if !first {
-            let last_curr_block = self.get_meta_last_block_in_db()?;
-
-            if block.header.block_id > last_curr_block {
-                self.put_meta_last_block_in_db(block.header.block_id)?;
+        let mut batch = WriteBatch::default();
+        if first || block_id > self.get_meta_last_block_in_db()? {
+            let block_id_bytes = borsh::to_vec(&block_id).map_err(|err| {
+                DbError::borsh_cast_message(
+                    err,
+                    Some("Failed to serialize last block id".to_string()),
+                )
+            })?;
+            if !first {
+                batch.put_cf(
+                    &cf_meta,
+                    borsh::to_vec(&DB_META_LAST_BLOCK_IN_DB_KEY).map_err(|err| {
+                        DbError::borsh_cast_message(
+                            err,
+                            Some("Failed to serialize DB_META_LAST_BLOCK_IN_DB_KEY".to_string()),
+                        )
+                    })?,
+                    &block_id_bytes,
+                );
             }
-        }
-
-        self.db
-            .put_cf(
-                &cf_block,
-                borsh::to_vec(&block.header.block_id).map_err(|err| {
-                    DbError::borsh_cast_message(
-                        err,
-                        Some("Failed to serialize block id".to_string()),
-                    )
-                })?,
-                borsh::to_vec(&HashableBlockData::from(block)).map_err(|err| {
+            batch.put_cf(
+                &cf_meta,
+                borsh::to_vec(&DB_META_ACCOUNT_TX_INDEX_LAST_BLOCK_KEY).map_err(|err| {
                     DbError::borsh_cast_message(
                         err,
-                        Some("Failed to serialize block data".to_string()),
+                        Some(
+                            "Failed to serialize DB_META_ACCOUNT_TX_INDEX_LAST_BLOCK_KEY"
+                                .to_string(),
+                        ),
                     )
                 })?,
-            )
+                &block_id_bytes,
+            );
+        }
+
+        self.batch_account_tx_index_entries(&mut batch, block_id, &block.body.transactions);
+        batch.put_cf(
+            &cf_block,
+            borsh::to_vec(&block_id).map_err(|err| {
+                DbError::borsh_cast_message(err, Some("Failed to serialize block id".to_string()))
+            })?,
+            borsh::to_vec(&HashableBlockData::from(block)).map_err(|err| {
+                DbError::borsh_cast_message(err, Some("Failed to serialize block data".to_string()))
+            })?,
+        );
+
+        self.db
+            .write(batch)
             .map_err(|rerr| DbError::rocksdb_cast_message(rerr, None))?;
         Ok(())
     }
@@ -339,4 +435,239 @@ impl RocksDBIO {
             ))
         }
     }
+
+    /// Returns `(block_id, tx_index)` pairs of transactions in which `account_id` appears, from
+    /// block `from_block_id` on, in chain order
+    pub fn get_account_tx_index(
+        &self,
+        account_id: [u8; 32],
+        from_block_id: u64,
+    ) -> DbResult<Vec<(u64, u64)>> {
+        let cf_acc_index = self.account_tx_index_column();
+        let start_key = account_tx_index_key(account_id, from_block_id, 0);
+        let mut entries = vec![];
+
+        for entry in self.db.iterator_cf(
+            &cf_acc_index,
+            IteratorMode::From(&start_key, Direction::Forward),
+        ) {
+            let (key, _) = entry.map_err(|rerr| DbError::rocksdb_cast_message(rerr, None))?;
+            let Some((entry_account_id, block_id, tx_index)) = decode_account_tx_index_key(&key)
+            else {
+                continue;
+            };
+            if entry_account_id != account_id {
+                break;
+            }
+            entries.push((block_id, tx_index));
+        }
+
+        Ok(entries)
+    }
+
+    /// Adds index entries of every public account touched by `transactions` of block `block_id`
+    fn batch_account_tx_index_entries(
+        &self,
+        batch: &mut WriteBatch,
+        block_id: u64,
+        transactions: &[EncodedTransaction],
+    ) {
+        let cf_acc_index = self.account_tx_index_column();
+
+        for (tx_index, transaction) in transactions.iter().enumerate() {
+            // Undecodable transactions touch no accounts
+            let Ok(transaction) = NSSATransaction::try_from(transaction) else {
+                continue;
+            };
+            for account_id in transaction.public_account_ids() {
+                batch.put_cf(
+                    &cf_acc_index,
+                    account_tx_index_key(*account_id.value(), block_id, tx_index as u64),
+                    b"",
+                );
+            }
+        }
+    }
+
+    fn get_meta_account_tx_index_last_block(&self) -> DbResult<Option<u64>> {
+        let cf_meta = self.meta_column();
+        let res = self
+            .db
+            .get_cf(
+                &cf_meta,
+                borsh::to_vec(&DB_META_ACCOUNT_TX_INDEX_LAST_BLOCK_KEY).map_err(|err| {
+                    DbError::borsh_cast_message(
+                        err,
+                        Some(
+                            "Failed to serialize DB_META_ACCOUNT_TX_INDEX_LAST_BLOCK_KEY"
+                                .to_string(),
+                        ),
+                    )
+                })?,
+            )
+            .map_err(|rerr| DbError::rocksdb_cast_message(rerr, None))?;
+
+        res.map(|data| {
+            borsh::from_slice::<u64>(&data).map_err(|err| {
+                DbError::borsh_cast_message(
+                    err,
+                    Some("Failed to deserialize last indexed block".to_string()),
+                )
+            })
+        })
+        .transpose()
+    }
+
+    /// Indexes stored blocks, which are not in account transactions index yet, e.g. blocks
+    /// written before the index existed
+    fn backfill_account_tx_index(&self) -> DbResult<()> {
+        let last_block = self.get_meta_last_block_in_db()?;
+        let first_unindexed_block = match self.get_meta_account_tx_index_last_block()? {
+            Some(last_indexed_block) => last_indexed_block + 1,
+            None => self.get_meta_first_block_in_db()?,
+        };
+
+        for block_id in first_unindexed_block..=last_block {
+            let block = self.get_block(block_id)?;
+
+            let mut batch = WriteBatch::default();
+            self.batch_account_tx_index_entries(&mut batch, block_id, &block.transactions);
+            batch.put_cf(
+                &self.meta_column(),
+                borsh::to_vec(&DB_META_ACCOUNT_TX_INDEX_LAST_BLOCK_KEY).map_err(|err| {
+                    DbError::borsh_cast_message(
+                        err,
+                        Some(
+                            "Failed to serialize DB_META_ACCOUNT_TX_INDEX_LAST_BLOCK_KEY"
+                                .to_string(),
+                        ),
+                    )
+                })?,
+                borsh::to_vec(&block_id).map_err(|err| {
+                    DbError::borsh_cast_message(
+                        err,
+                        Some("Failed to serialize last indexed block id".to_string()),
+                    )
+                })?,
+            );
+            self.db
+                .write(batch)
+                .map_err(|rerr| DbError::rocksdb_cast_message(rerr, None))?;
+        }
+
+        Ok(())
+    }
+
+    /// Stores pending transaction under the next arrival sequence number, so transactions are
+    /// restored in the order they were received. Transactions already stored are skipped.
+    pub fn put_mempool_transaction(&self, tx: &EncodedTransaction) -> DbResult<()> {
+        let tx_hash = tx.hash();
+        if self.get_mempool_seq(tx_hash)?.is_some() {
+            return Ok(());
+        }
+
+        let seq = self.next_mempool_seq.fetch_add(1, Ordering::Relaxed);
+        let cf_mempool = self.mempool_column();
+        let cf_mempool_index = self.mempool_index_column();
+
+        let mut batch = WriteBatch::default();
+        batch.put_cf(
+            &cf_mempool,
+            seq.to_be_bytes(),
+            borsh::to_vec(tx).map_err(|err| {
+                DbError::borsh_cast_message(
+                    err,
+                    Some("Failed to serialize pending transaction".to_string()),
+                )
+            })?,
+        );
+        batch.put_cf(&cf_mempool_index, tx_hash, seq.to_be_bytes());
+        self.db
+            .write(batch)
+            .map_err(|rerr| DbError::rocksdb_cast_message(rerr, None))?;
+        Ok(())
+    }
+
+    pub fn delete_mempool_transaction(&self, tx_hash: HashType) -> DbResult<()> {
+        let Some(seq) = self.get_mempool_seq(tx_hash)? else {
+            return Ok(());
+        };
+
+        let cf_mempool = self.mempool_column();
+        let cf_mempool_index = self.mempool_index_column();
+
+        let mut batch = WriteBatch::default();
+        batch.delete_cf(&cf_mempool, seq.to_be_bytes());
+        batch.delete_cf(&cf_mempool_index, tx_hash);
+        self.db
+            .write(batch)
+            .map_err(|rerr| DbError::rocksdb_cast_message(rerr, None))?;
+        Ok(())
+    }
+
+    /// Returns all stored pending transactions in arrival order
+    pub fn get_mempool_transactions(&self) -> DbResult<Vec<EncodedTransaction>> {
+        let cf_mempool = self.mempool_column();
+        self.db
+            .iterator_cf(&cf_mempool, IteratorMode::Start)
+            .map(|entry| {
+                let (_, data) = entry.map_err(|rerr| DbError::rocksdb_cast_message(rerr, None))?;
+                borsh::from_slice::<EncodedTransaction>(&data).map_err(|err| {
+                    DbError::borsh_cast_message(
+                        err,
+                        Some("Failed to deserialize pending transaction".to_string()),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    fn get_mempool_seq(&self, tx_hash: HashType) -> DbResult<Option<u64>> {
+        let cf_mempool_index = self.mempool_index_column();
+        let res = self
+            .db
+            .get_cf(&cf_mempool_index, tx_hash)
+            .map_err(|rerr| DbError::rocksdb_cast_message(rerr, None))?;
+
+        res.map(|data| decode_mempool_seq(&data)).transpose()
+    }
+
+    fn get_last_mempool_seq(&self) -> DbResult<Option<u64>> {
+        let cf_mempool = self.mempool_column();
+        let Some(entry) = self.db.iterator_cf(&cf_mempool, IteratorMode::End).next() else {
+            return Ok(None);
+        };
+        let (key, _) = entry.map_err(|rerr| DbError::rocksdb_cast_message(rerr, None))?;
+
+        decode_mempool_seq(&key).map(Some)
+    }
+}
+
+/// Big-endian ids keep entries of one account sorted in chain order
+fn account_tx_index_key(account_id: [u8; 32], block_id: u64, tx_index: u64) -> Vec<u8> {
+    [
+        account_id.as_slice(),
+        &block_id.to_be_bytes(),
+        &tx_index.to_be_bytes(),
+    ]
+    .concat()
+}
+
+/// Splits index key into `(account_id, block_id, tx_index)`, `None` for keys of other layout
+fn decode_account_tx_index_key(key: &[u8]) -> Option<([u8; 32], u64, u64)> {
+    let (account_id, ids) = key.split_first_chunk::<32>()?;
+    let (block_id, tx_index) = ids.split_first_chunk::<8>()?;
+    let tx_index: [u8; 8] = tx_index.try_into().ok()?;
+    Some((
+        *account_id,
+        u64::from_be_bytes(*block_id),
+        u64::from_be_bytes(tx_index),
+    ))
+}
+
+fn decode_mempool_seq(bytes: &[u8]) -> DbResult<u64> {
+    let bytes = bytes.try_into().map_err(|_| {
+        DbError::db_interaction_error("Invalid pending transaction sequence number".to_string())
+    })?;
+    Ok(u64::from_be_bytes(bytes))
 }
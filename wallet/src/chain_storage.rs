@@ -1,6 +1,10 @@
-use std::collections::{HashMap, hash_map::Entry};
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    path::Path,
+};
 
 use anyhow::Result;
+use common::transaction::TxKind;
 use key_protocol::{
     key_management::{
         key_tree::{KeyTreePrivate, KeyTreePublic, chain_index::ChainIndex},
@@ -9,12 +13,36 @@ use key_protocol::{
     key_protocol_core::NSSAUserData,
 };
 use nssa::program::Program;
+use nssa_core::Commitment;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{InitialAccountData, PersistentAccountData, PersistentStorage, WalletConfig};
 
-use crate::config::{InitialAccountData, PersistentAccountData, WalletConfig};
+/// Transaction touching account of the wallet, observed during sync
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxRecord {
+    /// Hex encoded transaction hash
+    pub tx_hash: String,
+    pub kind: TxKind,
+    /// Account of the wallet touched by transaction
+    pub account_id: nssa::AccountId,
+    /// Amount of native tokens moved, known only for public native token transfers
+    pub amount: Option<u128>,
+    /// Other side of native token transfer
+    pub counterparty: Option<nssa::AccountId>,
+    pub block_height: u64,
+    pub timestamp: u64,
+}
 
 pub struct WalletChainStore {
     pub user_data: NSSAUserData,
     pub wallet_config: WalletConfig,
+    /// Block ids at which private account commitments were observed during sync
+    pub private_commitment_blocks: HashMap<Commitment, u64>,
+    /// Public accounts, which balances are monitored without owning their keys
+    pub watched_account_ids: Vec<nssa::AccountId>,
+    /// Transactions touching accounts of the wallet, in order of inclusion
+    pub tx_history: Vec<TxRecord>,
 }
 
 impl WalletChainStore {
@@ -84,6 +112,9 @@ impl WalletChainStore {
                 private_tree,
             )?,
             wallet_config: config,
+            private_commitment_blocks: HashMap::new(),
+            watched_account_ids: vec![],
+            tx_history: vec![],
         })
     }
 
@@ -119,9 +150,58 @@ impl WalletChainStore {
                 private_tree,
             )?,
             wallet_config: config,
+            private_commitment_blocks: HashMap::new(),
+            watched_account_ids: vec![],
+            tx_history: vec![],
         })
     }
 
+    /// Rewrites v0 storage at `old_path` into v1 storage at `new_path`.
+    ///
+    /// Returns number of migrated accounts.
+    pub fn migrate_v0_to_v1(old_path: &Path, new_path: &Path) -> Result<usize> {
+        let storage_content = std::fs::read(old_path)?;
+        let mut storage: PersistentStorage = serde_json::from_slice(&storage_content)?;
+
+        if storage.version != 0 {
+            anyhow::bail!("Expected v0 storage, found v{}", storage.version);
+        }
+
+        for pers_acc_data in &storage.accounts {
+            if let PersistentAccountData::Preconfigured(acc_data) = pers_acc_data {
+                let account_id = match acc_data {
+                    InitialAccountData::Public(data) => &data.account_id,
+                    InitialAccountData::Private(data) => &data.account_id,
+                };
+                account_id.parse::<nssa::AccountId>()?;
+            }
+        }
+
+        storage.version = 1;
+        std::fs::write(new_path, serde_json::to_vec_pretty(&storage)?)?;
+
+        Ok(storage.accounts.len())
+    }
+
+    /// Rewrites v1 storage at `old_path` into v2 storage at `new_path`.
+    ///
+    /// v2 stores block ids of observed private commitments. They are unknown for commitments
+    /// synced before, so ages of such accounts stay unknown until their next state is synced.
+    pub fn migrate_v1_to_v2(old_path: &Path, new_path: &Path) -> Result<()> {
+        let storage_content = std::fs::read(old_path)?;
+        let mut storage: PersistentStorage = serde_json::from_slice(&storage_content)?;
+
+        if storage.version != 1 {
+            anyhow::bail!("Expected v1 storage, found v{}", storage.version);
+        }
+
+        storage.version = 2;
+        storage.private_commitment_blocks = vec![];
+        std::fs::write(new_path, serde_json::to_vec_pretty(&storage)?)?;
+
+        Ok(())
+    }
+
     pub fn insert_private_account_data(
         &mut self,
         account_id: nssa::AccountId,
@@ -149,6 +229,38 @@ impl WalletChainStore {
                 });
         }
     }
+
+    pub fn insert_private_commitment_block(&mut self, commitment: Commitment, block_id: u64) {
+        self.private_commitment_blocks.insert(commitment, block_id);
+    }
+
+    /// Returns how many blocks ago `commitment` was observed, relative to `current_block_id`
+    pub fn get_commitment_age(
+        &self,
+        commitment: &Commitment,
+        current_block_id: u64,
+    ) -> Option<u64> {
+        self.private_commitment_blocks
+            .get(commitment)
+            .map(|block_id| current_block_id.saturating_sub(*block_id))
+    }
+
+    /// Returns page of transactions touching `account_id`, most recent first
+    pub fn get_tx_history(
+        &self,
+        account_id: nssa::AccountId,
+        limit: usize,
+        offset: usize,
+    ) -> Vec<TxRecord> {
+        self.tx_history
+            .iter()
+            .rev()
+            .filter(|record| record.account_id == account_id)
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -291,4 +403,167 @@ mod tests {
 
         let _ = WalletChainStore::new(config.clone(), accs).unwrap();
     }
+
+    #[test]
+    fn test_get_commitment_age() {
+        let config = create_sample_wallet_config();
+        let accs = create_sample_persistent_accounts();
+        let mut store = WalletChainStore::new(config, accs).unwrap();
+
+        let private_data = ChildKeysPrivate::root([47; 64]);
+        let commitment = Commitment::new(
+            &private_data.value.0.nullifer_public_key,
+            &private_data.value.1,
+        );
+
+        assert_eq!(store.get_commitment_age(&commitment, 10), None);
+
+        store.insert_private_commitment_block(commitment.clone(), 10);
+
+        assert_eq!(store.get_commitment_age(&commitment, 10), Some(0));
+        assert_eq!(store.get_commitment_age(&commitment, 15), Some(5));
+    }
+
+    #[test]
+    fn test_get_tx_history_pagination() {
+        let config = create_sample_wallet_config();
+        let accs = create_sample_persistent_accounts();
+        let mut store = WalletChainStore::new(config, accs).unwrap();
+        let account_id = nssa::AccountId::new([1; 32]);
+        let other_account_id = nssa::AccountId::new([2; 32]);
+
+        for block_height in 1..=5 {
+            for account_id in [account_id, other_account_id] {
+                store.tx_history.push(TxRecord {
+                    tx_hash: hex::encode([block_height as u8; 32]),
+                    kind: TxKind::Public,
+                    account_id,
+                    amount: Some(block_height.into()),
+                    counterparty: None,
+                    block_height,
+                    timestamp: block_height * 100,
+                });
+            }
+        }
+
+        let block_heights = |records: Vec<TxRecord>| {
+            records
+                .into_iter()
+                .map(|record| {
+                    assert_eq!(record.account_id, account_id);
+                    record.block_height
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            block_heights(store.get_tx_history(account_id, 2, 0)),
+            vec![5, 4]
+        );
+        assert_eq!(
+            block_heights(store.get_tx_history(account_id, 2, 2)),
+            vec![3, 2]
+        );
+        assert_eq!(
+            block_heights(store.get_tx_history(account_id, 2, 4)),
+            vec![1]
+        );
+        assert!(store.get_tx_history(account_id, 2, 5).is_empty());
+        assert!(
+            store
+                .get_tx_history(nssa::AccountId::new([3; 32]), 10, 0)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_migrate_v0_to_v1() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let old_path = temp_dir.path().join("storage_v0.json");
+        let new_path = temp_dir.path().join("storage_v1.json");
+
+        let mut accounts = create_sample_persistent_accounts();
+        accounts.extend(
+            create_initial_accounts()
+                .into_iter()
+                .map(PersistentAccountData::from),
+        );
+        let v0_storage = serde_json::json!({
+            "accounts": accounts,
+            "last_synced_block": 5,
+        });
+        std::fs::write(&old_path, serde_json::to_vec(&v0_storage).unwrap()).unwrap();
+
+        let migrated = WalletChainStore::migrate_v0_to_v1(&old_path, &new_path).unwrap();
+        assert_eq!(migrated, 4);
+
+        let v1_storage: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&new_path).unwrap()).unwrap();
+        assert_eq!(v1_storage["version"], 1);
+        assert_eq!(v1_storage["last_synced_block"], 5);
+
+        let v1_storage: PersistentStorage = serde_json::from_value(v1_storage).unwrap();
+        assert_eq!(
+            v1_storage
+                .accounts
+                .iter()
+                .map(PersistentAccountData::account_id)
+                .collect::<Vec<_>>(),
+            accounts
+                .iter()
+                .map(PersistentAccountData::account_id)
+                .collect::<Vec<_>>(),
+        );
+
+        let config = create_sample_wallet_config();
+        let _ = WalletChainStore::new(config, v1_storage.accounts).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_v0_to_v1_rejects_v1_storage() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let old_path = temp_dir.path().join("storage_v1.json");
+        let new_path = temp_dir.path().join("storage_v1_migrated.json");
+
+        let v1_storage = PersistentStorage {
+            version: 1,
+            accounts: create_sample_persistent_accounts(),
+            last_synced_block: 0,
+            watched_account_ids: vec![],
+            tx_history: vec![],
+            private_commitment_blocks: vec![],
+        };
+        std::fs::write(&old_path, serde_json::to_vec(&v1_storage).unwrap()).unwrap();
+
+        assert!(WalletChainStore::migrate_v0_to_v1(&old_path, &new_path).is_err());
+        assert!(!new_path.exists());
+    }
+
+    #[test]
+    fn test_migrate_v1_to_v2() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let old_path = temp_dir.path().join("storage_v1.json");
+        let new_path = temp_dir.path().join("storage_v2.json");
+
+        let v1_storage = serde_json::json!({
+            "version": 1,
+            "accounts": create_sample_persistent_accounts(),
+            "last_synced_block": 5,
+        });
+        std::fs::write(&old_path, serde_json::to_vec(&v1_storage).unwrap()).unwrap();
+
+        WalletChainStore::migrate_v1_to_v2(&old_path, &new_path).unwrap();
+
+        let v2_storage: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&new_path).unwrap()).unwrap();
+        assert_eq!(v2_storage["version"], 2);
+        assert_eq!(v2_storage["last_synced_block"], 5);
+        assert_eq!(
+            v2_storage["private_commitment_blocks"],
+            serde_json::json!([])
+        );
+
+        // Storage of other versions is not touched
+        assert!(WalletChainStore::migrate_v1_to_v2(&new_path, &old_path).is_err());
+    }
 }
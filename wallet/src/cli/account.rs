@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
 use base58::ToBase58;
 use clap::Subcommand;
@@ -17,6 +19,7 @@ const TOKEN_DEFINITION_DATA_SIZE: usize = 23;
 
 const TOKEN_HOLDING_TYPE: u8 = 1;
 const TOKEN_HOLDING_DATA_SIZE: usize = 49;
+const TOKEN_ALLOWANCE_ENTRY_SIZE: usize = 48;
 
 struct TokenDefinition {
     #[allow(unused)]
@@ -52,12 +55,17 @@ impl TokenDefinition {
 
 impl TokenHolding {
     fn parse(data: &[u8]) -> Option<Self> {
-        if data.len() != TOKEN_HOLDING_DATA_SIZE || data[0] != TOKEN_HOLDING_TYPE {
+        // Allowance entries may follow the fixed-size holding fields
+        if data.len() < TOKEN_HOLDING_DATA_SIZE
+            || !(data.len() - TOKEN_HOLDING_DATA_SIZE).is_multiple_of(TOKEN_ALLOWANCE_ENTRY_SIZE)
+            || data[0] != TOKEN_HOLDING_TYPE
+        {
             None
         } else {
             let account_type = data[0];
             let definition_id = AccountId::new(data[1..33].try_into().unwrap());
-            let balance = u128::from_le_bytes(data[33..].try_into().unwrap());
+            let balance =
+                u128::from_le_bytes(data[33..TOKEN_HOLDING_DATA_SIZE].try_into().unwrap());
             Some(Self {
                 definition_id,
                 balance,
@@ -86,7 +94,48 @@ pub enum AccountSubcommand {
     SyncPrivate {},
     /// List all accounts owned by the wallet
     #[command(visible_alias = "ls")]
-    List {},
+    List {
+        /// Flag to show table of accounts with their balances
+        ///
+        /// Public balances are fetched from sequencer, private ones are taken from local storage.
+        #[arg(short, long)]
+        balances: bool,
+    },
+    /// Show total value of all accounts owned by the wallet
+    Portfolio {},
+    /// Watch balance of public account not owned by the wallet
+    Watch {
+        /// Valid 32 byte base58 string with privacy prefix
+        #[arg(short, long)]
+        account_id: String,
+    },
+    /// Show transactions touching account, most recent first
+    History {
+        /// Valid 32 byte base58 string with privacy prefix
+        #[arg(short, long)]
+        account_id: String,
+        /// Maximum number of transactions to show
+        #[arg(short, long, default_value_t = 10)]
+        limit: usize,
+        /// Number of most recent transactions to skip
+        #[arg(short, long, default_value_t = 0)]
+        offset: usize,
+    },
+    /// Export signing key of public account, encrypted with password
+    Export {
+        /// Valid 32 byte base58 string with privacy prefix
+        #[arg(short, long)]
+        account_id: String,
+        /// File to write encrypted key to
+        #[arg(short, long)]
+        output_file: PathBuf,
+    },
+    /// Import public account from file produced by `export`
+    Import {
+        /// File with encrypted key
+        #[arg(short, long)]
+        input_file: PathBuf,
+    },
 }
 
 /// Represents generic register CLI subcommand
@@ -275,6 +324,13 @@ impl WalletSubcommand for AccountSubcommand {
 
                 println!("{}", acc_view);
 
+                if addr_kind == AccountPrivacyKind::Private {
+                    match wallet_core.get_private_account_age(&account_id) {
+                        Some(age) => println!("Account state was created {age} blocks ago"),
+                        None => println!("Account state was not observed during sync"),
+                    }
+                }
+
                 Ok(SubcommandReturnValue::Empty)
             }
             AccountSubcommand::New(new_subcommand) => {
@@ -305,7 +361,40 @@ impl WalletSubcommand for AccountSubcommand {
 
                 Ok(SubcommandReturnValue::SyncedToBlock(curr_last_block))
             }
-            AccountSubcommand::List {} => {
+            AccountSubcommand::List { balances: true } => {
+                println!("{:<8}{:<48}{:>40}", "Kind", "Account id", "Balance");
+
+                for (account_id, kind) in wallet_core.list_accounts() {
+                    let (kind, balance) = match kind {
+                        AccountPrivacyKind::Public => (
+                            "Public",
+                            Some(wallet_core.get_account_balance(account_id).await?),
+                        ),
+                        AccountPrivacyKind::Private => (
+                            "Private",
+                            wallet_core
+                                .get_account_private(&account_id)
+                                .map(|account| account.balance),
+                        ),
+                    };
+                    let balance = balance.map_or("-".to_string(), |balance| balance.to_string());
+
+                    println!("{kind:<8}{:<48}{balance:>40}", account_id.to_string());
+                }
+
+                for account_id in &wallet_core.storage.watched_account_ids {
+                    let balance = wallet_core.get_account_balance(*account_id).await?;
+
+                    println!(
+                        "{:<8}{:<48}{balance:>40}",
+                        "Watched",
+                        account_id.to_string()
+                    );
+                }
+
+                Ok(SubcommandReturnValue::Empty)
+            }
+            AccountSubcommand::List { balances: false } => {
                 let user_data = &wallet_core.storage.user_data;
                 let accounts = user_data
                     .default_pub_account_signing_keys
@@ -336,6 +425,115 @@ impl WalletSubcommand for AccountSubcommand {
                 println!("{accounts}");
                 Ok(SubcommandReturnValue::Empty)
             }
+            AccountSubcommand::Watch { account_id } => {
+                let (account_id, addr_kind) = parse_addr_with_privacy_prefix(&account_id)?;
+
+                if addr_kind != AccountPrivacyKind::Public {
+                    anyhow::bail!("Only public accounts can be watched");
+                }
+
+                let account_id = account_id.parse()?;
+
+                if wallet_core.watch_account(account_id) {
+                    println!("Watching account Public/{account_id}");
+
+                    let path = wallet_core.store_persistent_data().await?;
+
+                    println!("Stored persistent accounts at {path:#?}");
+                } else {
+                    println!("Account Public/{account_id} is already watched");
+                }
+
+                Ok(SubcommandReturnValue::Empty)
+            }
+            AccountSubcommand::History {
+                account_id,
+                limit,
+                offset,
+            } => {
+                let (account_id, _) = parse_addr_with_privacy_prefix(&account_id)?;
+                let account_id = account_id.parse()?;
+
+                println!(
+                    "{:<8}{:<14}{:<20}{:>40}  {:<48}Tx hash",
+                    "Block", "Timestamp", "Kind", "Amount", "Counterparty"
+                );
+
+                for record in wallet_core
+                    .storage
+                    .get_tx_history(account_id, limit, offset)
+                {
+                    let amount = record
+                        .amount
+                        .map_or("-".to_string(), |amount| amount.to_string());
+                    let counterparty = record
+                        .counterparty
+                        .map_or("-".to_string(), |counterparty| counterparty.to_string());
+
+                    println!(
+                        "{:<8}{:<14}{:<20}{amount:>40}  {counterparty:<48}{}",
+                        record.block_height,
+                        record.timestamp,
+                        format!("{:?}", record.kind),
+                        record.tx_hash
+                    );
+                }
+
+                Ok(SubcommandReturnValue::Empty)
+            }
+            AccountSubcommand::Export {
+                account_id,
+                output_file,
+            } => {
+                let (account_id, addr_kind) = parse_addr_with_privacy_prefix(&account_id)?;
+
+                if addr_kind != AccountPrivacyKind::Public {
+                    anyhow::bail!("Only public accounts can be exported");
+                }
+
+                let account_id = account_id.parse()?;
+                let password = rpassword::prompt_password("Export password: ")?;
+                if rpassword::prompt_password("Repeat password: ")? != password {
+                    anyhow::bail!("Passwords do not match");
+                }
+
+                let exported = wallet_core.export_account(account_id, &password)?;
+                tokio::fs::write(&output_file, exported).await?;
+
+                println!("Exported account Public/{account_id} to {output_file:#?}");
+
+                Ok(SubcommandReturnValue::Empty)
+            }
+            AccountSubcommand::Import { input_file } => {
+                let exported = tokio::fs::read_to_string(&input_file).await?;
+                let password = rpassword::prompt_password("Export password: ")?;
+
+                let account_id = wallet_core.import_account(&exported, &password)?;
+
+                println!("Imported account Public/{account_id}");
+
+                let path = wallet_core.store_persistent_data().await?;
+
+                println!("Stored persistent accounts at {path:#?}");
+
+                Ok(SubcommandReturnValue::RegisterAccount { account_id })
+            }
+            AccountSubcommand::Portfolio {} => {
+                let summary = wallet_core.compute_portfolio_value().await?;
+
+                println!("{:<24}{:>40}", "Public balance", summary.public_balance);
+                println!(
+                    "{:<24}{:>40}",
+                    "Private accounts", summary.private_account_count
+                );
+                println!(
+                    "{:<24}{:>40}",
+                    "Private balance", summary.private_total_value
+                );
+                println!("{:<24}{:>40}", "Total", summary.total_value);
+
+                Ok(SubcommandReturnValue::Empty)
+            }
         }
     }
 }
@@ -1,5 +1,6 @@
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use clap::Subcommand;
+use common::transaction::EncodedTransaction;
 
 use crate::{
     WalletCore,
@@ -22,6 +23,14 @@ pub enum ChainSubcommand {
         #[arg(short, long)]
         hash: String,
     },
+    /// Get fee schedule of public transactions from sequencer
+    FeeParams {},
+    /// Get minimal fee sequencer requires for transaction
+    EstimateFee {
+        /// tx_json - transaction as JSON, e.g. `{"tx_kind":"Public","encoded_transaction_data":[..]}`
+        #[arg(long)]
+        tx_json: String,
+    },
 }
 
 impl WalletSubcommand for ChainSubcommand {
@@ -48,6 +57,26 @@ impl WalletSubcommand for ChainSubcommand {
 
                 println!("Last block id is {:#?}", tx_res.transaction);
             }
+            ChainSubcommand::FeeParams {} => {
+                let fee_params = wallet_core
+                    .sequencer_client
+                    .get_fee_params()
+                    .await?
+                    .fee_params;
+
+                println!(
+                    "Base fee is {}, fee per started KiB of transaction is {}",
+                    fee_params.base_fee, fee_params.fee_per_kb
+                );
+            }
+            ChainSubcommand::EstimateFee { tx_json } => {
+                let tx: EncodedTransaction =
+                    serde_json::from_str(&tx_json).context("Failed to parse transaction JSON")?;
+
+                let fee = wallet_core.estimate_fee(&tx).await?;
+
+                println!("Estimated fee is {fee}");
+            }
         }
         Ok(SubcommandReturnValue::Empty)
     }
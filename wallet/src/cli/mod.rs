@@ -13,7 +13,10 @@ use crate::{
             token::TokenProgramAgnosticSubcommand,
         },
     },
-    helperfunctions::fetch_config,
+    helperfunctions::{
+        AccountPrivacyKind, check_home_writable, fetch_config, get_home,
+        parse_addr_with_privacy_prefix, parse_program_id_hex,
+    },
 };
 
 pub mod account;
@@ -51,6 +54,20 @@ pub enum Command {
     /// Command to setup config, get and set config fields
     #[command(subcommand)]
     Config(ConfigSubcommand),
+    /// Check config fields, wallet home directory and sequencer reachability
+    ValidateConfig {},
+    /// Send public transaction with raw instruction data to arbitrary program
+    SendInstruction {
+        /// Program id as hex string of its 32 bytes
+        #[arg(long)]
+        program_id_hex: String,
+        /// Accounts of transaction in order, valid 32 byte base58 strings with `Public/` prefix
+        #[arg(long)]
+        account_ids: Vec<String>,
+        /// Serialized instruction data as hex string
+        #[arg(long)]
+        instruction_hex: String,
+    },
 }
 
 /// Represents overarching CLI command for a wallet with setup included
@@ -60,7 +77,8 @@ pub enum OverCommand {
     /// Represents CLI command for a wallet
     #[command(subcommand)]
     Command(Command),
-    /// Setup of a storage. Initializes rots for public and private trees from `password`.
+    /// Setup of a storage. Initializes rots for public and private trees from `password`, which
+    /// must be at least `MIN_SETUP_PASSWORD_LENGTH` characters long.
     Setup {
         #[arg(short, long)]
         password: String,
@@ -95,6 +113,18 @@ pub enum SubcommandReturnValue {
 
 pub async fn execute_subcommand(command: Command) -> Result<SubcommandReturnValue> {
     let wallet_config = fetch_config().await?;
+
+    // Checked before wallet start, so broken storage does not hide config errors
+    if let Command::ValidateConfig {} = command {
+        wallet_config.validate()?;
+        check_home_writable(&get_home()?)?;
+        wallet_config.check_sequencer_reachable().await?;
+
+        println!("✅Config is valid");
+
+        return Ok(SubcommandReturnValue::Empty);
+    }
+
     let mut wallet_core = WalletCore::start_from_config_update_chain(wallet_config).await?;
 
     let subcommand_ret = match command {
@@ -154,6 +184,37 @@ pub async fn execute_subcommand(command: Command) -> Result<SubcommandReturnValu
                 .handle_subcommand(&mut wallet_core)
                 .await?
         }
+        Command::ValidateConfig {} => unreachable!("Config is validated before wallet start"),
+        Command::SendInstruction {
+            program_id_hex,
+            account_ids,
+            instruction_hex,
+        } => {
+            let program_id = parse_program_id_hex(&program_id_hex)?;
+            let account_ids = account_ids
+                .iter()
+                .map(|account_id| {
+                    let (account_id, addr_kind) = parse_addr_with_privacy_prefix(account_id)?;
+                    if addr_kind != AccountPrivacyKind::Public {
+                        anyhow::bail!("Only public accounts are supported");
+                    }
+                    Ok(account_id.parse()?)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let instruction_data = hex::decode(instruction_hex)?;
+
+            let res = wallet_core
+                .sign_and_submit_arbitrary_instruction(program_id, account_ids, instruction_data)
+                .await?;
+
+            println!("Results of tx send are {res:#?}");
+
+            let tx = wallet_core.poll_transaction(res.tx_hash).await?;
+
+            println!("Transaction data is {tx:?}");
+
+            SubcommandReturnValue::Empty
+        }
     };
 
     Ok(subcommand_ret)
@@ -178,7 +239,22 @@ pub async fn execute_continuous_run() -> Result<()> {
     }
 }
 
+/// Minimal length of the setup password.
+///
+/// Wallet mnemonic is fixed, so the password is the only secret the key trees are derived from.
+pub const MIN_SETUP_PASSWORD_LENGTH: usize = 12;
+
+fn check_setup_password(password: &str) -> Result<()> {
+    anyhow::ensure!(
+        password.chars().count() >= MIN_SETUP_PASSWORD_LENGTH,
+        "Password must be at least {MIN_SETUP_PASSWORD_LENGTH} characters long"
+    );
+    Ok(())
+}
+
 pub async fn execute_setup(password: String) -> Result<()> {
+    check_setup_password(&password)?;
+
     let config = fetch_config().await?;
     let wallet_core = WalletCore::start_from_config_new_storage(config.clone(), password).await?;
 
@@ -186,3 +262,15 @@ pub async fn execute_setup(password: String) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_setup_password() {
+        assert!(check_setup_password("short_pass").is_err());
+        assert!(check_setup_password("long_enough_pass").is_ok());
+        assert!(check_setup_password(&"a".repeat(MIN_SETUP_PASSWORD_LENGTH)).is_ok());
+    }
+}
@@ -1,10 +1,20 @@
+use std::time::Duration;
+
+use anyhow::{Context as _, Result};
 use key_protocol::key_management::{
     KeyChain,
     key_tree::{
         chain_index::ChainIndex, keys_private::ChildKeysPrivate, keys_public::ChildKeysPublic,
     },
 };
+use nssa_core::Commitment;
 use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::chain_storage::TxRecord;
+
+/// Timeout of connection attempt in sequencer reachability check
+pub const SEQUENCER_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InitialAccountDataPublic {
@@ -54,10 +64,25 @@ pub enum PersistentAccountData {
     Preconfigured(InitialAccountData),
 }
 
+/// Current version of persistent storage format
+pub const PERSISTENT_STORAGE_VERSION: u32 = 2;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersistentStorage {
+    /// Storage format version, absent in v0 storage
+    #[serde(default)]
+    pub version: u32,
     pub accounts: Vec<PersistentAccountData>,
     pub last_synced_block: u64,
+    /// Public accounts watched without owning their keys
+    #[serde(default)]
+    pub watched_account_ids: Vec<nssa::AccountId>,
+    /// Transactions touching accounts of the wallet
+    #[serde(default)]
+    pub tx_history: Vec<TxRecord>,
+    /// Block ids at which private account commitments were observed during sync, added in v2
+    #[serde(default)]
+    pub private_commitment_blocks: Vec<(Commitment, u64)>,
 }
 
 impl InitialAccountData {
@@ -145,6 +170,51 @@ pub struct WalletConfig {
     pub initial_accounts: Vec<InitialAccountData>,
 }
 
+impl WalletConfig {
+    /// Checks, that config fields are within acceptable ranges
+    pub fn validate(&self) -> Result<()> {
+        let sequencer_url = self.sequencer_url()?;
+        if !matches!(sequencer_url.scheme(), "http" | "https") {
+            anyhow::bail!(
+                "Sequencer address {} must use http or https scheme",
+                self.sequencer_addr
+            );
+        }
+
+        if self.seq_tx_poll_max_blocks == 0 {
+            anyhow::bail!("seq_tx_poll_max_blocks must be positive");
+        }
+        if self.seq_block_poll_max_amount == 0 {
+            anyhow::bail!("seq_block_poll_max_amount must be positive");
+        }
+
+        Ok(())
+    }
+
+    /// Checks, that sequencer accepts TCP connections within `SEQUENCER_CONNECT_TIMEOUT`
+    pub async fn check_sequencer_reachable(&self) -> Result<()> {
+        let socket_addrs = self
+            .sequencer_url()?
+            .socket_addrs(|| None)
+            .with_context(|| format!("Failed to resolve {}", self.sequencer_addr))?;
+
+        tokio::time::timeout(
+            SEQUENCER_CONNECT_TIMEOUT,
+            tokio::net::TcpStream::connect(socket_addrs.as_slice()),
+        )
+        .await
+        .with_context(|| format!("Connection to {} timed out", self.sequencer_addr))?
+        .with_context(|| format!("Sequencer at {} is unreachable", self.sequencer_addr))?;
+
+        Ok(())
+    }
+
+    fn sequencer_url(&self) -> Result<Url> {
+        Url::parse(&self.sequencer_addr)
+            .with_context(|| format!("Invalid sequencer address {}", self.sequencer_addr))
+    }
+}
+
 impl Default for WalletConfig {
     fn default() -> Self {
         Self {
@@ -700,3 +770,49 @@ impl Default for WalletConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert!(WalletConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_fields() {
+        let invalid_configs = [
+            WalletConfig {
+                sequencer_addr: "not an url".to_string(),
+                ..WalletConfig::default()
+            },
+            WalletConfig {
+                sequencer_addr: "ftp://127.0.0.1:3040".to_string(),
+                ..WalletConfig::default()
+            },
+            WalletConfig {
+                seq_block_poll_max_amount: 0,
+                ..WalletConfig::default()
+            },
+        ];
+
+        for config in invalid_configs {
+            assert!(config.validate().is_err(), "{}", config.sequencer_addr);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_sequencer_reachable() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let config = WalletConfig {
+            sequencer_addr: format!("http://127.0.0.1:{port}"),
+            ..WalletConfig::default()
+        };
+        assert!(config.check_sequencer_reachable().await.is_ok());
+
+        drop(listener);
+        assert!(config.check_sequencer_reachable().await.is_err());
+    }
+}
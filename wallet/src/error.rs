@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+
+use crate::{HOME_DIR_ENV_VAR, config::PERSISTENT_STORAGE_VERSION};
+
+/// Errors of wallet helper functions
+#[derive(thiserror::Error, Debug)]
+pub enum WalletError {
+    #[error("Failed to get wallet home, neither {HOME_DIR_ENV_VAR} nor HOME is set")]
+    HomeNotSet,
+    #[error("Wallet home {0:#?} does not exist")]
+    HomeNotFound(PathBuf),
+    #[error("Wallet home {0:#?} is not writable")]
+    HomeNotWritable(PathBuf, #[source] std::io::Error),
+    #[error("Storage {0:#?} not found, please setup roots from config command beforehand")]
+    StorageNotFound(PathBuf),
+    #[error(
+        "Storage version {0} is newer than supported version {PERSISTENT_STORAGE_VERSION}, please update the wallet"
+    )]
+    UnsupportedStorageVersion(u32),
+    #[error("Failed to migrate storage {0:#?} to version {PERSISTENT_STORAGE_VERSION}")]
+    StorageMigrationFailed(PathBuf, #[source] anyhow::Error),
+    #[error("Unsupported privacy kind in {0:?}, available variants is Public/ and Private/")]
+    UnsupportedPrivacyKind(String),
+    #[error("Invalid program id: {0}")]
+    InvalidProgramId(String),
+    #[error("Instruction data length must be a multiple of 4 bytes")]
+    InvalidInstructionDataLength,
+    #[error("Failed to derive key from password: {0}")]
+    KeyDerivation(String),
+    #[error("Failed to encrypt data")]
+    EncryptionFailed,
+    #[error("Failed to decrypt data, wrong password or corrupted data")]
+    DecryptionFailed,
+    #[error("{0} balance overflow")]
+    BalanceOverflow(&'static str),
+    #[error("IO error")]
+    Io(#[from] std::io::Error),
+    #[error("Serde error")]
+    Serde(#[from] serde_json::Error),
+}
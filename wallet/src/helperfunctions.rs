@@ -1,32 +1,48 @@
-use std::{path::PathBuf, str::FromStr};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
-use anyhow::Result;
+use aes_gcm::{Aes256Gcm, KeyInit as _, aead::Aead as _};
+use argon2::Argon2;
 use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
 use key_protocol::key_protocol_core::NSSAUserData;
+use log::info;
 use nssa::Account;
-use nssa_core::account::Nonce;
+use nssa_core::{
+    Commitment,
+    account::Nonce,
+    program::{InstructionData, ProgramId},
+};
 use rand::{RngCore, rngs::OsRng};
 use serde::Serialize;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 use crate::{
     HOME_DIR_ENV_VAR,
+    chain_storage::{TxRecord, WalletChainStore},
     config::{
         InitialAccountData, InitialAccountDataPrivate, InitialAccountDataPublic,
-        PersistentAccountDataPrivate, PersistentAccountDataPublic, PersistentStorage, WalletConfig,
+        PERSISTENT_STORAGE_VERSION, PersistentAccountDataPrivate, PersistentAccountDataPublic,
+        PersistentStorage, WalletConfig,
     },
+    error::WalletError,
 };
 
+type Result<T> = std::result::Result<T, WalletError>;
+
 /// Get home dir for wallet. Env var `NSSA_WALLET_HOME_DIR` must be set before execution to succeed.
 pub fn get_home_nssa_var() -> Result<PathBuf> {
-    Ok(PathBuf::from_str(&std::env::var(HOME_DIR_ENV_VAR)?)?)
+    std::env::var(HOME_DIR_ENV_VAR)
+        .map(PathBuf::from)
+        .map_err(|_| WalletError::HomeNotSet)
 }
 
 /// Get home dir for wallet. Env var `HOME` must be set before execution to succeed.
 pub fn get_home_default_path() -> Result<PathBuf> {
     std::env::home_dir()
         .map(|path| path.join(".nssa").join("wallet"))
-        .ok_or(anyhow::anyhow!("Failed to get HOME"))
+        .ok_or(WalletError::HomeNotSet)
 }
 
 /// Get home dir for wallet.
@@ -38,6 +54,18 @@ pub fn get_home() -> Result<PathBuf> {
     }
 }
 
+/// Checks, that wallet home exists and files can be created in it
+pub fn check_home_writable(home: &Path) -> Result<()> {
+    if !home.is_dir() {
+        return Err(WalletError::HomeNotFound(home.to_path_buf()));
+    }
+
+    tempfile::NamedTempFile::new_in(home)
+        .map_err(|err| WalletError::HomeNotWritable(home.to_path_buf(), err))?;
+
+    Ok(())
+}
+
 /// Fetch config from default home
 pub async fn fetch_config() -> Result<WalletConfig> {
     let config_home = get_home()?;
@@ -62,7 +90,7 @@ pub async fn fetch_config() -> Result<WalletConfig> {
 
                 WalletConfig::default()
             }
-            _ => anyhow::bail!("IO error {err:#?}"),
+            _ => return Err(err.into()),
         },
     };
 
@@ -94,29 +122,62 @@ pub async fn fetch_config() -> Result<WalletConfig> {
 /// File must be created through setup beforehand.
 pub async fn fetch_persistent_storage() -> Result<PersistentStorage> {
     let home = get_home()?;
-    let accs_path = home.join("storage.json");
+    load_persistent_storage(&home.join("storage.json")).await
+}
+
+/// Reads storage at `accs_path`, storage of older versions is migrated to the current one in place
+async fn load_persistent_storage(accs_path: &Path) -> Result<PersistentStorage> {
     let mut storage_content = vec![];
 
     match tokio::fs::File::open(accs_path).await {
         Ok(mut file) => {
             file.read_to_end(&mut storage_content).await?;
-            Ok(serde_json::from_slice(&storage_content)?)
         }
-        Err(err) => match err.kind() {
-            std::io::ErrorKind::NotFound => {
-                anyhow::bail!("Not found, please setup roots from config command beforehand");
-            }
-            _ => {
-                anyhow::bail!("IO error {err:#?}");
-            }
-        },
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(WalletError::StorageNotFound(accs_path.to_path_buf()));
+        }
+        Err(err) => return Err(err.into()),
+    }
+
+    let storage: PersistentStorage = serde_json::from_slice(&storage_content)?;
+    match storage.version {
+        PERSISTENT_STORAGE_VERSION => Ok(storage),
+        0 | 1 => {
+            // Migrated storage replaces the old one only after it was written completely
+            let migrated_path = accs_path.with_extension("json.migrated");
+            migrate_storage(storage.version, accs_path, &migrated_path)
+                .and_then(|_| Ok(std::fs::rename(&migrated_path, accs_path)?))
+                .map_err(|err| WalletError::StorageMigrationFailed(accs_path.to_path_buf(), err))?;
+            info!("Migrated storage {accs_path:#?} to version {PERSISTENT_STORAGE_VERSION}");
+
+            Ok(PersistentStorage {
+                version: PERSISTENT_STORAGE_VERSION,
+                private_commitment_blocks: vec![],
+                ..storage
+            })
+        }
+        version => Err(WalletError::UnsupportedStorageVersion(version)),
+    }
+}
+
+/// Migrates storage of `version` at `old_path` to the current version at `new_path`, one version
+/// at a time
+fn migrate_storage(version: u32, old_path: &Path, new_path: &Path) -> anyhow::Result<()> {
+    let mut old_path = old_path;
+    if version == 0 {
+        WalletChainStore::migrate_v0_to_v1(old_path, new_path)?;
+        old_path = new_path;
     }
+    WalletChainStore::migrate_v1_to_v2(old_path, new_path)
 }
 
 /// Produces data for storage
 pub fn produce_data_for_storage(
     user_data: &NSSAUserData,
     last_synced_block: u64,
+    watched_account_ids: &[nssa::AccountId],
+    tx_history: &[TxRecord],
+    private_commitment_blocks: &HashMap<Commitment, u64>,
 ) -> PersistentStorage {
     let mut vec_for_storage = vec![];
 
@@ -168,8 +229,15 @@ pub fn produce_data_for_storage(
     }
 
     PersistentStorage {
+        version: PERSISTENT_STORAGE_VERSION,
         accounts: vec_for_storage,
         last_synced_block,
+        watched_account_ids: watched_account_ids.to_vec(),
+        tx_history: tx_history.to_vec(),
+        private_commitment_blocks: private_commitment_blocks
+            .iter()
+            .map(|(commitment, block_id)| (commitment.clone(), *block_id))
+            .collect(),
     }
 }
 
@@ -199,10 +267,83 @@ pub(crate) fn parse_addr_with_privacy_prefix(
             AccountPrivacyKind::Private,
         ))
     } else {
-        anyhow::bail!("Unsupported privacy kind, available variants is Public/ and Private/");
+        Err(WalletError::UnsupportedPrivacyKind(
+            account_base58.to_string(),
+        ))
     }
 }
 
+const PASSWORD_SALT_SIZE: usize = 16;
+const AES_GCM_NONCE_SIZE: usize = 12;
+
+fn password_cipher(password: &str, salt: &[u8]) -> Result<Aes256Gcm> {
+    let mut key = [0; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|err| WalletError::KeyDerivation(err.to_string()))?;
+    Ok(Aes256Gcm::new(&key.into()))
+}
+
+/// Encrypts `plaintext` with AES-256-GCM under a key derived from `password` with Argon2id.
+///
+/// Result is base64 of `salt || nonce || ciphertext`.
+pub(crate) fn encrypt_with_password(plaintext: &[u8], password: &str) -> Result<String> {
+    let mut salt = [0; PASSWORD_SALT_SIZE];
+    let mut nonce = [0; AES_GCM_NONCE_SIZE];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let ciphertext = password_cipher(password, &salt)?
+        .encrypt(&nonce.into(), plaintext)
+        .map_err(|_| WalletError::EncryptionFailed)?;
+
+    let bytes: Vec<u8> = salt.into_iter().chain(nonce).chain(ciphertext).collect();
+    Ok(BASE64.encode(bytes))
+}
+
+/// Reverses [`encrypt_with_password`]
+pub(crate) fn decrypt_with_password(encoded: &str, password: &str) -> Result<Vec<u8>> {
+    let bytes = BASE64
+        .decode(encoded.trim())
+        .map_err(|_| WalletError::DecryptionFailed)?;
+    if bytes.len() < PASSWORD_SALT_SIZE + AES_GCM_NONCE_SIZE {
+        return Err(WalletError::DecryptionFailed);
+    }
+    let (salt, rest) = bytes.split_at(PASSWORD_SALT_SIZE);
+    let (nonce, ciphertext) = rest.split_at(AES_GCM_NONCE_SIZE);
+
+    password_cipher(password, salt)?
+        .decrypt(nonce.into(), ciphertext)
+        .map_err(|_| WalletError::DecryptionFailed)
+}
+
+/// Parses program id from 64 char hex string of its little-endian words
+pub(crate) fn parse_program_id_hex(program_id_hex: &str) -> Result<ProgramId> {
+    let bytes: [u8; 32] = hex::decode(program_id_hex)
+        .map_err(|err| WalletError::InvalidProgramId(err.to_string()))?
+        .try_into()
+        .map_err(|_| WalletError::InvalidProgramId("Program id must be 32 bytes long".into()))?;
+
+    let mut program_id = ProgramId::default();
+    for (word, chunk) in program_id.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+
+    Ok(program_id)
+}
+
+/// Converts serialized instruction bytes into instruction data words
+pub(crate) fn instruction_data_from_bytes(bytes: &[u8]) -> Result<InstructionData> {
+    if !bytes.len().is_multiple_of(4) {
+        return Err(WalletError::InvalidInstructionDataLength);
+    }
+
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
+
 /// Human-readable representation of an account.
 #[derive(Serialize)]
 pub(crate) struct HumanReadableAccount {
@@ -225,9 +366,49 @@ impl From<Account> for HumanReadableAccount {
     }
 }
 
+/// Aggregated balances of all accounts owned by the wallet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PortfolioSummary {
+    pub public_balance: u128,
+    pub private_account_count: usize,
+    pub private_total_value: u128,
+    pub total_value: u128,
+}
+
+impl PortfolioSummary {
+    pub fn from_balances(
+        public_balances: impl IntoIterator<Item = u128>,
+        private_balances: impl IntoIterator<Item = u128>,
+    ) -> Result<Self> {
+        let public_balance = public_balances
+            .into_iter()
+            .try_fold(0u128, u128::checked_add)
+            .ok_or(WalletError::BalanceOverflow("Public"))?;
+
+        let mut private_account_count = 0;
+        let private_total_value = private_balances
+            .into_iter()
+            .inspect(|_| private_account_count += 1)
+            .try_fold(0u128, u128::checked_add)
+            .ok_or(WalletError::BalanceOverflow("Private"))?;
+
+        let total_value = public_balance
+            .checked_add(private_total_value)
+            .ok_or(WalletError::BalanceOverflow("Total"))?;
+
+        Ok(Self {
+            public_balance,
+            private_account_count,
+            private_total_value,
+            total_value,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::PersistentAccountData;
 
     #[test]
     fn test_get_home_get_env_var() {
@@ -237,7 +418,7 @@ mod tests {
 
         let home = get_home().unwrap();
 
-        assert_eq!(PathBuf::from_str("/path/to/configs").unwrap(), home);
+        assert_eq!(PathBuf::from("/path/to/configs"), home);
 
         unsafe {
             std::env::remove_var(HOME_DIR_ENV_VAR);
@@ -257,6 +438,247 @@ mod tests {
         assert_eq!(addr_kind, AccountPrivacyKind::Private);
 
         let addr_base58 = "asdsada/BLgCRDXYdQPMMWVHYRFGQZbgeHx9frkipa8GtpG2Syqy";
-        assert!(parse_addr_with_privacy_prefix(addr_base58).is_err());
+        assert!(matches!(
+            parse_addr_with_privacy_prefix(addr_base58),
+            Err(WalletError::UnsupportedPrivacyKind(_))
+        ));
+    }
+
+    #[test]
+    fn test_portfolio_summary_from_balances() {
+        let summary = PortfolioSummary::from_balances([100, 200], [10, 20, 30]).unwrap();
+
+        assert_eq!(
+            summary,
+            PortfolioSummary {
+                public_balance: 300,
+                private_account_count: 3,
+                private_total_value: 60,
+                total_value: 360,
+            }
+        );
+    }
+
+    #[test]
+    fn test_portfolio_summary_empty() {
+        let summary = PortfolioSummary::from_balances([], []).unwrap();
+
+        assert_eq!(summary, PortfolioSummary::default());
+    }
+
+    #[test]
+    fn test_parse_program_id_hex() {
+        let program_id: ProgramId = [1, 2, 3, 4, 5, 6, 7, u32::MAX];
+        let program_id_hex = hex::encode(bytemuck::cast_slice::<u32, u8>(&program_id));
+
+        assert_eq!(parse_program_id_hex(&program_id_hex).unwrap(), program_id);
+        assert!(matches!(
+            parse_program_id_hex(&program_id_hex[2..]),
+            Err(WalletError::InvalidProgramId(_))
+        ));
+        assert!(matches!(
+            parse_program_id_hex("zz"),
+            Err(WalletError::InvalidProgramId(_))
+        ));
+    }
+
+    #[test]
+    fn test_instruction_data_from_bytes() {
+        let instruction_data = nssa::program::Program::serialize_instruction(1337u128).unwrap();
+        let bytes: Vec<u8> = bytemuck::cast_slice(&instruction_data).to_vec();
+
+        assert_eq!(
+            instruction_data_from_bytes(&bytes).unwrap(),
+            instruction_data
+        );
+        assert!(matches!(
+            instruction_data_from_bytes(&bytes[1..]),
+            Err(WalletError::InvalidInstructionDataLength)
+        ));
+    }
+
+    #[test]
+    fn test_encrypt_with_password_roundtrip() {
+        let encrypted = encrypt_with_password(b"secret key bytes", "hunter2").unwrap();
+
+        assert_eq!(
+            decrypt_with_password(&encrypted, "hunter2").unwrap(),
+            b"secret key bytes"
+        );
+        assert!(matches!(
+            decrypt_with_password(&encrypted, "hunter3"),
+            Err(WalletError::DecryptionFailed)
+        ));
+        assert!(matches!(
+            decrypt_with_password(&encrypted[..8], "hunter2"),
+            Err(WalletError::DecryptionFailed)
+        ));
+    }
+
+    #[test]
+    fn test_check_home_writable() {
+        let home = tempfile::tempdir().unwrap();
+        assert!(check_home_writable(home.path()).is_ok());
+
+        let missing_home = home.path().join("missing");
+        assert!(matches!(
+            check_home_writable(&missing_home),
+            Err(WalletError::HomeNotFound(path)) if path == missing_home
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_load_persistent_storage_migrates_v0_fixture() {
+        let home = tempfile::tempdir().unwrap();
+        let accs_path = home.path().join("storage.json");
+        std::fs::copy(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/test_data/storage_v0.json"),
+            &accs_path,
+        )
+        .unwrap();
+
+        let storage = load_persistent_storage(&accs_path).await.unwrap();
+        assert_eq!(storage.version, PERSISTENT_STORAGE_VERSION);
+        assert_eq!(storage.accounts.len(), 4);
+        assert_eq!(storage.last_synced_block, 5);
+
+        // Migrated storage is written back and loaded as is next time
+        let stored: PersistentStorage =
+            serde_json::from_slice(&std::fs::read(&accs_path).unwrap()).unwrap();
+        assert_eq!(stored.version, PERSISTENT_STORAGE_VERSION);
+        assert!(!accs_path.with_extension("json.migrated").exists());
+
+        let reloaded = load_persistent_storage(&accs_path).await.unwrap();
+        assert_eq!(
+            reloaded
+                .accounts
+                .iter()
+                .map(PersistentAccountData::account_id)
+                .collect::<Vec<_>>(),
+            storage
+                .accounts
+                .iter()
+                .map(PersistentAccountData::account_id)
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_persistent_storage_migrates_v1() {
+        let home = tempfile::tempdir().unwrap();
+        let accs_path = home.path().join("storage.json");
+        let storage = serde_json::json!({
+            "version": 1,
+            "accounts": [],
+            "last_synced_block": 7,
+        });
+        std::fs::write(&accs_path, serde_json::to_vec(&storage).unwrap()).unwrap();
+
+        let storage = load_persistent_storage(&accs_path).await.unwrap();
+        assert_eq!(storage.version, PERSISTENT_STORAGE_VERSION);
+        assert_eq!(storage.last_synced_block, 7);
+        assert!(storage.private_commitment_blocks.is_empty());
+
+        let stored: PersistentStorage =
+            serde_json::from_slice(&std::fs::read(&accs_path).unwrap()).unwrap();
+        assert_eq!(stored.version, PERSISTENT_STORAGE_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_load_persistent_storage_rejects_newer_version() {
+        let home = tempfile::tempdir().unwrap();
+        let accs_path = home.path().join("storage.json");
+        let storage = serde_json::json!({
+            "version": PERSISTENT_STORAGE_VERSION + 1,
+            "accounts": [],
+            "last_synced_block": 0,
+        });
+        std::fs::write(&accs_path, serde_json::to_vec(&storage).unwrap()).unwrap();
+
+        assert!(matches!(
+            load_persistent_storage(&accs_path).await,
+            Err(WalletError::UnsupportedStorageVersion(version))
+                if version == PERSISTENT_STORAGE_VERSION + 1
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_load_persistent_storage_not_found() {
+        let home = tempfile::tempdir().unwrap();
+        let accs_path = home.path().join("storage.json");
+
+        assert!(matches!(
+            load_persistent_storage(&accs_path).await,
+            Err(WalletError::StorageNotFound(path)) if path == accs_path
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_privacy_kind() {
+        for addr in [
+            "BLgCRDXYdQPMMWVHYRFGQZbgeHx9frkipa8GtpG2Syqy",
+            "public/BLgCRDXYdQPMMWVHYRFGQZbgeHx9frkipa8GtpG2Syqy",
+            "Public",
+            "",
+        ] {
+            assert!(matches!(
+                parse_addr_with_privacy_prefix(addr),
+                Err(WalletError::UnsupportedPrivacyKind(kind)) if kind == addr
+            ));
+        }
+    }
+
+    #[test]
+    fn test_invalid_program_id() {
+        for program_id_hex in ["", "0g", &"00".repeat(31), &"00".repeat(33)] {
+            assert!(matches!(
+                parse_program_id_hex(program_id_hex),
+                Err(WalletError::InvalidProgramId(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_invalid_instruction_data_length() {
+        assert!(instruction_data_from_bytes(&[]).unwrap().is_empty());
+        for len in [1, 2, 3, 5, 7] {
+            assert!(matches!(
+                instruction_data_from_bytes(&vec![0; len]),
+                Err(WalletError::InvalidInstructionDataLength)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_decryption_failed() {
+        let encrypted = encrypt_with_password(b"secret key bytes", "hunter2").unwrap();
+
+        let mut tampered = BASE64.decode(&encrypted).unwrap();
+        *tampered.last_mut().unwrap() ^= 1;
+        let tampered = BASE64.encode(tampered);
+
+        for encoded in ["not base64!", "", &encrypted[..8], &tampered] {
+            assert!(matches!(
+                decrypt_with_password(encoded, "hunter2"),
+                Err(WalletError::DecryptionFailed)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_balance_overflow() {
+        assert!(matches!(
+            PortfolioSummary::from_balances([u128::MAX, 1], []),
+            Err(WalletError::BalanceOverflow("Public"))
+        ));
+        assert!(matches!(
+            PortfolioSummary::from_balances([], [u128::MAX, 1]),
+            Err(WalletError::BalanceOverflow("Private"))
+        ));
+        assert!(matches!(
+            PortfolioSummary::from_balances([u128::MAX], [1]),
+            Err(WalletError::BalanceOverflow("Total"))
+        ));
+        assert!(PortfolioSummary::from_balances([u128::MAX], []).is_ok());
     }
 }
@@ -1,67 +1,109 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{collections::HashSet, path::PathBuf, sync::Arc};
 
 use anyhow::Result;
 use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
-use chain_storage::WalletChainStore;
+use chain_storage::{TxRecord, WalletChainStore};
 use common::{
+    activity::ActivityScore,
+    block::HashableBlockData,
     error::ExecutionFailureKind,
     rpc_primitives::requests::SendTxResponse,
     sequencer_client::SequencerClient,
     transaction::{EncodedTransaction, NSSATransaction},
 };
 use config::WalletConfig;
-use key_protocol::key_management::key_tree::{chain_index::ChainIndex, traits::KeyNode as _};
-use log::info;
+use key_protocol::key_management::{
+    ViewingKeyProof,
+    key_tree::{
+        KeyTree, chain_index::ChainIndex, keys_private::ChildKeysPrivate,
+        keys_public::ChildKeysPublic, traits::KeyNode,
+    },
+};
+use log::{info, warn};
 use nssa::{
     Account, AccountId, PrivacyPreservingTransaction,
     privacy_preserving_transaction::message::EncryptedAccountData, program::Program,
 };
-use nssa_core::{Commitment, MembershipProof, SharedSecretKey, program::InstructionData};
+use nssa_core::{
+    Commitment, MembershipProof, Nullifier, SharedSecretKey,
+    program::{InstructionData, ProgramId},
+};
 pub use privacy_preserving_tx::PrivacyPreservingAccount;
-use tokio::io::AsyncWriteExt;
+use tokio::{io::AsyncWriteExt, sync::broadcast};
 
 use crate::{
     config::PersistentStorage,
     helperfunctions::{
-        fetch_persistent_storage, get_home, produce_data_for_storage, produce_random_nonces,
+        AccountPrivacyKind, PortfolioSummary, decrypt_with_password, encrypt_with_password,
+        fetch_persistent_storage, get_home, instruction_data_from_bytes, produce_data_for_storage,
+        produce_random_nonces,
     },
     poller::TxPoller,
 };
 
 pub const HOME_DIR_ENV_VAR: &str = "NSSA_WALLET_HOME_DIR";
 
+/// Number of block events kept for slow subscribers before they start lagging
+pub const BLOCK_EVENTS_CHANNEL_CAPACITY: usize = 1024;
+
 pub mod chain_storage;
 pub mod cli;
 pub mod config;
+pub mod error;
 pub mod helperfunctions;
 pub mod poller;
 mod privacy_preserving_tx;
 pub mod program_facades;
 
+/// Event emitted by wallet while syncing with the chain
+#[derive(Debug, Clone)]
+pub enum BlockEvent {
+    /// Block was synced, emitted after events of its transactions
+    NewBlock { height: u64, tx_count: usize },
+    /// Private account of this wallet got new state
+    PrivateAccountReceived {
+        account_id: AccountId,
+        account: Account,
+    },
+    /// Nullifier was published in synced block
+    NullifierSpent { nullifier: Nullifier },
+}
+
 pub struct WalletCore {
     pub storage: WalletChainStore,
     pub poller: TxPoller,
     pub sequencer_client: Arc<SequencerClient>,
     pub last_synced_block: u64,
+    block_events: broadcast::Sender<BlockEvent>,
 }
 
 impl WalletCore {
     pub async fn start_from_config_update_chain(config: WalletConfig) -> Result<Self> {
+        config.validate()?;
+
         let client = Arc::new(SequencerClient::new(config.sequencer_addr.clone())?);
         let tx_poller = TxPoller::new(config.clone(), client.clone());
 
         let PersistentStorage {
             accounts: persistent_accounts,
             last_synced_block,
+            watched_account_ids,
+            tx_history,
+            private_commitment_blocks,
+            ..
         } = fetch_persistent_storage().await?;
 
-        let storage = WalletChainStore::new(config, persistent_accounts)?;
+        let mut storage = WalletChainStore::new(config, persistent_accounts)?;
+        storage.watched_account_ids = watched_account_ids;
+        storage.tx_history = tx_history;
+        storage.private_commitment_blocks = private_commitment_blocks.into_iter().collect();
 
         Ok(Self {
             storage,
             poller: tx_poller,
             sequencer_client: client.clone(),
             last_synced_block,
+            block_events: broadcast::channel(BLOCK_EVENTS_CHANNEL_CAPACITY).0,
         })
     }
 
@@ -69,6 +111,8 @@ impl WalletCore {
         config: WalletConfig,
         password: String,
     ) -> Result<Self> {
+        config.validate()?;
+
         let client = Arc::new(SequencerClient::new(config.sequencer_addr.clone())?);
         let tx_poller = TxPoller::new(config.clone(), client.clone());
 
@@ -79,6 +123,7 @@ impl WalletCore {
             poller: tx_poller,
             sequencer_client: client.clone(),
             last_synced_block: 0,
+            block_events: broadcast::channel(BLOCK_EVENTS_CHANNEL_CAPACITY).0,
         })
     }
 
@@ -87,7 +132,13 @@ impl WalletCore {
         let home = get_home()?;
         let storage_path = home.join("storage.json");
 
-        let data = produce_data_for_storage(&self.storage.user_data, self.last_synced_block);
+        let data = produce_data_for_storage(
+            &self.storage.user_data,
+            self.last_synced_block,
+            &self.storage.watched_account_ids,
+            &self.storage.tx_history,
+            &self.storage.private_commitment_blocks,
+        );
         let storage = serde_json::to_vec_pretty(&data)?;
 
         let mut storage_file = tokio::fs::File::create(storage_path.as_path()).await?;
@@ -124,6 +175,41 @@ impl WalletCore {
             .generate_new_privacy_preserving_transaction_key_chain(chain_index)
     }
 
+    /// Restores accounts derived from the wallet seed, e.g. after storage was lost.
+    ///
+    /// Children of the root key are derived until `max_gap` consecutive ones are unused on chain.
+    /// Public accounts are used if their state is not default, private ones if their
+    /// initialization nullifier is published. States of recovered private accounts are restored
+    /// by syncing from genesis afterwards.
+    pub async fn recover_accounts(&mut self, max_gap: usize) -> Result<Vec<AccountId>> {
+        let client = self.sequencer_client.clone();
+
+        let mut recovered = recover_root_children(
+            &mut self.storage.user_data.public_key_tree,
+            max_gap,
+            async |node: &ChildKeysPublic| {
+                let account = client.get_account(node.account_id().to_string()).await?;
+                Ok(account.account != Account::default())
+            },
+        )
+        .await?;
+
+        recovered.extend(
+            recover_root_children(
+                &mut self.storage.user_data.private_key_tree,
+                max_gap,
+                async |node: &ChildKeysPrivate| {
+                    let nullifier =
+                        Nullifier::for_account_initialization(&node.value.0.nullifer_public_key);
+                    Ok(client.get_proof_for_nullifier(nullifier).await?.is_some())
+                },
+            )
+            .await?,
+        );
+
+        Ok(recovered)
+    }
+
     /// Get account balance
     pub async fn get_account_balance(&self, acc: AccountId) -> Result<u128> {
         Ok(self
@@ -133,6 +219,95 @@ impl WalletCore {
             .balance)
     }
 
+    /// Lists all accounts owned by the wallet, preconfigured ones first
+    pub fn list_accounts(&self) -> Vec<(AccountId, AccountPrivacyKind)> {
+        let user_data = &self.storage.user_data;
+
+        let public_account_ids = user_data
+            .default_pub_account_signing_keys
+            .keys()
+            .chain(user_data.public_key_tree.account_id_map.keys())
+            .map(|account_id| (*account_id, AccountPrivacyKind::Public));
+        let private_account_ids = user_data
+            .default_user_private_accounts
+            .keys()
+            .chain(user_data.private_key_tree.account_id_map.keys())
+            .map(|account_id| (*account_id, AccountPrivacyKind::Private));
+
+        public_account_ids.chain(private_account_ids).collect()
+    }
+
+    /// Starts monitoring public account not owned by the wallet.
+    ///
+    /// Returns `false` if account is already watched.
+    pub fn watch_account(&mut self, account_id: AccountId) -> bool {
+        if self.storage.watched_account_ids.contains(&account_id) {
+            return false;
+        }
+        self.storage.watched_account_ids.push(account_id);
+        true
+    }
+
+    /// Exports signing key of public account, encrypted with `password`
+    pub fn export_account(&self, account_id: AccountId, password: &str) -> Result<String> {
+        let signing_key =
+            self.get_account_public_signing_key(&account_id)
+                .ok_or(anyhow::anyhow!(
+                    "Public account {account_id} not found in storage"
+                ))?;
+
+        Ok(encrypt_with_password(signing_key.value(), password)?)
+    }
+
+    /// Imports public account from output of [`WalletCore::export_account`]
+    pub fn import_account(&mut self, ciphertext: &str, password: &str) -> Result<AccountId> {
+        let key_bytes: [u8; 32] = decrypt_with_password(ciphertext, password)?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Signing key must be 32 bytes long"))?;
+        let signing_key = nssa::PrivateKey::try_new(key_bytes)?;
+        let account_id = AccountId::from(&nssa::PublicKey::new_from_private_key(&signing_key));
+
+        self.storage
+            .user_data
+            .default_pub_account_signing_keys
+            .insert(account_id, signing_key);
+
+        Ok(account_id)
+    }
+
+    /// Sums balances of all public and private accounts owned by the wallet
+    ///
+    /// Private balances are taken from local storage, so wallet should be synced beforehand.
+    pub async fn compute_portfolio_value(&self) -> Result<PortfolioSummary> {
+        let mut public_balances = vec![];
+        let mut private_balances = vec![];
+        for (account_id, kind) in self.list_accounts() {
+            match kind {
+                AccountPrivacyKind::Public => {
+                    public_balances.push(self.get_account_balance(account_id).await?)
+                }
+                AccountPrivacyKind::Private => private_balances.extend(
+                    self.get_account_private(&account_id)
+                        .map(|account| account.balance),
+                ),
+            }
+        }
+
+        Ok(PortfolioSummary::from_balances(
+            public_balances,
+            private_balances,
+        )?)
+    }
+
+    /// Get account activity score, computed by sequencer
+    pub async fn get_account_activity_score(&self, acc: AccountId) -> Result<ActivityScore> {
+        Ok(self
+            .sequencer_client
+            .get_account_activity(acc.to_string())
+            .await?
+            .activity)
+    }
+
     /// Get accounts nonces
     pub async fn get_accounts_nonces(&self, accs: Vec<AccountId>) -> Result<Vec<u128>> {
         Ok(self
@@ -142,6 +317,39 @@ impl WalletCore {
             .nonces)
     }
 
+    /// Get minimal fee sequencer requires for `tx`
+    pub async fn estimate_fee(&self, tx: &EncodedTransaction) -> Result<u128> {
+        Ok(self.sequencer_client.estimate_fee(tx).await?.fee)
+    }
+
+    /// Signs public transaction paying the minimal fee the sequencer requires for it
+    ///
+    /// Fee is paid by the first signer, so transactions without signers pay none.
+    pub async fn sign_public_transaction(
+        &self,
+        message: nssa::public_transaction::Message,
+        signing_keys: &[&nssa::PrivateKey],
+    ) -> Result<nssa::PublicTransaction, ExecutionFailureKind> {
+        let witness_set = nssa::public_transaction::WitnessSet::for_message(&message, signing_keys);
+        let tx = nssa::PublicTransaction::new(message, witness_set)?;
+        if signing_keys.is_empty() {
+            return Ok(tx);
+        }
+
+        let fee = self
+            .sequencer_client
+            .estimate_fee(&NSSATransaction::Public(tx.clone()).into())
+            .await?
+            .fee;
+        if fee == 0 {
+            return Ok(tx);
+        }
+
+        let message = tx.message().clone().with_fee(fee);
+        let witness_set = nssa::public_transaction::WitnessSet::for_message(&message, signing_keys);
+        Ok(nssa::PublicTransaction::new(message, witness_set)?)
+    }
+
     /// Get account
     pub async fn get_account_public(&self, account_id: AccountId) -> Result<Account> {
         let response = self
@@ -172,13 +380,40 @@ impl WalletCore {
         Some(Commitment::new(&keys.nullifer_public_key, account))
     }
 
+    /// Returns how many synced blocks ago the current commitment of private account was created
+    pub fn get_private_account_age(&self, account_id: &AccountId) -> Option<u64> {
+        let commitment = self.get_private_account_commitment(account_id)?;
+        self.storage
+            .get_commitment_age(&commitment, self.last_synced_block)
+    }
+
+    /// Proves control over viewing key of private account by signing `challenge` with it
+    pub fn generate_viewing_key_proof(
+        &self,
+        account_id: &AccountId,
+        challenge: [u8; 32],
+    ) -> Result<ViewingKeyProof> {
+        let (key_chain, _) = self
+            .storage
+            .user_data
+            .get_private_account(account_id)
+            .ok_or(anyhow::anyhow!("Private account not found in storage"))?;
+
+        Ok(key_chain.produce_viewing_key_proof(&challenge))
+    }
+
     /// Poll transactions
     pub async fn poll_native_token_transfer(&self, hash: String) -> Result<NSSATransaction> {
+        self.poll_transaction(hash).await
+    }
+
+    /// Polls until a transaction of any program with `hash` is included in a block
+    pub async fn poll_transaction(&self, hash: String) -> Result<NSSATransaction> {
         let transaction_encoded = self.poller.poll_tx(hash).await?;
         let tx_base64_decode = BASE64.decode(transaction_encoded)?;
-        let pub_tx = borsh::from_slice::<EncodedTransaction>(&tx_base64_decode).unwrap();
+        let encoded_tx = borsh::from_slice::<EncodedTransaction>(&tx_base64_decode)?;
 
-        Ok(NSSATransaction::try_from(&pub_tx)?)
+        Ok(NSSATransaction::try_from(&encoded_tx)?)
     }
 
     pub async fn check_private_account_initialized(
@@ -223,6 +458,45 @@ impl WalletCore {
         Ok(())
     }
 
+    /// Builds public transaction with raw instruction data for arbitrary program and submits it
+    ///
+    /// Every account, which signing key is known to the wallet, is treated as authorized and
+    /// signs the message.
+    pub async fn sign_and_submit_arbitrary_instruction(
+        &self,
+        program_id: ProgramId,
+        account_ids: Vec<AccountId>,
+        instruction_data: Vec<u8>,
+    ) -> Result<SendTxResponse> {
+        let instruction_data = instruction_data_from_bytes(&instruction_data)?;
+
+        let mut unique_ids = HashSet::new();
+        if let Some(duplicate) = account_ids.iter().find(|id| !unique_ids.insert(*id)) {
+            anyhow::bail!("Account {duplicate} is passed to the instruction more than once");
+        }
+
+        let (signer_ids, signing_keys): (Vec<_>, Vec<_>) = account_ids
+            .iter()
+            .filter_map(|account_id| {
+                self.get_account_public_signing_key(account_id)
+                    .map(|key| (*account_id, key))
+            })
+            .unzip();
+
+        let nonces = self.get_accounts_nonces(signer_ids).await?;
+
+        let message = nssa::public_transaction::Message::new_preserialized(
+            program_id,
+            account_ids,
+            nonces,
+            instruction_data,
+            0,
+        );
+        let tx = self.sign_public_transaction(message, &signing_keys).await?;
+
+        Ok(self.sequencer_client.send_tx_public(tx).await?)
+    }
+
     pub async fn send_privacy_preserving_tx(
         &self,
         accounts: Vec<PrivacyPreservingAccount>,
@@ -312,12 +586,7 @@ impl WalletCore {
             std::pin::pin!(poller.poll_block_range(self.last_synced_block + 1..=block_id));
 
         while let Some(block) = blocks.try_next().await? {
-            for tx in block.transactions {
-                let nssa_tx = NSSATransaction::try_from(&tx)?;
-                self.sync_private_accounts_with_tx(nssa_tx);
-            }
-
-            self.last_synced_block = block.block_id;
+            self.sync_block(block)?;
             self.store_persistent_data().await?;
         }
 
@@ -329,11 +598,128 @@ impl WalletCore {
         Ok(())
     }
 
-    fn sync_private_accounts_with_tx(&mut self, tx: NSSATransaction) {
+    /// Returns stream of events produced by syncing blocks.
+    ///
+    /// Events emitted before subscription are not included.
+    pub fn subscribe_to_block_events(&self) -> impl futures::Stream<Item = BlockEvent> + use<> {
+        let mut receiver = self.block_events.subscribe();
+
+        async_stream::stream! {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => yield event,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Block events subscriber lagged, skipped {skipped} events");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+
+    fn emit_block_event(&self, event: BlockEvent) {
+        // Sending fails only if there are no subscribers, which is fine
+        let _ = self.block_events.send(event);
+    }
+
+    fn sync_block(&mut self, block: HashableBlockData) -> Result<()> {
+        let tx_count = block.transactions.len();
+
+        for tx in block.transactions {
+            let nssa_tx = NSSATransaction::try_from(&tx)?;
+            let tx_record = |account_id, amount, counterparty| TxRecord {
+                tx_hash: hex::encode(tx.hash()),
+                kind: tx.tx_kind,
+                account_id,
+                amount,
+                counterparty,
+                block_height: block.block_id,
+                timestamp: block.timestamp,
+            };
+
+            if let NSSATransaction::Public(public_tx) = &nssa_tx {
+                for (account_id, amount, counterparty) in
+                    self.owned_accounts_of_public_tx(public_tx)
+                {
+                    self.storage
+                        .tx_history
+                        .push(tx_record(account_id, amount, counterparty));
+                }
+            }
+
+            for account_id in self.sync_private_accounts_with_tx(nssa_tx, block.block_id) {
+                self.storage
+                    .tx_history
+                    .push(tx_record(account_id, None, None));
+            }
+        }
+
+        self.last_synced_block = block.block_id;
+        self.emit_block_event(BlockEvent::NewBlock {
+            height: block.block_id,
+            tx_count,
+        });
+
+        Ok(())
+    }
+
+    /// Public accounts of the wallet touched by `tx`, with amount and counterparty for native
+    /// token transfers
+    fn owned_accounts_of_public_tx(
+        &self,
+        tx: &nssa::PublicTransaction,
+    ) -> Vec<(AccountId, Option<u128>, Option<AccountId>)> {
+        let message = tx.message();
+        let owned_account_ids: Vec<_> = message
+            .account_ids()
+            .iter()
+            .filter(|account_id| self.get_account_public_signing_key(account_id).is_some())
+            .collect();
+        if owned_account_ids.is_empty() {
+            return vec![];
+        }
+
+        let transfer = match message.account_ids() {
+            [from, to]
+                if message.program_id() == Program::authenticated_transfer_program().id() =>
+            {
+                Program::deserialize_instruction::<u128>(message.instruction_data())
+                    .ok()
+                    .map(|amount| (amount, *from, *to))
+            }
+            _ => None,
+        };
+
+        owned_account_ids
+            .into_iter()
+            .map(|account_id| match transfer {
+                Some((amount, from, to)) => {
+                    let counterparty = if *account_id == from { to } else { from };
+                    (*account_id, Some(amount), Some(counterparty))
+                }
+                None => (*account_id, None, None),
+            })
+            .collect()
+    }
+
+    /// Updates private accounts of the wallet with new states from `tx`.
+    ///
+    /// Returns ids of affected accounts
+    fn sync_private_accounts_with_tx(
+        &mut self,
+        tx: NSSATransaction,
+        block_id: u64,
+    ) -> Vec<AccountId> {
         let NSSATransaction::PrivacyPreserving(tx) = tx else {
-            return;
+            return vec![];
         };
 
+        for (nullifier, _) in tx.message().new_nullifiers() {
+            self.emit_block_event(BlockEvent::NullifierSpent {
+                nullifier: nullifier.clone(),
+            });
+        }
+
         let private_account_key_chains = self
             .storage
             .user_data
@@ -374,16 +760,348 @@ impl WalletCore {
                             ciph_id as u32,
                         )
                     })
-                    .map(move |res_acc| (acc_account_id, res_acc))
+                    .map(move |res_acc| (acc_account_id, key_chain, res_acc))
             })
             .collect::<Vec<_>>();
 
-        for (affected_account_id, new_acc) in affected_accounts {
+        let affected_accounts = affected_accounts
+            .into_iter()
+            .map(|(acc_account_id, key_chain, new_acc)| {
+                let commitment = Commitment::new(&key_chain.nullifer_public_key, &new_acc);
+                (acc_account_id, commitment, new_acc)
+            })
+            .collect::<Vec<_>>();
+
+        let mut affected_account_ids = vec![];
+        for (affected_account_id, commitment, new_acc) in affected_accounts {
+            affected_account_ids.push(affected_account_id);
             println!(
                 "Received new account for account_id {affected_account_id:#?} with account object {new_acc:#?}"
             );
             self.storage
-                .insert_private_account_data(affected_account_id, new_acc);
+                .insert_private_account_data(affected_account_id, new_acc.clone());
+            self.storage
+                .insert_private_commitment_block(commitment, block_id);
+            self.emit_block_event(BlockEvent::PrivateAccountReceived {
+                account_id: affected_account_id,
+                account: new_acc,
+            });
+        }
+
+        affected_account_ids
+    }
+}
+
+/// Derives children of the root of `tree` after the existing ones, until `max_gap` consecutive
+/// children are not used. Children up to the last used one are inserted, so they stay contiguous.
+/// Returns account ids of used children
+async fn recover_root_children<N: KeyNode>(
+    tree: &mut KeyTree<N>,
+    max_gap: usize,
+    mut is_used: impl AsyncFnMut(&N) -> Result<bool>,
+) -> Result<Vec<AccountId>> {
+    let root = ChainIndex::root();
+    let mut child_id = tree
+        .find_next_last_child_of_id(&root)
+        .expect("Key tree always has root");
+
+    let mut recovered = vec![];
+    let mut unused = vec![];
+    while unused.len() < max_gap {
+        let node = tree.key_map[&root].nth_child(child_id);
+        let chain_index = root.nth_child(child_id);
+        child_id += 1;
+
+        if !is_used(&node).await? {
+            unused.push((chain_index, node));
+            continue;
+        }
+
+        recovered.push(node.account_id());
+        for (chain_index, node) in unused.drain(..).chain([(chain_index, node)]) {
+            tree.insert(node.account_id(), chain_index, node);
+        }
+    }
+
+    Ok(recovered)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt as _;
+    use key_protocol::key_management::{key_tree::KeyTreePublic, secret_holders::SeedHolder};
+
+    use super::*;
+
+    async fn wallet_for_tests() -> WalletCore {
+        let config = WalletConfig {
+            initial_accounts: vec![],
+            ..WalletConfig::default()
+        };
+
+        WalletCore::start_from_config_new_storage(config, "password".to_string())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_to_block_events_receives_synced_blocks() {
+        let mut wallet = wallet_for_tests().await;
+        let mut events = std::pin::pin!(wallet.subscribe_to_block_events());
+
+        for block_id in 1..=3 {
+            wallet
+                .sync_block(HashableBlockData {
+                    block_id,
+                    prev_block_hash: [0; 32],
+                    timestamp: block_id * 100,
+                    transactions: vec![],
+                })
+                .unwrap();
+        }
+
+        for block_id in 1..=3 {
+            let event = events.next().await.unwrap();
+            assert!(matches!(
+                event,
+                BlockEvent::NewBlock { height, tx_count: 0 } if height == block_id
+            ));
+        }
+        assert_eq!(wallet.last_synced_block, 3);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_to_block_events_skips_past_events() {
+        let mut wallet = wallet_for_tests().await;
+        let block = |block_id| HashableBlockData {
+            block_id,
+            prev_block_hash: [0; 32],
+            timestamp: 0,
+            transactions: vec![],
+        };
+
+        wallet.sync_block(block(1)).unwrap();
+        let mut events = std::pin::pin!(wallet.subscribe_to_block_events());
+        wallet.sync_block(block(2)).unwrap();
+
+        let event = events.next().await.unwrap();
+        assert!(matches!(event, BlockEvent::NewBlock { height: 2, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_submit_arbitrary_instruction_rejects_duplicate_accounts() {
+        let wallet = wallet_for_tests().await;
+        let account_id = AccountId::new([1; 32]);
+
+        let result = wallet
+            .sign_and_submit_arbitrary_instruction(
+                [0; 8],
+                vec![account_id, AccountId::new([2; 32]), account_id],
+                vec![],
+            )
+            .await;
+
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("passed to the instruction more than once")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recover_root_children_finds_used_accounts() {
+        let seed = SeedHolder::new_mnemonic("password".to_string());
+        let mut lost_tree = KeyTreePublic::new(&seed);
+        let created: Vec<_> = (0..7)
+            .map(|_| lost_tree.generate_new_node(ChainIndex::root()).unwrap())
+            .collect();
+        // Accounts 2 and 5 were never used on chain
+        let used: Vec<_> = [0, 1, 3, 4, 6].map(|index| created[index]).to_vec();
+
+        let mut tree = KeyTreePublic::new(&seed);
+        let recovered = recover_root_children(&mut tree, 2, async |node: &ChildKeysPublic| {
+            Ok(used.contains(&node.account_id()))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(recovered, used);
+        for account_id in created {
+            assert!(tree.get_node(account_id).is_some());
+        }
+        assert_eq!(
+            tree.find_next_last_child_of_id(&ChainIndex::root()),
+            Some(7)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_accounts() {
+        let mut wallet = wallet_for_tests().await;
+        let num_initial_accounts = wallet.list_accounts().len();
+
+        let public_account_id = wallet.create_new_account_public(ChainIndex::root());
+        let private_account_id = wallet.create_new_account_private(ChainIndex::root());
+
+        let accounts = wallet.list_accounts();
+        assert_eq!(accounts.len(), num_initial_accounts + 2);
+        assert!(accounts.contains(&(public_account_id, AccountPrivacyKind::Public)));
+        assert!(accounts.contains(&(private_account_id, AccountPrivacyKind::Private)));
+    }
+
+    #[tokio::test]
+    async fn test_watch_account() {
+        let mut wallet = wallet_for_tests().await;
+        let account_id = AccountId::new([7; 32]);
+
+        assert!(wallet.watch_account(account_id));
+        assert!(!wallet.watch_account(account_id));
+        assert_eq!(wallet.storage.watched_account_ids, vec![account_id]);
+
+        let data = produce_data_for_storage(
+            &wallet.storage.user_data,
+            wallet.last_synced_block,
+            &wallet.storage.watched_account_ids,
+            &wallet.storage.tx_history,
+            &wallet.storage.private_commitment_blocks,
+        );
+        let restored: PersistentStorage =
+            serde_json::from_slice(&serde_json::to_vec(&data).unwrap()).unwrap();
+        assert_eq!(restored.watched_account_ids, vec![account_id]);
+    }
+
+    #[tokio::test]
+    async fn test_private_account_age_survives_storage_roundtrip() {
+        let mut wallet = wallet_for_tests().await;
+        let account_id = wallet.create_new_account_private(ChainIndex::root());
+        let commitment = wallet.get_private_account_commitment(&account_id).unwrap();
+        wallet
+            .storage
+            .insert_private_commitment_block(commitment, 10);
+        wallet.last_synced_block = 15;
+        assert_eq!(wallet.get_private_account_age(&account_id), Some(5));
+
+        let data = produce_data_for_storage(
+            &wallet.storage.user_data,
+            wallet.last_synced_block,
+            &wallet.storage.watched_account_ids,
+            &wallet.storage.tx_history,
+            &wallet.storage.private_commitment_blocks,
+        );
+        let restored: PersistentStorage =
+            serde_json::from_slice(&serde_json::to_vec(&data).unwrap()).unwrap();
+
+        let mut restored_wallet = wallet_for_tests().await;
+        restored_wallet.storage = WalletChainStore::new(
+            restored_wallet.storage.wallet_config.clone(),
+            restored.accounts,
+        )
+        .unwrap();
+        restored_wallet.storage.private_commitment_blocks =
+            restored.private_commitment_blocks.into_iter().collect();
+        restored_wallet.last_synced_block = restored.last_synced_block;
+
+        assert_eq!(
+            restored_wallet.get_private_account_age(&account_id),
+            Some(5)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_import_account_roundtrip() {
+        let mut wallet = wallet_for_tests().await;
+        let account_id = wallet.create_new_account_public(ChainIndex::root());
+        let signing_key = wallet
+            .get_account_public_signing_key(&account_id)
+            .unwrap()
+            .clone();
+
+        let exported = wallet
+            .export_account(account_id, "export password")
+            .unwrap();
+        assert!(wallet.import_account(&exported, "wrong password").is_err());
+
+        let mut other_wallet = wallet_for_tests().await;
+        let imported_account_id = other_wallet
+            .import_account(&exported, "export password")
+            .unwrap();
+
+        assert_eq!(imported_account_id, account_id);
+        assert_eq!(
+            other_wallet.get_account_public_signing_key(&account_id),
+            Some(&signing_key)
+        );
+        assert!(
+            other_wallet
+                .list_accounts()
+                .contains(&(account_id, AccountPrivacyKind::Public))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_unknown_account_fails() {
+        let wallet = wallet_for_tests().await;
+
+        assert!(
+            wallet
+                .export_account(AccountId::new([7; 32]), "password")
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_block_records_tx_history() {
+        let mut wallet = wallet_for_tests().await;
+        let account_id = wallet.create_new_account_public(ChainIndex::root());
+        let signing_key = wallet
+            .get_account_public_signing_key(&account_id)
+            .unwrap()
+            .clone();
+        let recipient_id = AccountId::new([7; 32]);
+
+        let transfer = |nonce, amount| {
+            common::test_utils::create_transaction_native_token_transfer(
+                *account_id.value(),
+                nonce,
+                *recipient_id.value(),
+                amount,
+                signing_key.clone(),
+            )
+        };
+        let unrelated_transfer = common::test_utils::create_transaction_native_token_transfer(
+            [8; 32],
+            0,
+            [9; 32],
+            1,
+            nssa::PrivateKey::try_new([8; 32]).unwrap(),
+        );
+
+        let first_transfer = transfer(0, 10);
+
+        for (block_id, transactions) in [
+            (1, vec![first_transfer.clone()]),
+            (2, vec![unrelated_transfer]),
+            (3, vec![transfer(1, 20)]),
+        ] {
+            wallet
+                .sync_block(HashableBlockData {
+                    block_id,
+                    prev_block_hash: [0; 32],
+                    timestamp: block_id * 100,
+                    transactions,
+                })
+                .unwrap();
         }
+
+        let history = wallet.storage.get_tx_history(account_id, 10, 0);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].block_height, 3);
+        assert_eq!(history[0].timestamp, 300);
+        assert_eq!(history[0].amount, Some(20));
+        assert_eq!(history[0].counterparty, Some(recipient_id));
+        assert_eq!(history[1].block_height, 1);
+        assert_eq!(history[1].amount, Some(10));
+        assert_eq!(history[1].tx_hash, hex::encode(first_transfer.hash()));
     }
 }
@@ -1,7 +1,10 @@
 use anyhow::Result;
 use clap::{CommandFactory as _, Parser as _};
 use tokio::runtime::Builder;
-use wallet::cli::{Args, OverCommand, execute_continuous_run, execute_setup, execute_subcommand};
+use wallet::{
+    cli::{Args, OverCommand, execute_continuous_run, execute_setup, execute_subcommand},
+    error::WalletError,
+};
 
 pub const NUM_THREADS: usize = 2;
 
@@ -26,8 +29,13 @@ fn main() -> Result<()> {
         if let Some(over_command) = args.command {
             match over_command {
                 OverCommand::Command(command) => {
-                    let _output = execute_subcommand(command).await?;
-                    Ok(())
+                    let result = execute_subcommand(command).await;
+                    if let Err(err) = &result
+                        && let Some(WalletError::StorageNotFound(_)) = err.downcast_ref()
+                    {
+                        eprintln!("Wallet is not set up yet, run `wallet setup` first");
+                    }
+                    result.map(|_output| ())
                 }
                 OverCommand::Setup { password } => execute_setup(password).await,
             }
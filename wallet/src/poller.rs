@@ -1,7 +1,10 @@
 use std::sync::Arc;
 
 use anyhow::Result;
-use common::{block::HashableBlockData, sequencer_client::SequencerClient};
+use common::{
+    block::HashableBlockData, rpc_primitives::requests::MAX_BLOCK_RANGE_SIZE,
+    sequencer_client::SequencerClient,
+};
 use log::{info, warn};
 
 use crate::config::WalletConfig;
@@ -23,7 +26,8 @@ impl TxPoller {
             polling_delay_millis: config.seq_poll_timeout_millis,
             polling_max_blocks_to_query: config.seq_tx_poll_max_blocks,
             polling_max_error_attempts: config.seq_poll_max_retries,
-            block_poll_max_amount: config.seq_block_poll_max_amount,
+            // Sequencer rejects larger ranges
+            block_poll_max_amount: config.seq_block_poll_max_amount.min(MAX_BLOCK_RANGE_SIZE),
             client: client.clone(),
         }
     }
@@ -1,9 +1,5 @@
 use common::{error::ExecutionFailureKind, rpc_primitives::requests::SendTxResponse};
-use nssa::{
-    AccountId, PublicTransaction,
-    program::Program,
-    public_transaction::{Message, WitnessSet},
-};
+use nssa::{AccountId, program::Program, public_transaction::Message};
 
 use super::NativeTokenTransfer;
 
@@ -26,7 +22,7 @@ impl NativeTokenTransfer<'_> {
             let account_ids = vec![from, to];
             let program_id = Program::authenticated_transfer_program().id();
             let message =
-                Message::try_new(program_id, account_ids, nonces, balance_to_move).unwrap();
+                Message::try_new(program_id, account_ids, nonces, balance_to_move, 0).unwrap();
 
             let signing_key = self.0.storage.user_data.get_pub_account_signing_key(&from);
 
@@ -34,9 +30,15 @@ impl NativeTokenTransfer<'_> {
                 return Err(ExecutionFailureKind::KeyNotFoundError);
             };
 
-            let witness_set = WitnessSet::for_message(&message, &[signing_key]);
+            let tx = self
+                .0
+                .sign_public_transaction(message, &[signing_key])
+                .await?;
 
-            let tx = PublicTransaction::new(message, witness_set);
+            // Sender pays the fee on top of the transferred amount
+            if balance - balance_to_move < tx.message().fee() {
+                return Err(ExecutionFailureKind::InsufficientFundsError);
+            }
 
             Ok(self.0.sequencer_client.send_tx_public(tx).await?)
         } else {
@@ -55,7 +57,7 @@ impl NativeTokenTransfer<'_> {
         let instruction: u128 = 0;
         let account_ids = vec![from];
         let program_id = Program::authenticated_transfer_program().id();
-        let message = Message::try_new(program_id, account_ids, nonces, instruction).unwrap();
+        let message = Message::try_new(program_id, account_ids, nonces, instruction, 0).unwrap();
 
         let signing_key = self.0.storage.user_data.get_pub_account_signing_key(&from);
 
@@ -63,9 +65,10 @@ impl NativeTokenTransfer<'_> {
             return Err(ExecutionFailureKind::KeyNotFoundError);
         };
 
-        let witness_set = WitnessSet::for_message(&message, &[signing_key]);
-
-        let tx = PublicTransaction::new(message, witness_set);
+        let tx = self
+            .0
+            .sign_public_transaction(message, &[signing_key])
+            .await?;
 
         Ok(self.0.sequencer_client.send_tx_public(tx).await?)
     }
@@ -15,12 +15,16 @@ impl Pinata<'_> {
     ) -> Result<SendTxResponse, ExecutionFailureKind> {
         let account_ids = vec![pinata_account_id, winner_account_id];
         let program_id = nssa::program::Program::pinata().id();
-        let message =
-            nssa::public_transaction::Message::try_new(program_id, account_ids, vec![], solution)
-                .unwrap();
+        let message = nssa::public_transaction::Message::try_new(
+            program_id,
+            account_ids,
+            vec![],
+            solution,
+            0,
+        )
+        .unwrap();
 
-        let witness_set = nssa::public_transaction::WitnessSet::for_message(&message, &[]);
-        let tx = nssa::PublicTransaction::new(message, witness_set);
+        let tx = self.0.sign_public_transaction(message, &[]).await?;
 
         Ok(self.0.sequencer_client.send_tx_public(tx).await?)
     }
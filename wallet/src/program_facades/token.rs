@@ -28,12 +28,11 @@ impl Token<'_> {
             account_ids,
             vec![],
             instruction,
+            0,
         )
         .unwrap();
 
-        let witness_set = nssa::public_transaction::WitnessSet::for_message(&message, &[]);
-
-        let tx = nssa::PublicTransaction::new(message, witness_set);
+        let tx = self.0.sign_public_transaction(message, &[]).await?;
 
         Ok(self.0.sequencer_client.send_tx_public(tx).await?)
     }
@@ -87,6 +86,7 @@ impl Token<'_> {
             account_ids,
             nonces,
             instruction,
+            0,
         )
         .unwrap();
 
@@ -98,10 +98,10 @@ impl Token<'_> {
         else {
             return Err(ExecutionFailureKind::KeyNotFoundError);
         };
-        let witness_set =
-            nssa::public_transaction::WitnessSet::for_message(&message, &[signing_key]);
-
-        let tx = nssa::PublicTransaction::new(message, witness_set);
+        let tx = self
+            .0
+            .sign_public_transaction(message, &[signing_key])
+            .await?;
 
         Ok(self.0.sequencer_client.send_tx_public(tx).await?)
     }